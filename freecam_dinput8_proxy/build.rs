@@ -0,0 +1,3 @@
+fn main() {
+    forward_dll::forward_dll("C:\\Windows\\System32\\dinput8.dll").unwrap();
+}
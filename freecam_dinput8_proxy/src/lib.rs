@@ -0,0 +1 @@
+rust_hooking_utils::dll_main!(freecam_rs::dll_attach, freecam_rs::dll_detach);
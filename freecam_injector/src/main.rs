@@ -0,0 +1,210 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Context;
+use freecam_rs::control::{self, ControlEvent};
+use windows::core::HSTRING;
+use windows::Win32::Foundation::{CloseHandle, HANDLE, WAIT_OBJECT_0};
+use windows::Win32::System::Threading::{
+    OpenEventW, SetEvent, WaitForSingleObject, EVENT_MODIFY_STATE, SYNCHRONIZATION_ACCESS_RIGHTS,
+    SYNCHRONIZATION_SYNCHRONIZE,
+};
+
+use crate::process::TargetProcess;
+
+mod process;
+
+const USAGE: &str = "Usage: freecam_injector [inject|log|reload|detach] [--dll <path>] [--process <name>]
+
+Commands:
+  inject   Inject the freecam into the running game and stream its log until the game exits (default)
+  log      Stream the log of the freecam already running in the game
+  reload   Reload the freecam's config
+  detach   Stop the freecam, and unload its DLL if it was injected
+
+Options:
+  --dll <path>       The freecam DLL, `version.dll` next to the injector by default
+  --process <name>   The game's executable, `medieval2.exe` or `kingdoms.exe` by default";
+
+const DEFAULT_DLL_NAME: &str = "version.dll";
+const DEFAULT_PROCESS_NAMES: [&str; 2] = ["medieval2.exe", "kingdoms.exe"];
+/// How often the log file is checked for new lines.
+const LOG_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// How long the freecam gets to remove its patches after a detach was requested.
+const DETACH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Inject,
+    Log,
+    Reload,
+    Detach,
+}
+
+struct Args {
+    command: Command,
+    /// Always an absolute path, as that's how the module shows up in the game.
+    dll: PathBuf,
+    process_names: Vec<String>,
+}
+
+/// Companion to the proxy DLLs for injecting the freecam into an already running game, and controlling it afterwards.
+fn main() -> anyhow::Result<()> {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let game = TargetProcess::find(&args.process_names)?;
+    println!("Found {} (pid {})", game.name, game.pid);
+
+    match args.command {
+        Command::Inject => {
+            if !args.dll.is_file() {
+                anyhow::bail!("{:?} doesn't exist, pass the freecam DLL with `--dll`", args.dll);
+            }
+
+            let log = control::log_path(dll_directory(&args.dll)?)?;
+            // The freecam starts a fresh log once it initialises, which is detected as the file shrinking.
+            let existing = std::fs::metadata(&log).map_or(0, |meta| meta.len());
+            game.inject(&args.dll)?;
+            println!("Injected {:?}", args.dll);
+
+            stream_log(&game, &log, existing)
+        }
+        Command::Log => stream_log(&game, &control::log_path(dll_directory(&args.dll)?)?, 0),
+        Command::Reload => {
+            signal(game.pid, ControlEvent::Reload)?;
+            println!("Requested a config reload, see the log for the result");
+            Ok(())
+        }
+        Command::Detach => detach(&game, &args.dll),
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> anyhow::Result<Args> {
+    let mut command = None;
+    let mut dll = None;
+    let mut process_names = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dll" => dll = Some(PathBuf::from(args.next().context("Missing the path after `--dll`")?)),
+            "--process" => process_names.push(args.next().context("Missing the name after `--process`")?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ if command.is_some() => anyhow::bail!("Unexpected argument `{}`", arg),
+            "inject" => command = Some(Command::Inject),
+            "log" => command = Some(Command::Log),
+            "reload" => command = Some(Command::Reload),
+            "detach" => command = Some(Command::Detach),
+            _ => anyhow::bail!("Unknown command `{}`", arg),
+        }
+    }
+
+    let dll = match dll {
+        Some(dll) => dll,
+        None => std::env::current_exe()?
+            .parent()
+            .context("Executable is in root")?
+            .join(DEFAULT_DLL_NAME),
+    };
+    if process_names.is_empty() {
+        process_names = DEFAULT_PROCESS_NAMES.iter().map(|name| name.to_string()).collect();
+    }
+
+    Ok(Args {
+        command: command.unwrap_or(Command::Inject),
+        dll: std::path::absolute(dll)?,
+        process_names,
+    })
+}
+
+fn dll_directory(dll: &Path) -> anyhow::Result<&Path> {
+    dll.parent().context("DLL is in root")
+}
+
+/// Print everything written to the `log` after the first `position` bytes, until the game exits.
+///
+/// The file is reopened on every poll, as the freecam moves it aside whenever the log is rotated.
+fn stream_log(game: &TargetProcess, log: &Path, mut position: u64) -> anyhow::Result<()> {
+    println!("Streaming {:?}, press Ctrl+C to stop", log);
+    let mut stdout = std::io::stdout();
+
+    while game.is_alive() {
+        if let Ok(mut file) = std::fs::File::open(log) {
+            let len = file.metadata()?.len();
+            if len < position {
+                // A new log was started.
+                position = 0;
+            }
+
+            if len > position {
+                let mut new = Vec::new();
+                file.seek(SeekFrom::Start(position))?;
+                file.take(len - position).read_to_end(&mut new)?;
+                stdout.write_all(&new)?;
+                stdout.flush()?;
+                position += new.len() as u64;
+            }
+        }
+
+        std::thread::sleep(LOG_POLL_INTERVAL);
+    }
+
+    println!("The game exited");
+    Ok(())
+}
+
+/// Stop the freecam, and unload the `dll` if the game loaded it from that path.
+///
+/// A proxy DLL in the game's folder (which the game itself loaded) is never at the `dll` path, unless the injector was
+/// placed in the game's folder as well. `FreeLibrary` doesn't unload statically imported DLLs, so the game keeps its
+/// proxy either way.
+fn detach(game: &TargetProcess, dll: &Path) -> anyhow::Result<()> {
+    // Opened before requesting the detach, as the freecam closes its end once it stopped.
+    let stopped = open_event(game.pid, ControlEvent::Stopped, SYNCHRONIZATION_SYNCHRONIZE)?;
+    let result = signal(game.pid, ControlEvent::Detach).and_then(|_| {
+        if unsafe { WaitForSingleObject(stopped, DETACH_TIMEOUT.as_millis() as u32) } != WAIT_OBJECT_0 {
+            anyhow::bail!("The freecam didn't stop within {:?}", DETACH_TIMEOUT);
+        }
+        Ok(())
+    });
+    unsafe {
+        let _ = CloseHandle(stopped);
+    }
+    result?;
+    println!("The freecam stopped and removed its patches");
+
+    match game.find_module(dll)? {
+        Some(module) => {
+            game.unload(module)?;
+            println!("Unloaded {:?}", dll);
+        }
+        None => println!("{:?} isn't loaded by the game, leaving the DLL in place", dll),
+    }
+
+    Ok(())
+}
+
+/// Set one of the freecam's [ControlEvent]s.
+fn signal(pid: u32, event: ControlEvent) -> anyhow::Result<()> {
+    let handle = open_event(pid, event, EVENT_MODIFY_STATE)?;
+    let result = unsafe { SetEvent(handle) };
+    unsafe {
+        let _ = CloseHandle(handle);
+    }
+
+    Ok(result?)
+}
+
+fn open_event(pid: u32, event: ControlEvent, access: SYNCHRONIZATION_ACCESS_RIGHTS) -> anyhow::Result<HANDLE> {
+    unsafe { OpenEventW(access, false, &HSTRING::from(event.name(pid))) }
+        .with_context(|| format!("Failed to reach the freecam in pid {}, is it running?", pid))
+}
@@ -0,0 +1,214 @@
+//! The game process the freecam is injected into, see [TargetProcess].
+
+use std::ffi::c_void;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Context;
+use windows::core::{s, w, PCSTR};
+use windows::Win32::Foundation::{CloseHandle, HANDLE, WAIT_OBJECT_0, WAIT_TIMEOUT};
+use windows::Win32::System::Diagnostics::Debug::WriteProcessMemory;
+use windows::Win32::System::Diagnostics::ToolHelp::{
+    CreateToolhelp32Snapshot, Module32FirstW, Module32NextW, Process32FirstW, Process32NextW, MODULEENTRY32W,
+    PROCESSENTRY32W, TH32CS_SNAPMODULE, TH32CS_SNAPMODULE32, TH32CS_SNAPPROCESS,
+};
+use windows::Win32::System::LibraryLoader::{GetModuleHandleW, GetProcAddress};
+use windows::Win32::System::Memory::{
+    VirtualAllocEx, VirtualFreeEx, MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_READWRITE,
+};
+use windows::Win32::System::Threading::{
+    CreateRemoteThread, GetExitCodeThread, OpenProcess, WaitForSingleObject, PROCESS_CREATE_THREAD,
+    PROCESS_QUERY_INFORMATION, PROCESS_SYNCHRONIZE, PROCESS_VM_OPERATION, PROCESS_VM_READ, PROCESS_VM_WRITE,
+};
+
+/// How long a function called in the game (e.g., `LoadLibraryW`) may take.
+const REMOTE_CALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+type ThreadStartFn = unsafe extern "system" fn(*mut c_void) -> u32;
+
+pub struct TargetProcess {
+    pub pid: u32,
+    /// The file name of the game's executable.
+    pub name: String,
+    handle: HANDLE,
+}
+
+impl TargetProcess {
+    /// Open the first running process whose executable is called one of `names` (case-insensitive).
+    pub fn find(names: &[String]) -> anyhow::Result<Self> {
+        let mut matching = running_processes()?
+            .into_iter()
+            .filter(|(_, name)| names.iter().any(|allowed| allowed.eq_ignore_ascii_case(name)));
+        let (pid, name) = matching
+            .next()
+            .with_context(|| format!("None of {:?} are running", names))?;
+
+        let others = matching.map(|(pid, _)| pid).collect::<Vec<_>>();
+        if !others.is_empty() {
+            println!(
+                "Multiple games are running, using pid {} and ignoring {:?}",
+                pid, others
+            );
+        }
+
+        let handle = unsafe {
+            OpenProcess(
+                PROCESS_CREATE_THREAD
+                    | PROCESS_QUERY_INFORMATION
+                    | PROCESS_VM_OPERATION
+                    | PROCESS_VM_READ
+                    | PROCESS_VM_WRITE
+                    | PROCESS_SYNCHRONIZE,
+                false,
+                pid,
+            )
+        }
+        .with_context(|| format!("Failed to open {} (pid {}), try running as administrator", name, pid))?;
+
+        Ok(Self { pid, name, handle })
+    }
+
+    pub fn is_alive(&self) -> bool {
+        unsafe { WaitForSingleObject(self.handle, 0) == WAIT_TIMEOUT }
+    }
+
+    /// Load the DLL at `dll` (an absolute path) in the game through `LoadLibraryW`.
+    pub fn inject(&self, dll: &Path) -> anyhow::Result<()> {
+        let path = dll
+            .as_os_str()
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect::<Vec<_>>();
+        let size = path.len() * std::mem::size_of::<u16>();
+
+        unsafe {
+            let remote_path = VirtualAllocEx(self.handle, None, size, MEM_COMMIT | MEM_RESERVE, PAGE_READWRITE);
+            if remote_path.is_null() {
+                return Err(windows::core::Error::from_win32()).context("Failed to allocate memory in the game");
+            }
+
+            let result = WriteProcessMemory(self.handle, remote_path, path.as_ptr().cast(), size, None)
+                .context("Failed to write the DLL path to the game")
+                .and_then(|_| self.call(kernel32_fn(s!("LoadLibraryW"))?, remote_path));
+            let _ = VirtualFreeEx(self.handle, remote_path, 0, MEM_RELEASE);
+
+            // The exit code is the (truncated) module handle, `0` if the DLL couldn't be loaded.
+            if result? == 0 {
+                anyhow::bail!(
+                    "The game failed to load {:?}, was it built for `i686-pc-windows-msvc`?",
+                    dll
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The handle of the module loaded from `dll` (an absolute path) in the game, if any.
+    pub fn find_module(&self, dll: &Path) -> anyhow::Result<Option<usize>> {
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPMODULE | TH32CS_SNAPMODULE32, self.pid)?;
+            let mut entry = MODULEENTRY32W {
+                dwSize: std::mem::size_of::<MODULEENTRY32W>() as u32,
+                ..Default::default()
+            };
+            let mut found = None;
+            let mut next = Module32FirstW(snapshot, &mut entry);
+
+            while next.is_ok() {
+                let path = from_wide(&entry.szExePath);
+                if path.eq_ignore_ascii_case(&dll.to_string_lossy()) {
+                    found = Some(entry.hModule.0 as usize);
+                    break;
+                }
+                next = Module32NextW(snapshot, &mut entry);
+            }
+
+            let _ = CloseHandle(snapshot);
+            Ok(found)
+        }
+    }
+
+    /// Unload a module found with [Self::find_module] through `FreeLibrary`.
+    pub fn unload(&self, module: usize) -> anyhow::Result<()> {
+        if unsafe { self.call(kernel32_fn(s!("FreeLibrary"))?, module as *const c_void)? } == 0 {
+            anyhow::bail!("The game failed to unload the module at {:#X}", module);
+        }
+
+        Ok(())
+    }
+
+    /// Run `function` with the given `parameter` on a new thread in the game, returning its exit code.
+    ///
+    /// # Safety
+    /// `function` has to be a valid thread start routine in the game, e.g. a `kernel32` function.
+    unsafe fn call(&self, function: usize, parameter: *const c_void) -> anyhow::Result<u32> {
+        if cfg!(target_pointer_width = "64") {
+            // `kernel32` functions are at different addresses in the (32-bit) game.
+            anyhow::bail!("The injector has to be built for `i686-pc-windows-msvc` to call into the game");
+        }
+
+        let start = std::mem::transmute::<usize, ThreadStartFn>(function);
+        let thread = CreateRemoteThread(self.handle, None, 0, Some(start), Some(parameter), 0, None)
+            .context("Failed to start a thread in the game")?;
+        let waited = WaitForSingleObject(thread, REMOTE_CALL_TIMEOUT.as_millis() as u32);
+        let mut exit_code = 0;
+        let result = GetExitCodeThread(thread, &mut exit_code);
+        let _ = CloseHandle(thread);
+
+        if waited != WAIT_OBJECT_0 {
+            anyhow::bail!("The thread in the game didn't finish within {:?}", REMOTE_CALL_TIMEOUT);
+        }
+        result?;
+
+        Ok(exit_code)
+    }
+}
+
+impl Drop for TargetProcess {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseHandle(self.handle);
+        }
+    }
+}
+
+/// The pid and executable name of every running process.
+fn running_processes() -> anyhow::Result<Vec<(u32, String)>> {
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0)?;
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut processes = Vec::new();
+        let mut next = Process32FirstW(snapshot, &mut entry);
+
+        while next.is_ok() {
+            processes.push((entry.th32ProcessID, from_wide(&entry.szExeFile)));
+            next = Process32NextW(snapshot, &mut entry);
+        }
+
+        let _ = CloseHandle(snapshot);
+        Ok(processes)
+    }
+}
+
+/// The address of a `kernel32` function, which is the same in every (32-bit) process as `kernel32` is always loaded at
+/// the same address.
+fn kernel32_fn(name: PCSTR) -> anyhow::Result<usize> {
+    unsafe {
+        let kernel32 = GetModuleHandleW(w!("kernel32.dll"))?;
+        let function = GetProcAddress(kernel32, name).context("Missing kernel32 function")?;
+
+        Ok(function as usize)
+    }
+}
+
+/// Convert a nul-terminated UTF-16 buffer.
+fn from_wide(buffer: &[u16]) -> String {
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+
+    String::from_utf16_lossy(&buffer[..len])
+}
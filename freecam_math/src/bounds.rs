@@ -0,0 +1,102 @@
+use std::f32::consts::PI;
+
+use crate::coords::WorldPos;
+
+/// The maximum pitch (in either direction) of the camera, slightly less than straight up/down.
+pub const MAX_PITCH: f32 = (PI / 2.) * 0.9;
+/// The steepest pitch the camera can ever have, just short of looking straight up/down.
+///
+/// The game derives the camera's orientation from its target, so looking exactly vertically would lose the yaw.
+pub const VERTICAL_PITCH: f32 = PI / 2. - 1e-3;
+/// The maximum absolute `x`/`y` coordinate the camera can move to.
+pub const MAX_HORIZONTAL: f32 = 900.;
+/// The default maximum `z` coordinate the camera can move to.
+pub const MAX_HEIGHT: f32 = 2400.;
+
+/// Clamp the given pitch to [MAX_PITCH].
+///
+/// Note that a `NaN` pitch results in `-MAX_PITCH`.
+pub fn clamp_pitch(pitch: f32) -> f32 {
+    clamp_pitch_to(pitch, -MAX_PITCH, MAX_PITCH)
+}
+
+/// Clamp the given pitch to `min..=max`, which is itself limited to [VERTICAL_PITCH].
+///
+/// Note that a `NaN` pitch results in `min`.
+pub fn clamp_pitch_to(pitch: f32, min: f32, max: f32) -> f32 {
+    let (min, max) = (min.max(-VERTICAL_PITCH), max.min(VERTICAL_PITCH));
+
+    max.min(min.max(pitch))
+}
+
+/// Clamp the given position to the playable map area, with no minimum altitude and a maximum of [MAX_HEIGHT].
+///
+/// `NaN` horizontal coordinates result in `-MAX_HORIZONTAL`, a `NaN` height results in [MAX_HEIGHT].
+pub fn clamp_to_map_bounds(pos: WorldPos) -> WorldPos {
+    clamp_to_bounds(pos, f32::NEG_INFINITY, MAX_HEIGHT)
+}
+
+/// Clamp the given position to the playable map area, and its height to `min_altitude..=max_altitude`.
+///
+/// `NaN` horizontal coordinates result in `-MAX_HORIZONTAL`, a `NaN` height results in `max_altitude`.
+pub fn clamp_to_bounds(pos: WorldPos, min_altitude: f32, max_altitude: f32) -> WorldPos {
+    WorldPos {
+        x: MAX_HORIZONTAL.min((-MAX_HORIZONTAL).max(pos.x)),
+        y: MAX_HORIZONTAL.min((-MAX_HORIZONTAL).max(pos.y)),
+        z: min_altitude.max(max_altitude.min(pos.z)),
+    }
+}
+
+/// The factor (`0..=1`) to scale the camera's downward velocity with when it's `height` above the lowest point it may go.
+///
+/// Eases the camera to a stop over the final `slowdown_distance`, instead of halting abruptly once it hits the ground.
+/// A `slowdown_distance` of `0` disables the slowdown, as does a `NaN` height.
+pub fn ground_approach_factor(height: f32, slowdown_distance: f32) -> f32 {
+    let factor = height / slowdown_distance;
+
+    if slowdown_distance > 0. && !factor.is_nan() {
+        factor.clamp(0., 1.)
+    } else {
+        1.
+    }
+}
+
+/// Slows the camera down within a band of height above the ground, for precise low-level shots without having to hold
+/// the slow key.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LandingZone {
+    /// How far above the ground the slowdown starts.
+    pub height: f32,
+    /// The speed multiplier right at the ground, rising linearly to `1.0` at the top of the zone.
+    pub min_multiplier: f32,
+}
+
+impl LandingZone {
+    /// The factor (`min_multiplier..=1`) to scale the camera's speed with when it's `height` above the ground.
+    ///
+    /// A non-positive zone `height` (or `NaN` input) doesn't slow the camera down.
+    pub fn speed_multiplier(&self, height: f32) -> f32 {
+        let progress = height / self.height;
+
+        if self.height > 0. && !progress.is_nan() && !self.min_multiplier.is_nan() {
+            let min_multiplier = self.min_multiplier.clamp(0., 1.);
+            min_multiplier + (1. - min_multiplier) * progress.clamp(0., 1.)
+        } else {
+            1.
+        }
+    }
+}
+
+/// How strongly (`0..=1`) the camera should follow a change in terrain height while it's `height` above the ground.
+///
+/// Halves for every `half_height` above the ground, so low flights hug the terrain while high flights barely react to
+/// hills. A non-positive `half_height` (or `NaN` input) always follows the terrain fully.
+pub fn terrain_follow_factor(height: f32, half_height: f32) -> f32 {
+    let factor = (-height.max(0.) / half_height).exp2();
+
+    if half_height > 0. && !factor.is_nan() {
+        factor.clamp(0., 1.)
+    } else {
+        1.
+    }
+}
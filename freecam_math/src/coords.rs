@@ -0,0 +1,189 @@
+//! The game stores its coordinates in `x, z, y` order (with `z` being the height), while most of our math is far easier
+//! to reason about in `x, y, z` order.
+//!
+//! All camera math should happen on [WorldPos], only converting from/to [GamePos] when reading/writing game memory.
+
+use std::f32::consts::{PI, TAU};
+use std::ops::{Add, Mul, Sub};
+
+/// A position in intuitive world space, `x`/`y` are the horizontal axes while `z` is up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct WorldPos {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// A position in the game's memory layout, `x, z, y`.
+///
+/// Should only be used for reading/writing game memory, see [WorldPos] for everything else.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[repr(C)]
+pub struct GamePos {
+    pub x: f32,
+    pub z: f32,
+    pub y: f32,
+}
+
+impl WorldPos {
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn distance(&self, other: &WorldPos) -> f32 {
+        (*other - *self).length()
+    }
+
+    pub fn length(&self) -> f32 {
+        (self.x.powi(2) + self.y.powi(2) + self.z.powi(2)).sqrt()
+    }
+
+    /// Whether none of the coordinates are `NaN` or infinite.
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+
+    /// Return a vector with the same direction but a length of `1.0`, or `None` if this is a zero vector.
+    pub fn normalized(&self) -> Option<WorldPos> {
+        let length = self.length();
+
+        (length > f32::EPSILON).then(|| *self * (1. / length))
+    }
+
+    /// Linearly interpolate between `self` (`t = 0.0`) and `other` (`t = 1.0`).
+    pub fn lerp(&self, other: &WorldPos, t: f32) -> WorldPos {
+        *self + (*other - *self) * t
+    }
+
+    /// Calculate the `(pitch, yaw)` needed to look from `self` towards `target`.
+    ///
+    /// Returns `0.0` for either if they can't be determined (e.g., `self == target`).
+    pub fn pitch_yaw_towards(&self, target: &WorldPos) -> (f32, f32) {
+        let length = self.distance(target);
+
+        let mut pitch = ((target.z - self.z) / length).asin();
+        let mut yaw = ((target.y - self.y) / length).atan2((target.x - self.x) / length);
+
+        if pitch.is_nan() {
+            pitch = 0.;
+        }
+        if yaw.is_nan() {
+            yaw = 0.;
+        }
+
+        (pitch, yaw)
+    }
+
+    /// Calculate the point `distance` away from `self` in the direction given by `pitch` and `yaw`.
+    pub fn look_target(&self, pitch: f32, yaw: f32, distance: f32) -> WorldPos {
+        WorldPos {
+            x: (yaw.cos() * pitch.cos() * distance) + self.x,
+            y: (yaw.sin() * pitch.cos() * distance) + self.y,
+            z: (pitch.sin() * distance) + self.z,
+        }
+    }
+}
+
+/// The shortest signed rotation (in radians) from `from` to `to`, in the range `-PI..PI`.
+pub fn angle_difference(from: f32, to: f32) -> f32 {
+    (to - from + PI).rem_euclid(TAU) - PI
+}
+
+impl Add for WorldPos {
+    type Output = WorldPos;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        WorldPos::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl Sub for WorldPos {
+    type Output = WorldPos;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        WorldPos::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl Mul<f32> for WorldPos {
+    type Output = WorldPos;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        WorldPos::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl From<GamePos> for WorldPos {
+    fn from(value: GamePos) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+            z: value.z,
+        }
+    }
+}
+
+impl From<WorldPos> for GamePos {
+    fn from(value: WorldPos) -> Self {
+        Self {
+            x: value.x,
+            z: value.z,
+            y: value.y,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_pos_memory_layout() {
+        let game = GamePos::from(WorldPos::new(1., 2., 3.));
+        let raw: [f32; 3] = unsafe { std::mem::transmute(game) };
+
+        assert_eq!(raw, [1., 3., 2.]);
+    }
+
+    #[test]
+    fn conversion_round_trip() {
+        let world = WorldPos::new(-10.5, 300., 42.);
+
+        assert_eq!(WorldPos::from(GamePos::from(world)), world);
+    }
+
+    #[test]
+    fn is_finite() {
+        assert!(WorldPos::new(1., -2., 3.).is_finite());
+        assert!(!WorldPos::new(1., f32::NAN, 3.).is_finite());
+        assert!(!WorldPos::new(f32::INFINITY, 2., 3.).is_finite());
+    }
+
+    #[test]
+    fn pitch_yaw_round_trip() {
+        let camera = WorldPos::new(100., -50., 20.);
+        let (pitch, yaw) = (-0.4, 2.1);
+        let target = camera.look_target(pitch, yaw, 1000.);
+        let (new_pitch, new_yaw) = camera.pitch_yaw_towards(&target);
+
+        assert!((pitch - new_pitch).abs() < 1e-4);
+        assert!((yaw - new_yaw).abs() < 1e-4);
+    }
+
+    #[test]
+    fn lerp_and_normalize() {
+        let a = WorldPos::new(0., 0., 0.);
+        let b = WorldPos::new(10., 0., 0.);
+
+        assert_eq!(a.lerp(&b, 0.5), WorldPos::new(5., 0., 0.));
+        assert_eq!(b.normalized(), Some(WorldPos::new(1., 0., 0.)));
+        assert_eq!(a.normalized(), None);
+    }
+
+    #[test]
+    fn pitch_yaw_same_position() {
+        let camera = WorldPos::new(1., 1., 1.);
+
+        assert_eq!(camera.pitch_yaw_towards(&camera), (0., 0.));
+    }
+}
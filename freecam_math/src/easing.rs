@@ -0,0 +1,27 @@
+/// Easing curves for interpolating between two camera states.
+///
+/// All curves map `0.0 -> 0.0` and `1.0 -> 1.0`.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Easing {
+    Linear,
+    /// Starts slow, ends fast.
+    EaseIn,
+    /// Starts fast, ends slow.
+    EaseOut,
+    /// Starts and ends slow.
+    EaseInOut,
+}
+
+impl Easing {
+    /// Apply the easing curve to `t`, which is clamped to the range `0..=1`.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1. - (1. - t) * (1. - t),
+            Easing::EaseInOut => t * t * (3. - 2. * t),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::f32::consts::PI;
+
+/// The cutoff frequency (Hz) used for the speed estimate of the [LookFilter::OneEuro] filter.
+const DERIVATIVE_CUTOFF: f32 = 1.0;
+
+/// A filter for raw look (mouse) deltas, applied before they become acceleration.
+///
+/// Unlike the `look_smoothing` this doesn't add inertia, it only removes jitter from noisy sensors.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum LookFilter {
+    /// Use the raw deltas.
+    None,
+    /// Exponential smoothing, `smoothing` is how much of the previous delta is kept each tick, in the range `0..1`.
+    LowPass { smoothing: f32 },
+    /// The 1€ filter, smooths slow movements heavily while keeping fast movements responsive.
+    ///
+    /// `min_cutoff` (in Hz) sets the smoothing at low speeds, lower values smooth more.
+    /// `beta` sets how quickly the smoothing drops off as the mouse moves faster.
+    OneEuro { min_cutoff: f32, beta: f32 },
+}
+
+/// The filter state for a single input axis, see [LookFilter].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AxisFilter {
+    previous: Option<f32>,
+    derivative: f32,
+}
+
+impl AxisFilter {
+    /// Forget all previous samples, the next sample will pass through unfiltered.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Filter the next `raw` sample, `dt` being the seconds since the previous sample.
+    ///
+    /// The result always lies between the previous filtered value and `raw`.
+    pub fn filter(&mut self, filter: LookFilter, raw: f32, dt: f32) -> f32 {
+        let Some(previous) = self.previous else {
+            self.previous = Some(raw);
+            return raw;
+        };
+
+        let filtered = match filter {
+            LookFilter::None => raw,
+            LookFilter::LowPass { smoothing } => lerp(raw, previous, smoothing.clamp(0., 1.)),
+            LookFilter::OneEuro { min_cutoff, beta } => {
+                if dt.is_nan() || dt <= 0. {
+                    return previous;
+                }
+
+                let derivative = (raw - previous) / dt;
+                self.derivative = lerp(self.derivative, derivative, smoothing_factor(DERIVATIVE_CUTOFF, dt));
+                let cutoff = min_cutoff + beta * self.derivative.abs();
+
+                lerp(previous, raw, smoothing_factor(cutoff, dt))
+            }
+        };
+
+        self.previous = Some(filtered);
+        filtered
+    }
+}
+
+/// The weight of a new sample for an exponential filter with the given `cutoff` frequency, in the range `0..=1`.
+fn smoothing_factor(cutoff: f32, dt: f32) -> f32 {
+    let tau = 1. / (2. * PI * cutoff);
+    let alpha = 1. / (1. + tau / dt);
+
+    if alpha.is_nan() {
+        1.
+    } else {
+        alpha.clamp(0., 1.)
+    }
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
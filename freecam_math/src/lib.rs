@@ -0,0 +1,13 @@
+//! Platform independent camera math used by the freecam.
+//!
+//! Kept separate from `freecam_rs` so it can be tested without needing a Windows target or a running game.
+
+pub mod bounds;
+pub mod coords;
+pub mod easing;
+pub mod filter;
+pub mod scroll;
+pub mod shake;
+pub mod smoothing;
+pub mod snap;
+pub mod velocity;
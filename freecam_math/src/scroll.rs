@@ -0,0 +1,64 @@
+/// How the amount scrolled in a single tick translates into zoom velocity.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum ScrollResponse {
+    /// Every notch zooms the same amount, no matter how fast the wheel is spun.
+    Linear,
+    /// Scrolling faster zooms disproportionately further, the scrolled notches are raised to the given `exponent`.
+    ///
+    /// An `exponent` of `2.0` makes a quick flick of the wheel zoom much further than several slow notches.
+    Exponential { exponent: f32 },
+}
+
+impl ScrollResponse {
+    /// The zoom step for the given (signed, possibly fractional) amount of `notches` scrolled in a single tick.
+    ///
+    /// The magnitude is capped at `max_step`, so free-spinning wheels can't send the camera flying.
+    pub fn step(&self, notches: f32, max_step: f32) -> f32 {
+        // Checked up front, as `NaN.powf(0.)` is `1.` and would otherwise turn into a full step.
+        if notches.is_nan() || max_step.is_nan() {
+            return 0.;
+        }
+        let magnitude = match self {
+            ScrollResponse::Linear => notches.abs(),
+            ScrollResponse::Exponential { exponent } => notches.abs().powf(*exponent),
+        };
+
+        if magnitude.is_nan() {
+            0.
+        } else {
+            magnitude.min(max_step.max(0.)).copysign(notches)
+        }
+    }
+}
+
+/// Spreads zoom steps out over a short window, so single notches glide instead of jolting the camera.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScrollAccumulator {
+    pending: f32,
+}
+
+impl ScrollAccumulator {
+    /// Queue the given zoom `step`, see [ScrollResponse::step].
+    pub fn push(&mut self, step: f32) {
+        if step.is_finite() {
+            self.pending += step;
+        }
+    }
+
+    /// Release the part of the pending zoom which is due `dt` seconds after the previous release.
+    ///
+    /// Each release hands out `dt / window` of the remaining zoom, so it tapers off over roughly `window` seconds.
+    /// A `window` of `0` releases everything immediately.
+    pub fn release(&mut self, dt: f32, window: f32) -> f32 {
+        let fraction = if window > 0. && dt.is_finite() { (dt.max(0.) / window).min(1.) } else { 1. };
+
+        let released = self.pending * fraction;
+        self.pending -= released;
+        released
+    }
+
+    /// Drop any pending zoom, e.g., when the camera is teleported.
+    pub fn clear(&mut self) {
+        self.pending = 0.;
+    }
+}
@@ -0,0 +1,114 @@
+//! Procedural camera shake, see [Shake].
+//!
+//! The intensity follows the common "trauma" model: every trigger adds trauma (in the range `0..=1`), which fades out
+//! linearly, and the shake's amplitude scales with its square so small bumps stay subtle.
+
+/// How the camera moves while shaking.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ShakeMotion {
+    /// The maximum offset of the camera's position at full trauma, in world units.
+    pub amplitude: f32,
+    /// The maximum offset of the camera's pitch and yaw at full trauma, in degrees.
+    pub rotation_amplitude: f32,
+    /// Roughly how many times per second the shake changes direction.
+    pub frequency: f32,
+    /// How long full trauma takes to fade out completely, in seconds.
+    pub decay_time: f32,
+}
+
+impl Default for ShakeMotion {
+    fn default() -> Self {
+        Self {
+            amplitude: 0.3,
+            rotation_amplitude: 0.5,
+            frequency: 8.,
+            decay_time: 1.,
+        }
+    }
+}
+
+/// The offset a [Shake] adds to the camera, with the angles in radians.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize)]
+pub struct ShakeOffset {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub pitch: f32,
+    pub yaw: f32,
+}
+
+/// The state of an ongoing camera shake.
+#[derive(Debug, Clone, Default)]
+pub struct Shake {
+    trauma: f32,
+    /// The position along the noise, advancing with the [ShakeMotion::frequency].
+    time: f32,
+}
+
+impl Shake {
+    /// Add `amount` of trauma, the total is capped at `1`.
+    pub fn add_trauma(&mut self, amount: f32) {
+        if amount > 0. {
+            self.trauma = (self.trauma + amount).min(1.);
+        }
+    }
+
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    /// Advance the shake by `dt` seconds, returning the offset to apply to the camera.
+    pub fn update(&mut self, motion: &ShakeMotion, dt: f32) -> ShakeOffset {
+        if self.trauma <= 0. || !dt.is_finite() {
+            // Restart the noise with the next shake, keeping the time small enough for the noise to stay precise.
+            self.time = 0.;
+            return ShakeOffset::default();
+        }
+
+        self.time += dt.max(0.) * motion.frequency;
+        let intensity = self.trauma.powi(2);
+        self.trauma = if motion.decay_time > 0. { (self.trauma - dt / motion.decay_time).max(0.) } else { 0. };
+
+        let position = motion.amplitude * intensity;
+        let rotation = motion.rotation_amplitude.to_radians() * intensity;
+        ShakeOffset {
+            x: position * noise(self.time, 0),
+            y: position * noise(self.time, 1),
+            z: position * noise(self.time, 2),
+            pitch: rotation * noise(self.time, 3),
+            yaw: rotation * noise(self.time, 4),
+        }
+    }
+}
+
+/// One dimensional Perlin (gradient) noise in the range `-1..=1`, with each `seed` giving an independent curve.
+///
+/// Always `0.0` for non-finite `x`.
+pub fn noise(x: f32, seed: u32) -> f32 {
+    if !x.is_finite() {
+        return 0.;
+    }
+
+    let cell = x.floor();
+    let t = x - cell;
+    let cell = cell as i32;
+
+    let left = gradient(cell, seed) * t;
+    let right = gradient(cell.wrapping_add(1), seed) * (t - 1.);
+    // Quintic fade, so the curve has no visible kinks at the cell boundaries.
+    let fade = t * t * t * (t * (t * 6. - 15.) + 10.);
+
+    // The raw noise lies within `-0.5..=0.5`.
+    ((left + (right - left) * fade) * 2.).clamp(-1., 1.)
+}
+
+/// A pseudo random gradient in the range `-1..=1` for the given `cell`.
+fn gradient(cell: i32, seed: u32) -> f32 {
+    let mut hash = (cell as u32) ^ seed.wrapping_mul(0x9E37_79B9);
+    hash = (hash ^ (hash >> 16)).wrapping_mul(0x7FEB_352D);
+    hash = (hash ^ (hash >> 15)).wrapping_mul(0x846C_A68B);
+    hash ^= hash >> 16;
+
+    (hash as f32 / u32::MAX as f32) * 2. - 1.
+}
@@ -0,0 +1,104 @@
+//! How the camera's velocity decays once input stops, see [Smoother].
+//!
+//! Every model is driven by the same `Cinematic Smoothing` factors as [crate::velocity::Smoothing], so switching models
+//! keeps roughly the same amount of inertia.
+
+use crate::velocity::{Smoothing, Velocity};
+
+/// The fraction of the peak velocity at which [SmoothingModel::Linear] comes to a stop after the same number of ticks,
+/// roughly where the exponential decay becomes imperceptible.
+const LINEAR_STOP_FRACTION: f32 = 0.05;
+
+/// How a single axis slows down, see [SmoothingModels].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum SmoothingModel {
+    /// Keep the smoothing factor of the velocity every tick, fast at first and trailing off slowly.
+    #[default]
+    Exponential,
+    /// A critically damped spring, which eases into the deceleration and settles without overshooting.
+    Spring,
+    /// Slow down by a constant amount every tick, stopping completely once the exponential decay would be
+    /// imperceptible.
+    Linear,
+}
+
+/// The [SmoothingModel] for each group of axes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SmoothingModels {
+    pub horizontal: SmoothingModel,
+    pub vertical: SmoothingModel,
+    pub rotate: SmoothingModel,
+}
+
+/// The state a [SmoothingModel] keeps between ticks for a single axis.
+#[derive(Debug, Clone, Copy, Default)]
+struct AxisState {
+    /// The rate at which the velocity changes, for [SmoothingModel::Spring].
+    rate: f32,
+    /// The velocity returned by the last decay, to notice new input.
+    last: f32,
+    /// The largest speed since the axis last came to rest, for [SmoothingModel::Linear].
+    peak: f32,
+}
+
+impl AxisState {
+    fn decay(&mut self, velocity: f32, factor: f32, model: SmoothingModel) -> f32 {
+        match model {
+            SmoothingModel::Exponential => velocity * factor,
+            SmoothingModel::Spring => {
+                // The exact solution of a critically damped spring (towards zero) over one tick, with the stiffness
+                // chosen such that it settles at the same rate as the exponential decay.
+                let stiffness = -factor.max(f32::MIN_POSITIVE).ln();
+                if velocity != self.last {
+                    // New input, which starts a new spring from rest.
+                    self.rate = 0.;
+                }
+                let impulse = self.rate + stiffness * velocity;
+                self.rate = (self.rate - stiffness * impulse) * factor;
+                let next = (velocity + impulse) * factor;
+
+                // Starting from rest the spring never crosses zero, only rounding can make it do so.
+                self.last = if next * velocity <= 0. { 0. } else { next };
+                self.last
+            }
+            SmoothingModel::Linear => {
+                self.peak = self.peak.max(velocity.abs());
+                let ticks = LINEAR_STOP_FRACTION.ln() / factor.max(f32::MIN_POSITIVE).ln();
+                let step = self.peak / ticks.max(1.);
+
+                if velocity.abs() <= step {
+                    self.peak = 0.;
+                    0.
+                } else {
+                    velocity - step.copysign(velocity)
+                }
+            }
+        }
+    }
+}
+
+/// Decays a [Velocity] according to the configured [SmoothingModels].
+#[derive(Debug, Clone, Default)]
+pub struct Smoother {
+    /// In the order `x`, `y`, `z`, `pitch`, `yaw`.
+    axes: [AxisState; 5],
+}
+
+impl Smoother {
+    /// Decay the given `velocity` by one tick, the drop-in replacement for [crate::velocity::smooth_decay_velocity].
+    pub fn decay(&mut self, velocity: &mut Velocity, smoothing: &Smoothing, models: &SmoothingModels) {
+        let [x, y, z, pitch, yaw] = &mut self.axes;
+
+        velocity.x = x.decay(velocity.x, smoothing.horizontal, models.horizontal);
+        velocity.y = y.decay(velocity.y, smoothing.horizontal, models.horizontal);
+        velocity.z = z.decay(velocity.z, smoothing.vertical, models.vertical);
+        velocity.pitch = pitch.decay(velocity.pitch, smoothing.rotate, models.rotate);
+        velocity.yaw = yaw.decay(velocity.yaw, smoothing.rotate, models.rotate);
+    }
+
+    /// Forget all momentum, should be called whenever the velocity is reset.
+    pub fn reset(&mut self) {
+        self.axes = Default::default();
+    }
+}
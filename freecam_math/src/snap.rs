@@ -0,0 +1,37 @@
+/// Gently pulls the camera's pitch/yaw towards common angles once it's rotating slowly, see [AngleSnap::apply].
+///
+/// Makes it easy to end a manual rotation perfectly level with the horizon, or aligned with the map's axes.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AngleSnap {
+    /// The angles to snap to, e.g. `45.0` snaps to 0, 45, 90, etc. degrees. A pitch of `0` is level with the horizon.
+    pub step_degrees: f32,
+    /// How close (in degrees) an angle has to be to one of the snap angles before it's pulled towards it.
+    pub range_degrees: f32,
+    /// How much of the remaining distance to the snap angle is covered each tick, in the range `0..=1`.
+    pub strength: f32,
+    /// Snapping only happens while the rotational velocity (in radians per tick) is below this threshold.
+    pub velocity_threshold: f32,
+}
+
+impl AngleSnap {
+    /// Pull the `angle` (in radians) towards the nearest snap angle, given the angle's current `velocity`.
+    ///
+    /// Never moves past the snap angle, and leaves the angle as is while rotating quickly or when it's out of range.
+    pub fn apply(&self, angle: f32, velocity: f32) -> f32 {
+        let step = self.step_degrees.to_radians();
+        let rotating_slowly = velocity.abs() < self.velocity_threshold;
+
+        if !rotating_slowly || !angle.is_finite() || step.is_nan() || step <= 0. || self.strength.is_nan() {
+            return angle;
+        }
+
+        let target = (angle / step).round() * step;
+        let distance = target - angle;
+
+        if distance.abs() <= self.range_degrees.to_radians() {
+            angle + distance * self.strength.clamp(0., 1.)
+        } else {
+            angle
+        }
+    }
+}
@@ -0,0 +1,96 @@
+pub type Acceleration = Velocity;
+
+#[derive(Default, Debug, Clone, serde::Serialize)]
+pub struct Velocity {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub pitch: f32,
+    pub yaw: f32,
+}
+
+impl Velocity {
+    /// Whether none of the components are `NaN` or infinite.
+    pub fn is_finite(&self) -> bool {
+        [self.x, self.y, self.z, self.pitch, self.yaw]
+            .iter()
+            .all(|v| v.is_finite())
+    }
+}
+
+/// The `Cinematic Smoothing` factors, all in the range `0..1`.
+///
+/// Higher values mean slower velocity decay.
+#[derive(Debug, Clone, Copy)]
+pub struct Smoothing {
+    pub horizontal: f32,
+    pub vertical: f32,
+    pub rotate: f32,
+}
+
+/// Add the given `acceleration` to the `current_velocity`.
+///
+/// If `normalize` is set the positional part of the acceleration is scaled down to (at most) unit length, so moving
+/// diagonally isn't faster than moving straight. Otherwise the axes simply add up.
+pub fn calculate_next_velocity(
+    current_velocity: &mut Velocity,
+    acceleration: &Acceleration,
+    horizontal_speed: f32,
+    vertical_speed: f32,
+    smoothing: &Smoothing,
+    normalize: bool,
+) {
+    let length = if normalize {
+        (acceleration.x.powi(2) + acceleration.y.powi(2) + acceleration.z.powi(2))
+            .sqrt()
+            .max(1.)
+    } else {
+        1.
+    };
+
+    current_velocity.x += ((acceleration.x / length) * (horizontal_speed * (1. - smoothing.horizontal))) / 2.;
+    current_velocity.y += ((acceleration.y / length) * (horizontal_speed * (1. - smoothing.horizontal))) / 2.;
+    current_velocity.z += ((acceleration.z / length) * (vertical_speed * (1. - smoothing.vertical))) / 2.;
+    current_velocity.pitch += acceleration.pitch;
+    current_velocity.yaw += acceleration.yaw;
+}
+
+/// Set the positional part of the `velocity` to move exactly `distance` in the direction of the `acceleration`, for
+/// dolly shots at a constant speed. The rotation is added as in [calculate_next_velocity].
+///
+/// Nothing carries over between ticks, without any positional acceleration the camera stops immediately.
+pub fn dolly_velocity(velocity: &mut Velocity, acceleration: &Acceleration, distance: f32) {
+    let length = (acceleration.x.powi(2) + acceleration.y.powi(2) + acceleration.z.powi(2)).sqrt();
+    let scale = if length > 0. && length.is_finite() { distance / length } else { 0. };
+
+    velocity.x = acceleration.x * scale;
+    velocity.y = acceleration.y * scale;
+    velocity.z = acceleration.z * scale;
+    velocity.pitch += acceleration.pitch;
+    velocity.yaw += acceleration.yaw;
+}
+
+/// Limit the horizontal (`x`/`y`) and vertical (`z`) speed of the `velocity` to the given distances per tick.
+///
+/// The horizontal direction is kept, only its length is reduced. Limits which aren't positive numbers are ignored.
+pub fn clamp_velocity(velocity: &mut Velocity, max_horizontal: f32, max_vertical: f32) {
+    let horizontal = (velocity.x.powi(2) + velocity.y.powi(2)).sqrt();
+    if max_horizontal > 0. && horizontal > max_horizontal {
+        let scale = max_horizontal / horizontal;
+        velocity.x *= scale;
+        velocity.y *= scale;
+    }
+
+    if max_vertical > 0. && velocity.z.abs() > max_vertical {
+        velocity.z = max_vertical.copysign(velocity.z);
+    }
+}
+
+/// Decay the given velocity by the [Smoothing] factors.
+pub fn smooth_decay_velocity(velocity: &mut Velocity, smoothing: &Smoothing) {
+    velocity.x *= smoothing.horizontal;
+    velocity.y *= smoothing.horizontal;
+    velocity.z *= smoothing.vertical;
+    velocity.pitch *= smoothing.rotate;
+    velocity.yaw *= smoothing.rotate;
+}
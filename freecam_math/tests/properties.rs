@@ -0,0 +1,460 @@
+use std::f32::consts::PI;
+
+use freecam_math::bounds::{
+    clamp_pitch, clamp_pitch_to, clamp_to_bounds, clamp_to_map_bounds, ground_approach_factor, terrain_follow_factor,
+    LandingZone, MAX_HEIGHT, MAX_HORIZONTAL, MAX_PITCH, VERTICAL_PITCH,
+};
+use freecam_math::coords::{angle_difference, WorldPos};
+use freecam_math::filter::{AxisFilter, LookFilter};
+use freecam_math::scroll::{ScrollAccumulator, ScrollResponse};
+use freecam_math::shake::{noise, Shake, ShakeMotion};
+use freecam_math::smoothing::{Smoother, SmoothingModel, SmoothingModels};
+use freecam_math::snap::AngleSnap;
+use freecam_math::velocity::{
+    calculate_next_velocity, clamp_velocity, dolly_velocity, smooth_decay_velocity, Acceleration, Smoothing, Velocity,
+};
+use proptest::prelude::*;
+
+/// Any `f32`, including `NaN`, infinities, and subnormals.
+fn any_f32() -> impl Strategy<Value = f32> {
+    prop::num::f32::ANY
+}
+
+fn any_pos() -> impl Strategy<Value = WorldPos> {
+    (any_f32(), any_f32(), any_f32()).prop_map(|(x, y, z)| WorldPos::new(x, y, z))
+}
+
+fn map_pos() -> impl Strategy<Value = WorldPos> {
+    (
+        -MAX_HORIZONTAL..MAX_HORIZONTAL,
+        -MAX_HORIZONTAL..MAX_HORIZONTAL,
+        -100f32..MAX_HEIGHT,
+    )
+        .prop_map(|(x, y, z)| WorldPos::new(x, y, z))
+}
+
+/// Realistic smoothing values, values closer to `1.0` take too long to decay for [decay_converges_to_zero].
+fn look_filter() -> impl Strategy<Value = LookFilter> {
+    prop_oneof![
+        Just(LookFilter::None),
+        (0f32..1.).prop_map(|smoothing| LookFilter::LowPass { smoothing }),
+        (0.01f32..10., 0f32..1.).prop_map(|(min_cutoff, beta)| LookFilter::OneEuro { min_cutoff, beta }),
+    ]
+}
+
+fn scroll_response() -> impl Strategy<Value = ScrollResponse> {
+    prop_oneof![
+        Just(ScrollResponse::Linear),
+        any_f32().prop_map(|exponent| ScrollResponse::Exponential { exponent }),
+    ]
+}
+
+fn smoothing() -> impl Strategy<Value = Smoothing> {
+    (0f32..0.99, 0f32..0.99, 0f32..0.99).prop_map(|(horizontal, vertical, rotate)| Smoothing {
+        horizontal,
+        vertical,
+        rotate,
+    })
+}
+
+fn smoothing_models() -> impl Strategy<Value = SmoothingModels> {
+    let model = || {
+        prop_oneof![
+            Just(SmoothingModel::Exponential),
+            Just(SmoothingModel::Spring),
+            Just(SmoothingModel::Linear),
+        ]
+    };
+
+    (model(), model(), model()).prop_map(|(horizontal, vertical, rotate)| SmoothingModels {
+        horizontal,
+        vertical,
+        rotate,
+    })
+}
+
+proptest! {
+    #[test]
+    fn pitch_yaw_is_never_nan(camera in any_pos(), target in any_pos()) {
+        let (pitch, yaw) = camera.pitch_yaw_towards(&target);
+
+        prop_assert!(!pitch.is_nan());
+        prop_assert!(!yaw.is_nan());
+    }
+
+    #[test]
+    fn look_target_round_trips(camera in map_pos(), pitch in -MAX_PITCH..MAX_PITCH, yaw in -PI..PI) {
+        let target = camera.look_target(pitch, yaw, 1000.);
+        let (new_pitch, new_yaw) = camera.pitch_yaw_towards(&target);
+
+        prop_assert!((new_pitch - pitch).abs() < 1e-3, "{new_pitch} != {pitch}");
+        prop_assert!((new_yaw - yaw).abs() < 1e-3, "{new_yaw} != {yaw}");
+    }
+
+    #[test]
+    fn clamped_pitch_is_in_range(pitch in any_f32()) {
+        let clamped = clamp_pitch(pitch);
+
+        prop_assert!((-MAX_PITCH..=MAX_PITCH).contains(&clamped));
+        if (-MAX_PITCH..=MAX_PITCH).contains(&pitch) {
+            prop_assert_eq!(clamped, pitch);
+        }
+    }
+
+    #[test]
+    fn clamped_pitch_respects_limits(pitch in any_f32(), min in -PI..0f32, max in 0f32..PI) {
+        let clamped = clamp_pitch_to(pitch, min, max);
+
+        prop_assert!((-VERTICAL_PITCH..=VERTICAL_PITCH).contains(&clamped));
+        prop_assert!((min..=max).contains(&clamped));
+        if (min.max(-VERTICAL_PITCH)..=max.min(VERTICAL_PITCH)).contains(&pitch) {
+            prop_assert_eq!(clamped, pitch);
+        }
+    }
+
+    #[test]
+    fn clamped_position_is_in_bounds(pos in any_pos()) {
+        let clamped = clamp_to_map_bounds(pos);
+
+        prop_assert!((-MAX_HORIZONTAL..=MAX_HORIZONTAL).contains(&clamped.x));
+        prop_assert!((-MAX_HORIZONTAL..=MAX_HORIZONTAL).contains(&clamped.y));
+        prop_assert!(clamped.z <= MAX_HEIGHT);
+    }
+
+    #[test]
+    fn clamped_altitude_is_in_range(pos in any_pos(), min_altitude in -1000f32..1000., range in 0f32..3000.) {
+        let max_altitude = min_altitude + range;
+        let clamped = clamp_to_bounds(pos, min_altitude, max_altitude);
+
+        prop_assert!((min_altitude..=max_altitude).contains(&clamped.z), "{}", clamped.z);
+    }
+
+    #[test]
+    fn clamping_in_bounds_is_identity(pos in map_pos()) {
+        prop_assert_eq!(clamp_to_map_bounds(pos), pos);
+    }
+
+    #[test]
+    fn angle_snap_never_overshoots(
+        angle in -10f32..10.,
+        velocity in -0.01f32..0.01,
+        step_degrees in 1f32..90.,
+        range_degrees in 0f32..45.,
+        strength in 0f32..=1.,
+    ) {
+        let snap = AngleSnap { step_degrees, range_degrees, strength, velocity_threshold: 0.01 };
+        let step = step_degrees.to_radians();
+        let target = (angle / step).round() * step;
+        let snapped = snap.apply(angle, velocity);
+
+        prop_assert!((snapped - target).abs() <= (angle - target).abs() + 1e-6, "{} -> {} (target {})", angle, snapped, target);
+    }
+
+    #[test]
+    fn angle_snap_ignores_fast_rotation(angle in any_f32(), velocity in any_f32()) {
+        let snap = AngleSnap { step_degrees: 45., range_degrees: 5., strength: 0.2, velocity_threshold: 0.01 };
+        prop_assume!(velocity.is_nan() || velocity.abs() >= 0.01);
+
+        prop_assert_eq!(snap.apply(angle, velocity).to_bits(), angle.to_bits());
+    }
+
+    #[test]
+    fn ground_approach_factor_is_normalised(height in any_f32(), slowdown_distance in any_f32()) {
+        let factor = ground_approach_factor(height, slowdown_distance);
+
+        prop_assert!((0f32..=1.).contains(&factor), "{factor}");
+    }
+
+    #[test]
+    fn ground_approach_slows_down_closer_to_the_ground(
+        height in 0f32..100., closer in 0f32..1., slowdown_distance in 0.1f32..50.,
+    ) {
+        let far = ground_approach_factor(height, slowdown_distance);
+        let near = ground_approach_factor(height * closer, slowdown_distance);
+
+        prop_assert!(near <= far, "{near} > {far}");
+    }
+
+    #[test]
+    fn landing_zone_multiplier_is_bounded(height in any_f32(), zone_height in any_f32(), min_multiplier in 0f32..=1.) {
+        let zone = LandingZone { height: zone_height, min_multiplier };
+        let multiplier = zone.speed_multiplier(height);
+
+        prop_assert!((min_multiplier..=1.).contains(&multiplier), "{multiplier}");
+    }
+
+    #[test]
+    fn landing_zone_slows_down_closer_to_the_ground(
+        height in 0f32..100., closer in 0f32..1., zone_height in 0.1f32..50., min_multiplier in 0f32..=1.,
+    ) {
+        let zone = LandingZone { height: zone_height, min_multiplier };
+        let far = zone.speed_multiplier(height);
+        let near = zone.speed_multiplier(height * closer);
+
+        prop_assert!(near <= far, "{near} > {far}");
+    }
+
+    #[test]
+    fn terrain_follow_factor_is_normalised(height in any_f32(), half_height in any_f32()) {
+        let factor = terrain_follow_factor(height, half_height);
+
+        prop_assert!((0f32..=1.).contains(&factor), "{factor}");
+    }
+
+    #[test]
+    fn terrain_is_followed_less_when_higher(height in 0f32..1000., higher in 1f32..10., half_height in 0.1f32..500.) {
+        let low = terrain_follow_factor(height, half_height);
+        let high = terrain_follow_factor(height * higher, half_height);
+
+        prop_assert!(high <= low, "{high} > {low}");
+    }
+
+    #[test]
+    fn velocity_increase_is_bounded(
+        ax in -1f32..1., ay in -1f32..1., az in -1f32..1.,
+        horizontal_speed in 0f32..100., vertical_speed in 0f32..100.,
+        smoothing in smoothing(),
+    ) {
+        let mut velocity = Velocity::default();
+        let acceleration = Acceleration { x: ax, y: ay, z: az, ..Default::default() };
+
+        calculate_next_velocity(&mut velocity, &acceleration, horizontal_speed, vertical_speed, &smoothing, true);
+
+        let max_speed = horizontal_speed.max(vertical_speed) / 2.;
+        let length = (velocity.x.powi(2) + velocity.y.powi(2) + velocity.z.powi(2)).sqrt();
+        prop_assert!(length <= max_speed + 1e-3, "{length} > {max_speed}");
+    }
+
+    #[test]
+    fn dolly_moves_at_a_constant_speed(
+        ax in -1f32..1., ay in -1f32..1., az in -1f32..1.,
+        x in -100f32..100., y in -100f32..100., z in -100f32..100.,
+        distance in 0f32..100.,
+    ) {
+        prop_assume!(ax.abs() + ay.abs() + az.abs() > 1e-3);
+        let mut velocity = Velocity { x, y, z, ..Default::default() };
+        let acceleration = Acceleration { x: ax, y: ay, z: az, ..Default::default() };
+
+        dolly_velocity(&mut velocity, &acceleration, distance);
+
+        let length = (velocity.x.powi(2) + velocity.y.powi(2) + velocity.z.powi(2)).sqrt();
+        prop_assert!((length - distance).abs() <= 1e-3 * distance.max(1.), "{length} != {distance}");
+        // The direction is kept.
+        prop_assert!(velocity.x * ax >= 0. && velocity.y * ay >= 0. && velocity.z * az >= 0.);
+    }
+
+    #[test]
+    fn dolly_stops_without_acceleration(
+        x in -100f32..100., y in -100f32..100., z in -100f32..100.,
+        distance in any_f32(),
+    ) {
+        let mut velocity = Velocity { x, y, z, ..Default::default() };
+
+        dolly_velocity(&mut velocity, &Acceleration::default(), distance);
+
+        prop_assert_eq!((velocity.x, velocity.y, velocity.z), (0., 0., 0.));
+    }
+
+    #[test]
+    fn clamped_velocity_respects_limits(
+        x in -100f32..100., y in -100f32..100., z in -100f32..100.,
+        max_horizontal in 0.01f32..50., max_vertical in 0.01f32..50.,
+    ) {
+        let mut velocity = Velocity { x, y, z, ..Default::default() };
+
+        clamp_velocity(&mut velocity, max_horizontal, max_vertical);
+
+        let horizontal = (velocity.x.powi(2) + velocity.y.powi(2)).sqrt();
+        prop_assert!(horizontal <= max_horizontal * 1.0001, "{horizontal} > {max_horizontal}");
+        prop_assert!(velocity.z.abs() <= max_vertical, "{} > {max_vertical}", velocity.z);
+        // The direction is kept.
+        prop_assert!(velocity.x * x >= 0. && velocity.y * y >= 0. && velocity.z * z >= 0.);
+        prop_assert!((velocity.x * y - velocity.y * x).abs() <= 1e-2);
+    }
+
+    #[test]
+    fn additive_velocity_is_never_slower(
+        ax in -1f32..1., ay in -1f32..1., az in -1f32..1.,
+        horizontal_speed in 0f32..100., vertical_speed in 0f32..100.,
+        smoothing in smoothing(),
+    ) {
+        let acceleration = Acceleration { x: ax, y: ay, z: az, ..Default::default() };
+        let mut normalized = Velocity::default();
+        let mut additive = Velocity::default();
+
+        calculate_next_velocity(&mut normalized, &acceleration, horizontal_speed, vertical_speed, &smoothing, true);
+        calculate_next_velocity(&mut additive, &acceleration, horizontal_speed, vertical_speed, &smoothing, false);
+
+        for (normalized, additive) in [(normalized.x, additive.x), (normalized.y, additive.y), (normalized.z, additive.z)] {
+            prop_assert!(normalized.abs() <= additive.abs() + 1e-5, "{normalized} > {additive}");
+            prop_assert!(normalized * additive >= 0.);
+        }
+    }
+
+    #[test]
+    fn zero_acceleration_keeps_velocity(
+        x in -100f32..100., y in -100f32..100., z in -100f32..100.,
+        smoothing in smoothing(),
+    ) {
+        let mut velocity = Velocity { x, y, z, ..Default::default() };
+
+        calculate_next_velocity(&mut velocity, &Acceleration::default(), 10., 10., &smoothing, true);
+
+        prop_assert_eq!((velocity.x, velocity.y, velocity.z), (x, y, z));
+    }
+
+    #[test]
+    fn decay_never_increases_velocity(
+        x in any_f32(), y in any_f32(), z in any_f32(), pitch in any_f32(), yaw in any_f32(),
+        smoothing in smoothing(),
+    ) {
+        let before = Velocity { x, y, z, pitch, yaw };
+        let mut after = before.clone();
+
+        smooth_decay_velocity(&mut after, &smoothing);
+
+        for (old, new) in [(before.x, after.x), (before.y, after.y), (before.z, after.z), (before.pitch, after.pitch), (before.yaw, after.yaw)] {
+            prop_assert!(old.is_nan() || new.is_nan() || new.abs() <= old.abs(), "{new} > {old}");
+        }
+    }
+
+    #[test]
+    fn decay_converges_to_zero(x in -1000f32..1000., smoothing in smoothing()) {
+        let mut velocity = Velocity { x, ..Default::default() };
+
+        for _ in 0..10_000 {
+            smooth_decay_velocity(&mut velocity, &smoothing);
+        }
+
+        prop_assert!(velocity.x.abs() < 1e-3);
+    }
+
+    #[test]
+    fn smoothed_decay_never_overshoots(
+        x in -1000f32..1000., yaw in -10f32..10., smoothing in smoothing(), models in smoothing_models(),
+    ) {
+        let mut smoother = Smoother::default();
+        let mut velocity = Velocity { x, yaw, ..Default::default() };
+
+        for _ in 0..1_000 {
+            let before = velocity.clone();
+            smoother.decay(&mut velocity, &smoothing, &models);
+
+            for (old, new) in [(before.x, velocity.x), (before.yaw, velocity.yaw)] {
+                prop_assert!(new.abs() <= old.abs(), "{new} > {old}");
+                prop_assert!(new == 0. || new.signum() == old.signum(), "{new} overshot {old}");
+            }
+        }
+    }
+
+    #[test]
+    fn smoothed_decay_converges_to_zero(x in -1000f32..1000., smoothing in smoothing(), models in smoothing_models()) {
+        let mut smoother = Smoother::default();
+        let mut velocity = Velocity { x, ..Default::default() };
+
+        for _ in 0..10_000 {
+            smoother.decay(&mut velocity, &smoothing, &models);
+        }
+
+        prop_assert!(velocity.x.abs() < 1e-3);
+    }
+
+    #[test]
+    fn filtered_look_delta_stays_within_input_range(
+        filter in look_filter(),
+        samples in prop::collection::vec((-500f32..500., 0f32..0.1), 1..50),
+    ) {
+        let mut axis = AxisFilter::default();
+        let (min, max) = samples.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), (raw, _)| {
+            (min.min(*raw), max.max(*raw))
+        });
+
+        for (raw, dt) in samples {
+            let filtered = axis.filter(filter, raw, dt);
+
+            prop_assert!(filtered.is_finite());
+            prop_assert!(min - 1e-3 <= filtered && filtered <= max + 1e-3, "{filtered} not in {min}..={max}");
+        }
+    }
+
+    #[test]
+    fn scroll_step_is_capped(response in scroll_response(), notches in any_f32(), max_step in any_f32()) {
+        let step = response.step(notches, max_step);
+
+        prop_assert!(!step.is_nan());
+        prop_assert!(step.abs() <= max_step.max(0.) || max_step.is_nan(), "{step} > {max_step}");
+        prop_assert!(step == 0. || step.signum() == notches.signum(), "{step} vs {notches}");
+    }
+
+    #[test]
+    fn scroll_accumulator_releases_everything_pushed(
+        steps in prop::collection::vec(-100f32..100., 1..10), dt in 0.001f32..0.1, window in 0f32..0.5,
+    ) {
+        let mut accumulator = ScrollAccumulator::default();
+        let pushed: f32 = steps.iter().sum();
+        let mut released = 0.;
+
+        for step in steps {
+            accumulator.push(step);
+            released += accumulator.release(dt, window);
+        }
+        for _ in 0..10_000 {
+            released += accumulator.release(dt, window);
+        }
+
+        prop_assert!((released - pushed).abs() < 1e-2, "{released} != {pushed}");
+    }
+
+    #[test]
+    fn angle_difference_is_shortest_rotation(from in -100f32..100., to in -100f32..100.) {
+        let difference = angle_difference(from, to);
+        let turns = (from + difference - to) / (2. * PI);
+
+        prop_assert!((-PI..=PI).contains(&difference), "{difference}");
+        prop_assert!((turns - turns.round()).abs() < 1e-3, "{from} + {difference} doesn't end up at {to}");
+    }
+
+    #[test]
+    fn noise_stays_in_range(x in any_f32(), seed in any::<u32>()) {
+        let value = noise(x, seed);
+
+        prop_assert!((-1. ..=1.).contains(&value), "{value}");
+    }
+
+    #[test]
+    fn shake_stays_within_amplitude(
+        amplitude in 0f32..10., rotation_amplitude in 0f32..10., trauma in 0f32..2.,
+        deltas in prop::collection::vec(0f32..0.1, 1..50),
+    ) {
+        let motion = ShakeMotion { amplitude, rotation_amplitude, ..Default::default() };
+        let mut shake = Shake::default();
+        shake.add_trauma(trauma);
+
+        for delta in deltas {
+            let offset = shake.update(&motion, delta);
+
+            for position in [offset.x, offset.y, offset.z] {
+                prop_assert!(position.abs() <= amplitude, "{position} > {amplitude}");
+            }
+            for angle in [offset.pitch, offset.yaw] {
+                prop_assert!(angle.abs() <= rotation_amplitude.to_radians(), "{angle} > {rotation_amplitude}");
+            }
+        }
+    }
+
+    #[test]
+    fn shake_fades_out(trauma in 0f32..1., decay_time in 0.01f32..5.) {
+        let motion = ShakeMotion { decay_time, ..Default::default() };
+        let mut shake = Shake::default();
+        shake.add_trauma(trauma);
+
+        let mut elapsed = 0.;
+        while elapsed <= decay_time + 0.1 {
+            shake.update(&motion, 0.05);
+            elapsed += 0.05;
+        }
+
+        prop_assert_eq!(shake.trauma(), 0.);
+        prop_assert_eq!(shake.update(&motion, 0.05), Default::default());
+    }
+}
@@ -0,0 +1,32 @@
+use anyhow::Context;
+
+/// Standalone alternative to the `version.dll` proxy, see [freecam_rs::remote].
+///
+/// The game's window title (`*` matches any text) can optionally be passed as the first argument, and the game's process
+/// id with `--pid <pid>` when multiple instances are running.
+fn main() -> anyhow::Result<()> {
+    let exe_path = std::env::current_exe()?;
+    let default_directory = exe_path.parent().context("Executable is in root")?;
+    let mut window_title = None;
+    let mut pid = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--pid" {
+            let value = args.next().context("Missing the process id after `--pid`")?;
+            pid = Some(
+                value
+                    .parse::<u32>()
+                    .with_context(|| format!("Invalid process id `{}`", value))?,
+            );
+        } else if window_title.is_none() {
+            window_title = Some(arg);
+        } else {
+            anyhow::bail!("Unexpected argument `{}`", arg);
+        }
+    }
+
+    let window_title = window_title.unwrap_or_else(|| freecam_rs::remote::DEFAULT_WINDOW_TITLE.to_string());
+
+    freecam_rs::remote::run(default_directory, &window_title, pid)
+}
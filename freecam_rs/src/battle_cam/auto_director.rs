@@ -0,0 +1,167 @@
+//! Spectator mode which moves the camera between points of interest on its own, see [AutoDirector].
+
+use std::time::Instant;
+
+use crate::battle_cam::coords::WorldPos;
+use crate::battle_cam::data;
+use crate::battle_cam::easing::Easing;
+use crate::camera_path::{CameraPath, Keyframe, KeyframeSource};
+use crate::config::AutoDirectorConfig;
+
+/// How many degrees apart the keyframes of an orbit are, small enough for the linear interpolation to look circular.
+const ORBIT_KEYFRAME_DEGREES: f32 = 5.;
+
+/// Something in the battle worth looking at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum PointOfInterest {
+    /// The unit with the most soldiers fighting in melee, requires the `unit_list` in the `patch_profile`.
+    LargestMelee,
+    /// The first general's bodyguard unit, requires the `unit_list` in the `patch_profile`.
+    General,
+}
+
+impl PointOfInterest {
+    /// The current position of this point of interest, `None` if it can't be found.
+    pub fn position(self) -> Option<WorldPos> {
+        match self {
+            PointOfInterest::LargestMelee => data::battle_units()
+                .into_iter()
+                .filter(|unit| unit.melee_count > 0)
+                .max_by_key(|unit| unit.melee_count)
+                .map(|unit| unit.position),
+            PointOfInterest::General => data::battle_units()
+                .into_iter()
+                .find(|unit| unit.is_general)
+                .map(|unit| unit.position),
+        }
+    }
+}
+
+/// A single shot of the [AutoDirector], moving to a point of interest and slowly orbiting it.
+struct Shot {
+    /// From the camera's pose when the shot started to the start of the `orbit`.
+    transition: CameraPath,
+    /// Around the point of interest, starting once the `transition` is done.
+    orbit: CameraPath,
+    started: Instant,
+}
+
+/// Drifts the camera between the configured points of interest for spectating (AI) battles, see
+/// [crate::config::AutoDirectorConfig].
+///
+/// Each shot flies to a point of interest over the `transition_duration`, after which it slowly orbits it for the
+/// `dwell_duration`. The points of interest are visited in order, skipping any which can't be found.
+#[derive(Default)]
+pub struct AutoDirector {
+    /// The index of the next point of interest to visit.
+    next: usize,
+    shot: Option<Shot>,
+}
+
+impl AutoDirector {
+    /// Advance the current shot (starting the next one if it's done), and return the new `(position, pitch, yaw)`.
+    ///
+    /// Keeps the `current` pose if none of the points of interest can be found.
+    pub fn update(
+        &mut self,
+        conf: &AutoDirectorConfig,
+        current: (WorldPos, f32, f32),
+        locate: impl Fn(PointOfInterest) -> Option<WorldPos>,
+    ) -> (WorldPos, f32, f32) {
+        let shot_length = conf.transition_duration + conf.dwell_duration;
+        if !self
+            .shot
+            .as_ref()
+            .is_some_and(|shot| shot.started.elapsed() < shot_length)
+        {
+            self.shot = self.next_shot(conf, current, locate);
+        }
+
+        let Some(shot) = &self.shot else {
+            return current;
+        };
+
+        let elapsed = shot.started.elapsed().as_secs_f32();
+        let transition = conf.transition_duration.as_secs_f32();
+        let sample = if elapsed < transition {
+            shot.transition.sample(elapsed, conf.transition_easing)
+        } else {
+            shot.orbit.sample(elapsed - transition, Easing::Linear)
+        };
+
+        sample.unwrap_or(current)
+    }
+
+    /// Plan the shot of the next point of interest which can be found.
+    fn next_shot(
+        &mut self,
+        conf: &AutoDirectorConfig,
+        (pos, pitch, yaw): (WorldPos, f32, f32),
+        locate: impl Fn(PointOfInterest) -> Option<WorldPos>,
+    ) -> Option<Shot> {
+        let count = conf.points_of_interest.len();
+
+        for i in 0..count {
+            let index = (self.next + i) % count;
+            let poi = conf.points_of_interest[index];
+            let Some(subject) = locate(poi) else {
+                continue;
+            };
+
+            log::debug!("Auto director moving to {:?} at {:?}", poi, subject);
+            self.next = index + 1;
+
+            // Approach from the side the camera is already on, rather than flying around the subject first.
+            let start_angle = (pos.y - subject.y).atan2(pos.x - subject.x);
+            let orbit = orbit_path(conf, subject, start_angle);
+            let first = &orbit.keyframes[0];
+            let transition = CameraPath::new(vec![
+                keyframe(0., pos, pitch, yaw),
+                keyframe(
+                    conf.transition_duration.as_secs_f32(),
+                    first.pos,
+                    first.pitch,
+                    first.yaw,
+                ),
+            ]);
+
+            return Some(Shot {
+                transition,
+                orbit,
+                started: Instant::now(),
+            });
+        }
+
+        None
+    }
+}
+
+/// The path orbiting `subject` by the configured `orbit_degrees` over the `dwell_duration`, starting at `start_angle`
+/// (in radians, around the vertical axis).
+fn orbit_path(conf: &AutoDirectorConfig, subject: WorldPos, start_angle: f32) -> CameraPath {
+    let steps = (conf.orbit_degrees.abs() / ORBIT_KEYFRAME_DEGREES).ceil().max(1.) as usize;
+    let dwell = conf.dwell_duration.as_secs_f32();
+
+    let keyframes = (0..=steps)
+        .map(|step| {
+            let progress = step as f32 / steps as f32;
+            let angle = start_angle + (conf.orbit_degrees * progress).to_radians();
+            let pos = subject + WorldPos::new(angle.cos() * conf.distance, angle.sin() * conf.distance, conf.height);
+            let (pitch, yaw) = pos.pitch_yaw_towards(&subject);
+
+            keyframe(dwell * progress, pos, pitch, yaw)
+        })
+        .collect();
+
+    CameraPath::new(keyframes)
+}
+
+fn keyframe(time: f32, pos: WorldPos, pitch: f32, yaw: f32) -> Keyframe {
+    Keyframe {
+        time,
+        pos,
+        pitch,
+        yaw,
+        source: KeyframeSource::Custom,
+    }
+}
@@ -1,14 +1,22 @@
 use std::cell::UnsafeCell;
+
+use crate::battle_cam::coords::{GamePos, WorldPos};
+use crate::battle_cam::profile;
+use crate::memory::{self, GameMemory, LocalMemory};
+
+/// Generate accessors for game pointers, their addresses come from the active [profile::PatchProfile].
 macro_rules! game_pointers {
     ($
     (
         $(#[$inner:ident $($args:tt)*])*
-        $name:ident: $typ:ty = $addr:expr;
+        $name:ident: $typ:ty = $field:ident;
     )*
     ) => {
         $(
         $(#[$inner$($args)*])*
-        pub const $name: *mut $typ = $addr as *mut $typ;
+        pub fn $name() -> *mut $typ {
+            profile::active().$field.cast()
+        }
         )*
     };
 }
@@ -16,10 +24,12 @@ macro_rules! game_pointers {
 /// Game function which re-calculates the camera's Z coordinates and clips them appropriately.
 ///
 /// Normally only called when the user moves/scrolls the vanilla camera.
-pub static CALCULATE_DELTA_Z_TO_GROUND_FN_ADDR: usize = 0x0094ea00;
+pub fn calculate_delta_z_to_ground_fn_addr() -> usize {
+    profile::active().calculate_delta_z_to_ground_fn.get()
+}
 /// The first pointer should point to an array of at least 3 elements.
 ///
-/// The second pointer is the reference to `Z_FIX_DELTA_GROUND_ADDR`.
+/// The second pointer is the reference to [z_fix_delta_ground_addr].
 ///
 /// The third argument always seems to be `1`.
 pub type CalcDeltaFn = unsafe extern "stdcall" fn(*mut f32, *mut f32, f32);
@@ -28,46 +38,37 @@ game_pointers!(
     /// Contains the delta value between the current game camera `z` and the ground.
     ///
     /// This is seemingly used to a constant elevation for the camera whilst moving around.
-    Z_FIX_DELTA_GROUND_ADDR: f32 = 0x0193F364;
+    z_fix_delta_ground_addr: f32 = z_fix_delta_ground;
     /// When the given `u32 != 0` then the game is currently in a battle.
-    BATTLE_ONGOING_ADDR: u32 = 0x193D683;
+    battle_ongoing_addr: u32 = battle_ongoing;
     /// Holds the config value for the current camera type (RTS/TotalWar/etc).
-    BATTLE_CAM_CONF_TYPE_ADDR: BattleCameraType = 0x1639F14;
+    battle_cam_conf_type_addr: BattleCameraType = battle_cam_conf_type;
     /// The address for the semi-authoritative camera position when using TotalWar camera.
     ///
     /// Is different when using RTS.
-    BATTLE_CAM_ADDR: BattleCameraView = 0x193D598;
+    battle_cam_addr: BattleCameraView = battle_cam;
     /// The address for the semi-authoritative camera target position when using TotalWar camera.
     ///
     /// Is different when using RTS.
-    BATTLE_CAM_TARGET_ADDR: BattleCameraTargetView = 0x193D5DC;
+    battle_cam_target_addr: BattleCameraTargetView = battle_cam_target;
 );
 
 /// 0x0193D598, seems to represent the true map coordinates when using TotalWar Camera
 /// When using RTS/General it seems correlated to BattleCameraPosition in some way (and gets constantly overwritten by values)
 /// It seems to act sort of like BattleCameraTargetView when in RTS Camera mode.
-#[derive(Debug, Clone, Copy)]
-#[repr(C)]
-pub struct BattleCameraView {
-    /// 0x0193D598
-    pub x_coord: f32,
-    /// 0x0193D5A0
-    pub z_coord: f32,
-    /// 0x0193D59C
-    pub y_coord: f32,
-}
+///
+/// Layout: `x` at 0x0193D598, `z` at 0x0193D59C, `y` at 0x0193D5A0.
+pub type BattleCameraView = GamePos;
 
 /// 0x193D5DC
-#[derive(Debug, Clone, Copy)]
-#[repr(C)]
-pub struct BattleCameraTargetView {
-    /// 0x0193D5DC
-    pub x_coord: f32,
-    /// 0x0193D5E0
-    pub z_coord: f32,
-    /// 0x0193D5E4
-    pub y_coord: f32,
-}
+///
+/// Layout: `x` at 0x0193D5DC, `z` at 0x0193D5E0, `y` at 0x0193D5E4.
+pub type BattleCameraTargetView = GamePos;
+
+/// The game's audio listener position, its address is configured by the user.
+///
+/// Assumed to share the layout of [BattleCameraView].
+pub type AudioListenerPos = GamePos;
 
 /// 0x0193f34c, seems to represent the true map coordinates when using RTS/General camera
 #[derive(Debug, Clone, Copy)]
@@ -124,7 +125,70 @@ impl<T> GameCell<T> {
 /// Check whether we're currently in a battle or not.
 ///
 /// Hacky work-around for now.
-/// Not compatible with remote process approach.
+/// Only usable from within the game process, see [is_in_battle_in] for the remote process approach.
 pub fn is_in_battle() -> bool {
-    unsafe { *BATTLE_ONGOING_ADDR != 0 }
+    is_in_battle_in(&LocalMemory)
+}
+
+/// The most units read from the [profile::UnitList], guarding against reading a garbage count.
+const MAX_UNITS: usize = 1024;
+
+/// A unit in the current battle, see [battle_units].
+#[derive(Debug, Clone, Copy)]
+pub struct BattleUnit {
+    pub position: WorldPos,
+    /// How many of its soldiers are currently fighting in melee.
+    pub melee_count: u32,
+    pub is_general: bool,
+}
+
+/// All units in the current battle which could be read.
+///
+/// Empty if the active profile has no [profile::UnitList], or if the list isn't (yet) sensible.
+pub fn battle_units() -> Vec<BattleUnit> {
+    let Some(list) = profile::active().unit_list.as_ref() else {
+        return Vec::new();
+    };
+
+    unsafe {
+        let (Ok(units), Ok(count)) = (
+            LocalMemory.read(list.units.cast::<usize>()),
+            LocalMemory.read(list.count.cast::<u32>()),
+        ) else {
+            return Vec::new();
+        };
+        let count = count as usize;
+        if units == 0 || count > MAX_UNITS {
+            return Vec::new();
+        }
+
+        // Only read addresses which are accessible, as the list may be outdated or not yet initialised.
+        let accessible = |address: usize| (address != 0 && memory::is_accessible(address)).then_some(address);
+
+        (0..count)
+            .filter_map(|i| {
+                let entry = accessible(units.checked_add(i * std::mem::size_of::<usize>())?)?;
+                let unit = LocalMemory.read(entry as *const usize).ok()?;
+                let field = |offset: usize| accessible(unit.checked_add(offset).filter(|_| unit != 0)?);
+
+                let position = WorldPos::from(LocalMemory.read(field(list.position_offset)? as *const GamePos).ok()?);
+                let melee_count = LocalMemory.read(field(list.melee_count_offset)? as *const u32).ok()?;
+                let is_general = LocalMemory.read(field(list.general_flag_offset)? as *const u8).ok()? != 0;
+
+                [position.x, position.y, position.z]
+                    .iter()
+                    .all(|v| v.is_finite())
+                    .then_some(BattleUnit {
+                        position,
+                        melee_count,
+                        is_general,
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Check whether we're currently in a battle or not, using the given memory backend.
+pub fn is_in_battle_in(memory: &impl GameMemory) -> bool {
+    unsafe { memory.read(battle_ongoing_addr()).map(|v| v != 0).unwrap_or(false) }
 }
@@ -0,0 +1,105 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use retour::static_detour;
+
+use crate::battle_cam::coords::GamePos;
+use crate::config::GameThreadTeleportConfig;
+
+/// The assumed signature of the game's own "set camera position" function, taking a camera position and target.
+///
+/// Calling this (rather than writing to [crate::battle_cam::data::battle_cam_addr]) lets the game update all dependent
+/// state (audio listener, LOD center, etc.) in one go.
+pub type SetCameraFn = unsafe extern "stdcall" fn(*const GamePos, *const GamePos);
+/// Any argument-less game function which is called once per frame on the game thread.
+pub type GameTickFn = unsafe extern "stdcall" fn();
+
+static_detour! {
+    static GameTickHook: unsafe extern "stdcall" fn();
+}
+
+/// The teleport to execute on the next game tick, see [GameThreadTeleporter::queue_teleport].
+static PENDING_TELEPORT: Mutex<Option<(GamePos, GamePos)>> = Mutex::new(None);
+static SET_CAMERA_FN_ADDR: AtomicUsize = AtomicUsize::new(0);
+/// The address [GameTickHook] was initialised with, `0` if it wasn't yet. A detour can only be initialised once.
+static HOOKED_TICK_FN: AtomicUsize = AtomicUsize::new(0);
+
+/// Executes camera teleports on the game thread by calling the game's own [SetCameraFn].
+///
+/// The teleport is queued and executed from a hook on a per-frame game function, as calling the game's code from our
+/// own thread would race with the game's rendering.
+pub struct GameThreadTeleporter {
+    _private: (),
+}
+
+impl GameThreadTeleporter {
+    /// Hook the configured tick function.
+    ///
+    /// # Safety
+    /// The configured addresses must point to functions matching [GameTickFn] and [SetCameraFn].
+    pub unsafe fn new(conf: &GameThreadTeleportConfig) -> anyhow::Result<Self> {
+        match HOOKED_TICK_FN.load(Ordering::SeqCst) {
+            0 => {
+                let target: GameTickFn = std::mem::transmute(conf.tick_fn);
+                GameTickHook.initialize(target, game_tick_detour)?;
+                HOOKED_TICK_FN.store(conf.tick_fn, Ordering::SeqCst);
+            }
+            hooked if hooked != conf.tick_fn => anyhow::bail!(
+                "The game tick function is already hooked at {:#X}, restart the game to hook {:#X} instead",
+                hooked,
+                conf.tick_fn
+            ),
+            _ => {}
+        }
+
+        SET_CAMERA_FN_ADDR.store(conf.set_camera_fn, Ordering::SeqCst);
+        if !GameTickHook.is_enabled() {
+            GameTickHook.enable()?;
+        }
+
+        log::info!(
+            "Hooked game tick function at {:#X} for teleports using {:#X}",
+            conf.tick_fn,
+            conf.set_camera_fn
+        );
+
+        Ok(Self { _private: () })
+    }
+
+    /// Queue a teleport of the game's camera, replacing any teleport which hasn't executed yet.
+    pub fn queue_teleport(&self, camera: GamePos, target: GamePos) {
+        if let Ok(mut pending) = PENDING_TELEPORT.lock() {
+            *pending = Some((camera, target));
+        }
+    }
+
+    /// Whether a queued teleport has yet to be executed by the game thread.
+    pub fn is_pending(&self) -> bool {
+        PENDING_TELEPORT.lock().map(|p| p.is_some()).unwrap_or_default()
+    }
+}
+
+impl Drop for GameThreadTeleporter {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = GameTickHook.disable();
+        }
+        if let Ok(mut pending) = PENDING_TELEPORT.lock() {
+            *pending = None;
+        }
+    }
+}
+
+fn game_tick_detour() {
+    // Never block the game thread, we'll simply try again next frame.
+    let teleport = PENDING_TELEPORT.try_lock().ok().and_then(|mut p| p.take());
+
+    if let Some((camera, target)) = teleport {
+        unsafe {
+            let set_camera: SetCameraFn = std::mem::transmute(SET_CAMERA_FN_ADDR.load(Ordering::SeqCst));
+            set_camera(&camera, &target);
+        }
+    }
+
+    unsafe { GameTickHook.call() }
+}
@@ -1,52 +1,219 @@
 use rust_hooking_utils::patching::LocalPatcher;
+use std::cell::Cell;
 use std::f32::consts::PI;
 use std::ops::{Add, Div};
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
-use rust_hooking_utils::raw_input::key_manager::{KeyState, KeyboardManager};
-use windows::Win32::Foundation::POINT;
+use once_cell::sync::Lazy;
+use rust_hooking_utils::raw_input::key_manager::KeyState;
+use rust_hooking_utils::raw_input::virtual_keys::VirtualKey;
+use windows::Win32::Foundation::{POINT, RECT};
 use windows::Win32::UI::WindowsAndMessaging::{GetCursorPos, SetCursorPos};
 
-use data::Z_FIX_DELTA_GROUND_ADDR;
+use coords::WorldPos;
+use data::z_fix_delta_ground_addr;
 use data::{BattleCameraTargetView, BattleCameraType, BattleCameraView};
-
-use crate::battle_cam::patches::{DynamicPatch, RemoteData};
-use crate::config::FreecamConfig;
-use crate::mouse::MouseManager;
-
+use easing::Easing;
+use filter::AxisFilter;
+use scroll::ScrollAccumulator;
+use shake::{Shake, ShakeOffset};
+use smoothing::Smoother;
+use velocity::{Acceleration, Velocity};
+
+use crate::battle_cam::auto_director::{AutoDirector, PointOfInterest};
+#[cfg(feature = "game-thread-teleport")]
+use crate::battle_cam::game_thread::GameThreadTeleporter;
+use crate::battle_cam::patch_locations::{ForeignPatchHandling, PatchGroup};
+use crate::battle_cam::patches::{BattleUnitCameraTeleport, DynamicPatch, RemoteData};
+use crate::battle_cam::timings::TickTimings;
+use crate::battle_cam::transitions::TransitionLog;
+use crate::battle_cam::verification::{ExpectedPatch, PatchVerification};
+use crate::config::{CameraConfig, FreecamConfig, RemoteCaptureConfig};
+use crate::error_report;
+use crate::input::{FreecamMode, InputManager, KeyBinding};
+use crate::memory;
+use crate::mouse::{HorizontalScroll, MouseManager};
+use crate::overlay;
+
+pub use freecam_math::{bounds, coords, easing, filter, scroll, shake, smoothing, snap, velocity};
+
+pub mod auto_director;
 pub mod data;
+#[cfg(feature = "game-thread-teleport")]
+mod game_thread;
 pub mod patch_locations;
 mod patches;
-
-type Acceleration = Velocity;
-
-#[derive(Default, Debug, Clone)]
-pub struct Velocity {
-    x: f32,
-    y: f32,
-    z: f32,
+pub mod profile;
+mod timings;
+mod transitions;
+mod verification;
+
+/// How far the game's camera has to be from where we put it to count as the game moving it, see
+/// [RemoteCaptureConfig::unit_card_teleport].
+const GAME_CAMERA_MOVE_THRESHOLD: f32 = 0.5;
+/// How long a partially written unit card teleport is kept around before it's discarded.
+const TELEPORT_EXPIRY: Duration = Duration::from_millis(500);
+/// How long the game keeps control of the camera after [BattleState::reset_view], before we resync with it.
+const VIEW_RESET_SETTLE_TIME: Duration = Duration::from_millis(250);
+/// The distance the camera target is placed at until the game's own distance is known, see
+/// [crate::config::TargetDistance::Game].
+pub const DEFAULT_TARGET_DISTANCE: f32 = 1000.;
+/// The closest the camera target may be placed, any closer and the view direction loses precision.
+pub const MIN_TARGET_DISTANCE: f32 = 1.;
+/// Set once an unreadable battle phase address was logged, as the phase is detected on every update.
+static UNREADABLE_PHASE_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// The arrow keys as `[up, down, left, right]`, see [crate::config::CameraConfig::arrow_keys_move_camera].
+static ARROW_KEYS: Lazy<[KeyBinding; 4]> = Lazy::new(|| {
+    [
+        VirtualKey::VK_UP.into(),
+        VirtualKey::VK_DOWN.into(),
+        VirtualKey::VK_LEFT.into(),
+        VirtualKey::VK_RIGHT.into(),
+    ]
+});
+
+#[derive(Default, Debug, Clone, serde::Serialize)]
+struct CustomCameraState {
+    pos: WorldPos,
     pitch: f32,
     yaw: f32,
 }
 
-#[derive(Default, Debug)]
-struct CustomCameraState {
-    x: f32,
-    y: f32,
-    z: f32,
-    pitch: f32,
-    yaw: f32,
+impl CustomCameraState {
+    fn is_finite(&self) -> bool {
+        self.pos.is_finite() && self.pitch.is_finite() && self.yaw.is_finite()
+    }
+
+    /// Interpolate between `self` (`t = 0.0`) and `other` (`t = 1.0`), taking the shortest path for the yaw.
+    fn lerp(&self, other: &CustomCameraState, t: f32) -> CustomCameraState {
+        let mut yaw_diff = (other.yaw - self.yaw) % (2. * PI);
+        if yaw_diff > PI {
+            yaw_diff -= 2. * PI;
+        } else if yaw_diff < -PI {
+            yaw_diff += 2. * PI;
+        }
+
+        CustomCameraState {
+            pos: self.pos.lerp(&other.pos, t),
+            pitch: self.pitch + (other.pitch - self.pitch) * t,
+            yaw: self.yaw + yaw_diff * t,
+        }
+    }
+}
+
+/// Counts the intercepted unit card teleports, see [crate::config::FreecamConfig::log_teleport_events].
+#[derive(Default, Debug, Clone, serde::Serialize)]
+struct TeleportStats {
+    /// Teleports which moved the camera.
+    consumed: u32,
+    /// Teleports which were discarded, as the game never finished writing them within [TELEPORT_EXPIRY].
+    expired: u32,
+    /// The [BattleUnitCameraTeleport::sequence] of the last consumed teleport.
+    sequence: u32,
+    /// When the game first started writing the current teleport.
+    #[serde(skip)]
+    pending_since: Option<Instant>,
+}
+
+/// An ongoing interpolation from our custom camera to a unit card teleport's destination, see
+/// [BattleState::bc_handle_camera_teleport].
+///
+/// The camera is placed on the eased path directly, bypassing the velocity (and thus its smoothing) entirely, so the
+/// flight always takes exactly the configured duration.
+#[derive(Debug)]
+struct SyncTransition {
+    from: CustomCameraState,
+    to: CustomCameraState,
+    started: Instant,
+    duration: Duration,
+    easing: Easing,
+}
+
+/// The game moving the camera itself while our camera is in control, e.g., when panning towards a unit or a double
+/// clicked spot on the map.
+///
+/// The game writes a new intermediate camera state every frame of its pan. Rather than jumping between those (and our
+/// own corrections in between), our camera eases from where it was towards the latest `target` over the
+/// `sync_transition_duration`, after which it follows the game's writes directly until the pan ends.
+#[derive(Debug)]
+struct GamePan {
+    from: CustomCameraState,
+    /// The _final_ camera state of the game's latest write.
+    target: CustomCameraState,
+    started: Instant,
+    /// When the game last wrote to the camera.
+    last_write: Instant,
 }
 
 pub struct BattleCamera {
     current_state: BattleCameraState,
     patcher: LocalPatcher,
+    phase_transitions: TransitionLog<BattlePhase>,
+    /// Whether the freecam is enabled at all, see [crate::config::FreecamConfig::toggle_freecam_keys].
+    enabled: bool,
+    /// Set in the patch-free [crate::config::FreecamConfig::observer_mode], which only tracks the `observed_phase`.
+    observer: bool,
+    observed_phase: BattlePhase,
 }
 
 pub enum BattleCameraState {
     OutsideBattle,
+    /// The battle is loading, the [BattleState] is created (and verified) ahead of time so the first frame has no hitch.
+    Loading(BattleState),
+    /// Units are being deployed, all camera patches are removed to not interfere with the deployment camera.
+    Deployment(BattleState),
     InBattle(BattleState),
+    /// The battle has ended, all camera patches are removed to not interfere with the end-of-battle cinematic.
+    BattleEnded(BattleState),
+}
+
+/// A summary of the camera's current state, see [BattleCamera::status].
+#[derive(Debug, Clone, Copy)]
+pub struct CameraStatus {
+    pub phase: BattlePhase,
+    pub pos: WorldPos,
+    /// The camera's pitch and yaw in radians.
+    pub pitch: f32,
+    pub yaw: f32,
+    /// The camera's height above the ground, if the ground level is known.
+    pub ground_distance: Option<f32>,
+    /// How far our custom camera moves per update.
+    pub speed: f32,
+    pub patch_state: BattlePatchState,
+}
+
+/// The phase of a battle as detected from game memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BattlePhase {
+    OutsideBattle,
+    Loading,
+    Deployment,
+    InBattle,
+    BattleEnded,
+}
+
+impl BattleCameraState {
+    pub fn phase(&self) -> BattlePhase {
+        match self {
+            BattleCameraState::OutsideBattle => BattlePhase::OutsideBattle,
+            BattleCameraState::Loading(_) => BattlePhase::Loading,
+            BattleCameraState::Deployment(_) => BattlePhase::Deployment,
+            BattleCameraState::InBattle(_) => BattlePhase::InBattle,
+            BattleCameraState::BattleEnded(_) => BattlePhase::BattleEnded,
+        }
+    }
+
+    pub fn battle_state(&mut self) -> Option<&mut BattleState> {
+        match self {
+            BattleCameraState::OutsideBattle => None,
+            BattleCameraState::Loading(b_state)
+            | BattleCameraState::Deployment(b_state)
+            | BattleCameraState::InBattle(b_state)
+            | BattleCameraState::BattleEnded(b_state) => Some(b_state),
+        }
+    }
 }
 
 impl BattleCamera {
@@ -54,6 +221,31 @@ impl BattleCamera {
         Self {
             current_state: BattleCameraState::OutsideBattle,
             patcher,
+            phase_transitions: TransitionLog::new("Battle phase"),
+            enabled: true,
+            observer: false,
+            observed_phase: BattlePhase::OutsideBattle,
+        }
+    }
+
+    /// Create a camera for the [crate::config::FreecamConfig::observer_mode], which never patches the game.
+    ///
+    /// Only [Self::observe] should be used to update it, no [BattleState] is ever created.
+    pub fn new_observer(patcher: LocalPatcher) -> Self {
+        Self {
+            enabled: false,
+            observer: true,
+            ..Self::new(patcher)
+        }
+    }
+
+    /// Track the current battle phase without touching the game, see [Self::new_observer].
+    pub fn observe(&mut self, conf: &FreecamConfig) {
+        let phase = self.detect_phase(conf);
+
+        if phase != self.observed_phase {
+            self.phase_transitions.record(self.observed_phase, phase);
+            self.observed_phase = phase;
         }
     }
 
@@ -61,26 +253,120 @@ impl BattleCamera {
         &mut self,
         conf: &mut FreecamConfig,
         scroll: &mut MouseManager,
-        key_man: &mut KeyboardManager,
+        key_man: &mut InputManager,
         t_delta: Duration,
     ) -> anyhow::Result<()> {
-        let in_battle = self.is_in_battle();
+        let phase = self.detect_phase(conf);
+
+        match &mut self.current_state {
+            BattleCameraState::Deployment(b_state)
+            | BattleCameraState::InBattle(b_state)
+            | BattleCameraState::BattleEnded(b_state) => b_state.record_vanilla_camera(),
+            BattleCameraState::OutsideBattle | BattleCameraState::Loading(_) => {}
+        }
+
+        if phase != self.current_state.phase() {
+            self.transition(phase, conf, scroll);
+            return Ok(());
+        }
+
+        match &mut self.current_state {
+            BattleCameraState::InBattle(state) if self.enabled => state.run(scroll, key_man, t_delta, conf),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn phase(&self) -> BattlePhase {
+        if self.observer {
+            self.observed_phase
+        } else {
+            self.current_state.phase()
+        }
+    }
+
+    /// Whether our custom camera (rather than the game) is currently in control of the camera.
+    pub fn is_custom_camera_active(&mut self) -> bool {
+        self.current_state
+            .battle_state()
+            .is_some_and(|b_state| matches!(b_state.battle_patcher.state, BattlePatchState::Applied))
+    }
+
+    /// Enable or disable the entire freecam.
+    ///
+    /// While disabled all patches are removed and the original camera type is restored, handing full control back to the
+    /// game's own camera. Once re-enabled our camera continues from wherever the game left it.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled == self.enabled {
+            return;
+        }
+        self.enabled = enabled;
+
+        if enabled {
+            overlay::notify("Freecam enabled");
+        } else {
+            overlay::notify("Freecam disabled, the game's camera is in control");
+        }
+
+        if let Some(b_state) = self.current_state.battle_state() {
+            unsafe {
+                if enabled {
+                    b_state.resync_camera();
+                } else {
+                    b_state.change_camera_state(false);
+                    b_state.restore_camera_type();
+                }
+            }
+        }
+    }
 
-        // Handle state transitions
-        match self.current_state {
-            BattleCameraState::OutsideBattle if in_battle => {
+    /// Move to the given `phase`, running the exit hook of the current phase and the entry hook of the new one.
+    unsafe fn transition(&mut self, phase: BattlePhase, conf: &FreecamConfig, scroll: &mut MouseManager) {
+        self.phase_transitions.record(self.current_state.phase(), phase);
+
+        let previous = std::mem::replace(&mut self.current_state, BattleCameraState::OutsideBattle);
+        let b_state = Self::exit_phase(previous, conf, scroll);
+        self.current_state = Self::enter_phase(phase, b_state);
+    }
+
+    /// Exit hook, returns the [BattleState] to carry over to the next phase.
+    fn exit_phase(previous: BattleCameraState, conf: &FreecamConfig, scroll: &mut MouseManager) -> BattleState {
+        match previous {
+            BattleCameraState::OutsideBattle => {
                 // Reset any scroll delta just to be sure.
                 scroll.reset_scroll();
-                self.current_state = BattleCameraState::InBattle(BattleState::new());
-                Ok(())
+                BattleState::new(conf)
+            }
+            BattleCameraState::Loading(b_state)
+            | BattleCameraState::Deployment(b_state)
+            | BattleCameraState::InBattle(b_state)
+            | BattleCameraState::BattleEnded(b_state) => b_state,
+        }
+    }
+
+    /// Entry hook, sets up the `b_state` for the given phase.
+    unsafe fn enter_phase(phase: BattlePhase, mut b_state: BattleState) -> BattleCameraState {
+        // Patches are re-applied as soon as the user provides input in the `InBattle` phase.
+        if phase != BattlePhase::InBattle {
+            b_state.change_camera_state(false);
+        }
+
+        match phase {
+            // Transition out of battle, drop implementations take care of cleanup
+            BattlePhase::OutsideBattle => {
+                b_state.restore_vanilla_camera();
+                BattleCameraState::OutsideBattle
             }
-            BattleCameraState::InBattle(ref mut state) if in_battle => state.run(scroll, key_man, t_delta, conf),
-            BattleCameraState::InBattle(_) if !in_battle => {
-                // Transition out of battle, drop implementations take care of cleanup
-                self.current_state = BattleCameraState::OutsideBattle;
-                Ok(())
+            BattlePhase::Loading => {
+                b_state.prewarm();
+                BattleCameraState::Loading(b_state)
             }
-            _ => Ok(()),
+            BattlePhase::Deployment => BattleCameraState::Deployment(b_state),
+            BattlePhase::InBattle => BattleCameraState::InBattle(b_state),
+            BattlePhase::BattleEnded => BattleCameraState::BattleEnded(b_state),
         }
     }
 
@@ -88,14 +374,198 @@ impl BattleCamera {
     ///
     /// Only really useful for config updates.
     pub fn set_custom_camera(&mut self, enabled: bool) {
-        match &mut self.current_state {
-            BattleCameraState::OutsideBattle => {}
-            BattleCameraState::InBattle(b_state) => unsafe { b_state.change_camera_state(enabled) },
+        if let Some(b_state) = self.current_state.battle_state() {
+            unsafe { b_state.change_camera_state(enabled) }
+        }
+    }
+
+    /// The game camera's `(position, pitch, yaw)`, if we're in a battle and the game (not our custom camera) controls it.
+    pub fn vanilla_camera_pose(&mut self) -> Option<(WorldPos, f32, f32)> {
+        if self.is_custom_camera_active() {
+            return None;
+        }
+
+        self.camera_pose()
+    }
+
+    /// The current camera's `(position, pitch, yaw)` if we're in a battle, regardless of who controls it.
+    pub fn camera_pose(&mut self) -> Option<(WorldPos, f32, f32)> {
+        if self.phase() == BattlePhase::OutsideBattle {
+            return None;
+        }
+
+        unsafe {
+            let camera = WorldPos::from(*self.patcher.read(data::battle_cam_addr()));
+            let (pitch, yaw) = camera.pitch_yaw_towards(&(*self.patcher.read(data::battle_cam_target_addr())).into());
+            Some((camera, pitch, yaw))
+        }
+    }
+
+    /// A summary of the camera's state if we're in a battle, for [crate::console_status].
+    pub fn status(&mut self) -> Option<CameraStatus> {
+        let phase = self.phase();
+        let (pos, pitch, yaw) = self.camera_pose()?;
+        let b_state = self.current_state.battle_state()?;
+        let velocity = b_state.velocity;
+
+        Some(CameraStatus {
+            phase,
+            pos,
+            pitch,
+            yaw,
+            ground_distance: b_state.captures.remote_z.then(|| pos.z - b_state.get_ground_z_level()),
+            speed: WorldPos::new(velocity.x, velocity.y, velocity.z).length(),
+            patch_state: b_state.battle_patcher.state,
+        })
+    }
+
+    /// Move our custom camera to the given pose, exactly like a unit card teleport would.
+    ///
+    /// Returns `false` if we're not in a battle. The teleport is only picked up while our custom camera is running.
+    pub fn teleport_to(&mut self, pos: WorldPos, pitch: f32, yaw: f32) -> bool {
+        let Some(b_state) = self.current_state.battle_state() else {
+            return false;
+        };
+
+        let target = pos.look_target(pitch, yaw, b_state.game_target_distance);
+        let teleport_location = unsafe { b_state.remote_data.teleport_location.as_mut() };
+        teleport_location.publish(pos.into(), target.into());
+
+        true
+    }
+
+    /// Place our custom camera at the given pose immediately, without the transition of [Self::teleport_to].
+    ///
+    /// Returns `false` if we're not in a battle. The pose is only written while our custom camera is running.
+    pub fn set_camera_pose(&mut self, pos: WorldPos, pitch: f32, yaw: f32) -> bool {
+        let Some(b_state) = self.current_state.battle_state() else {
+            return false;
+        };
+
+        b_state.custom_camera = CustomCameraState { pos, pitch, yaw };
+        b_state.velocity = Velocity::default();
+        b_state.sync_transition = None;
+        b_state.game_pan = None;
+
+        true
+    }
+
+    /// Feed the given movement into our custom camera every update, in addition to the user's input.
+    ///
+    /// Stays active until replaced, pass [ScriptedInput::default] to stop. Only lasts for the current battle.
+    pub fn set_scripted_input(&mut self, input: ScriptedInput) {
+        if let Some(b_state) = self.current_state.battle_state() {
+            b_state.scripted_input = input;
+        }
+    }
+
+    /// Recover from any glitched state, see [crate::config::FreecamConfig::reset_keys].
+    ///
+    /// Shows and releases the cursor, stops the auto director, and re-syncs our custom camera with a fresh set of
+    /// patches.
+    pub fn reset(&mut self, mouse: &mut MouseManager) {
+        overlay::notify("Resetting the freecam");
+        mouse.show_cursor();
+        mouse.release_cursor_clip();
+        mouse.reset_scroll();
+
+        if let Some(b_state) = self.current_state.battle_state() {
+            unsafe { b_state.reset() }
+        }
+    }
+
+    /// Move the camera back to the view the battle started with, see [crate::config::FreecamConfig::reset_view_keys].
+    pub fn reset_view(&mut self, mouse: &mut MouseManager) {
+        self.end_mouse_look(mouse);
+
+        if let Some(b_state) = self.current_state.battle_state() {
+            unsafe { b_state.reset_view() }
+        }
+    }
+
+    /// Stop looking around or drag panning with the mouse, e.g., because the game window lost focus.
+    pub fn end_mouse_look(&mut self, mouse: &mut MouseManager) {
+        if let Some(b_state) = self.current_state.battle_state() {
+            unsafe { b_state.end_mouse_look(mouse) }
+        }
+    }
+
+    /// Keep the cursor within the game window after it was resized, see [MouseManager::take_window_change].
+    pub fn fit_cursor_to_window(&mut self, client_rect: RECT) {
+        if let Some(b_state) = self.current_state.battle_state() {
+            b_state.fit_cursor_to_window(client_rect)
+        }
+    }
+
+    /// Discard all velocity and re-sync our custom camera with the game's camera.
+    pub fn resync_camera(&mut self) {
+        if let Some(b_state) = self.current_state.battle_state() {
+            unsafe { b_state.resync_camera() }
         }
     }
 
     pub fn is_in_battle(&self) -> bool {
-        unsafe { *self.patcher.read(data::BATTLE_ONGOING_ADDR) != 0 }
+        unsafe { *self.patcher.read(data::battle_ongoing_addr()) != 0 }
+    }
+
+    /// Detect the current battle phase.
+    ///
+    /// The loading, deployment, and end-of-battle phases can only be detected if their addresses have been configured in
+    /// [crate::config::BattlePhaseAddresses]. Otherwise loading is considered part of [BattlePhase::OutsideBattle], and
+    /// the others part of [BattlePhase::InBattle]. The same goes for a configured address which isn't readable (e.g. a
+    /// wrong address for this executable), whose phase is unknown.
+    pub fn detect_phase(&self, conf: &FreecamConfig) -> BattlePhase {
+        let is_flag_set = |addr: Option<usize>| {
+            let Some(addr) = addr else {
+                return false;
+            };
+
+            if !crate::memory::is_accessible(addr) {
+                if !UNREADABLE_PHASE_WARNED.swap(true, Ordering::Relaxed) {
+                    log::warn!("Battle phase address {:#X} isn't readable, ignoring it", addr);
+                }
+                return false;
+            }
+
+            unsafe { *self.patcher.read(addr as *const u8) != 0 }
+        };
+
+        if let Some(addresses) = &conf.battle_phase_addresses {
+            if is_flag_set(addresses.loading) {
+                return BattlePhase::Loading;
+            }
+        }
+
+        if !self.is_in_battle() {
+            return BattlePhase::OutsideBattle;
+        }
+
+        match &conf.battle_phase_addresses {
+            Some(addresses) if is_flag_set(addresses.deployment) => BattlePhase::Deployment,
+            Some(addresses) if is_flag_set(addresses.battle_ended) => BattlePhase::BattleEnded,
+            _ => BattlePhase::InBattle,
+        }
+    }
+
+    /// Create a snapshot of the current camera state for use in bug reports.
+    pub fn dump_state(&self) -> serde_json::Value {
+        match &self.current_state {
+            BattleCameraState::OutsideBattle => serde_json::json!({
+                "state": format!("{:?}", self.phase()),
+                "enabled": self.enabled,
+                "observer": self.observer,
+                "phase_transitions": self.phase_transitions.dump(),
+            }),
+            BattleCameraState::Loading(b_state)
+            | BattleCameraState::Deployment(b_state)
+            | BattleCameraState::InBattle(b_state)
+            | BattleCameraState::BattleEnded(b_state) => serde_json::json!({
+                "state": format!("{:?}", self.current_state.phase()),
+                "enabled": self.enabled,
+                "phase_transitions": self.phase_transitions.dump(),
+                "battle": unsafe { b_state.dump_state() },
+            }),
+        }
     }
 }
 
@@ -107,31 +577,264 @@ pub struct BattleState {
     /// Note that this _must_ be below `battle_patcher` in the struct declaration to ensure the patches are removed
     /// before dropping this remote data.
     remote_data: RemoteData,
+    /// The captures the `battle_patcher` was created with, config changes only apply to the next battle.
+    captures: RemoteCaptureConfig,
+    /// Whether our custom camera took control since [RemoteData::vanilla_camera] was last recorded.
+    vanilla_camera_stale: bool,
     custom_camera: CustomCameraState,
     velocity: Velocity,
-    /// For panning
-    last_sync_time: Option<Instant>,
+    smoother: Smoother,
+    /// Zoom which has been scrolled, but not yet applied, see [crate::config::CameraConfig::scroll_smoothing_window].
+    scroll_zoom: ScrollAccumulator,
+    /// Set while the game pans the camera itself, see [Self::bc_follow_game_pan].
+    game_pan: Option<GamePan>,
+    /// The last time the altitude was logged, see [crate::config::CameraConfig::altitude_readout].
+    last_altitude_readout: Option<Instant>,
     last_cursor_pos_freecam: Option<POINT>,
+    /// Whether looking around is toggled on, see [FreecamMode::Toggle].
+    mouse_look_toggled: bool,
+    /// The filter state for the vertical and horizontal mouse deltas, see [crate::config::CameraConfig::look_filter].
+    look_filter: (AxisFilter, AxisFilter),
+    last_look_input: Option<Instant>,
+    /// The cursor position at which the `drag_pan_key` was pressed, see [Self::bc_handle_drag_pan].
+    last_cursor_pos_drag: Option<POINT>,
+    keyboard_look_ramp: KeyboardLookRamp,
     /// The amount that our scroll differs from Z. Should help the camera remain consistent across terrain.
     z_diff: f32,
+    /// The last valid value of [Self::get_ground_z_level].
+    last_ground_z: Cell<f32>,
+    /// The last `custom_camera` and `z_diff` without any `NaN` or infinite values, see [Self::bc_recover_invalid_state].
+    last_valid_camera: (CustomCameraState, f32),
+    /// How often our camera state had to be restored during this battle.
+    invalid_state_recoveries: u32,
+    /// Whether the game's camera contained `NaN` or infinite values as of the last sync, to only log it once.
+    game_camera_invalid: bool,
+    /// How far the game placed its camera target from the camera as of the last sync, see
+    /// [crate::config::TargetDistance::Game].
+    game_target_distance: f32,
+    last_height_eval: Option<Instant>,
+    /// Set if [Self::force_game_height_eval] was deemed unsafe during this battle.
+    height_eval_disabled: bool,
+    /// The user's camera type from before we forced the TotalWar camera, restored once forcing stops.
+    ///
+    /// Stored as a raw `u32` as the game could contain values we don't have a [BattleCameraType] variant for.
+    original_camera_type: Option<u32>,
+    /// Set while we're easing towards a unit card teleport's destination, see [Self::bc_apply_sync_transition].
+    sync_transition: Option<SyncTransition>,
+    teleports: TeleportStats,
+    timings: TickTimings,
+    /// The game's camera and target at the start of the battle, restored by [Self::reset_view].
+    start_view: Option<(BattleCameraView, BattleCameraTargetView)>,
+    /// Set while the game holds the camera after it was moved back to [Self::start_view], holding when it started and
+    /// whether our patches were applied before, see [Self::reset_view].
+    view_reset: Option<(Instant, bool)>,
+    /// Set while the camera moves between points of interest on its own, see [Self::bc_handle_auto_director].
+    auto_director: Option<AutoDirector>,
+    /// Whether the camera moves at the constant `dolly_speed`, see [Self::bc_handle_dolly_toggle].
+    dolly: bool,
+    /// See [BattleCamera::set_scripted_input].
+    scripted_input: ScriptedInput,
+    /// The camera shake, and the offset it currently adds to the camera, see [Self::bc_update_shake].
+    shake: (Shake, ShakeOffset),
+    /// Set if teleports should also be executed through the game's own function, see [GameThreadTeleporter].
+    #[cfg(feature = "game-thread-teleport")]
+    game_teleporter: Option<GameThreadTeleporter>,
+}
+
+/// Movement fed into the custom camera by code rather than the user, see [BattleCamera::set_scripted_input].
+///
+/// All values are in the range `-1..=1`, like holding down the corresponding keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScriptedInput {
+    /// Forward (positive) or backwards.
+    pub forward: f32,
+    /// Rotate left (positive) or right.
+    pub rotate: f32,
+    /// Up (positive) or down.
+    pub vertical: f32,
 }
 
 impl BattleState {
     /// Create a new ephemeral [BattleState] instance.
     ///
     /// A new struct should be created for each new battle.
-    pub fn new() -> Self {
+    #[cfg_attr(not(feature = "game-thread-teleport"), allow(unused_variables))]
+    pub fn new(conf: &FreecamConfig) -> Self {
         let remote = RemoteData::default();
+        #[cfg(feature = "game-thread-teleport")]
+        let game_teleporter = conf
+            .experimental
+            .game_thread_teleport
+            .as_ref()
+            .filter(|_| !conf.safe_mode)
+            .and_then(|teleport_conf| unsafe {
+                GameThreadTeleporter::new(teleport_conf)
+                    .map_err(|e| error_report::report(format!("Failed to hook game thread teleport: {}", e)))
+                    .ok()
+            });
+
+        let (battle_patcher, captures) = if conf.safe_mode {
+            log::info!("Running in safe mode, the game's code won't be patched");
+            (BattlePatcher::unpatched(), RemoteCaptureConfig::none())
+        } else {
+            let captures = conf.remote_captures.without(&conf.disabled_patch_groups);
+            let patcher = BattlePatcher::new(&remote, captures, &conf.disabled_patch_groups, conf.foreign_patches);
+            (patcher, captures)
+        };
 
         Self {
-            battle_patcher: BattlePatcher::new(&remote),
+            battle_patcher,
+            captures,
+            vanilla_camera_stale: false,
             velocity: Default::default(),
+            smoother: Default::default(),
+            scroll_zoom: Default::default(),
             custom_camera: Default::default(),
             z_diff: 0.0,
+            last_ground_z: Cell::new(0.0),
+            last_valid_camera: Default::default(),
+            game_target_distance: DEFAULT_TARGET_DISTANCE,
+            invalid_state_recoveries: 0,
+            game_camera_invalid: false,
+            last_height_eval: None,
+            height_eval_disabled: false,
             remote_data: remote,
             last_cursor_pos_freecam: Default::default(),
-            last_sync_time: None,
+            mouse_look_toggled: false,
+            look_filter: Default::default(),
+            last_look_input: None,
+            last_cursor_pos_drag: None,
+            keyboard_look_ramp: Default::default(),
+            game_pan: None,
+            last_altitude_readout: None,
+            original_camera_type: None,
+            teleports: Default::default(),
+            timings: TickTimings::new(conf.measure_tick_timings),
+            start_view: None,
+            view_reset: None,
+            auto_director: None,
+            dolly: false,
+            sync_transition: None,
+            scripted_input: ScriptedInput::default(),
+            shake: Default::default(),
+            #[cfg(feature = "game-thread-teleport")]
+            game_teleporter,
+        }
+    }
+
+    /// Create a snapshot of the current battle state.
+    ///
+    /// See [BattleCamera::dump_state].
+    pub unsafe fn dump_state(&self) -> serde_json::Value {
+        #[allow(unused_mut)]
+        let mut dump = serde_json::json!({
+            "custom_camera": self.custom_camera,
+            "velocity": self.velocity,
+            "z_diff": self.z_diff,
+            "invalid_state_recoveries": self.invalid_state_recoveries,
+            "game_camera_invalid": self.game_camera_invalid,
+            "remote_z": f32::from_bits(self.remote_data.remote_z.load(Ordering::SeqCst)),
+            "ground_z": self.get_ground_z_level(),
+            "ground_delta_z": *self.battle_patcher.patcher.read(z_fix_delta_ground_addr()),
+            "teleport_location": format!("{:?}", self.remote_data.teleport_location.as_ref()),
+            "teleports": self.teleports,
+            "patch_state": format!("{:?}", self.battle_patcher.state),
+            "patch_transitions": self.battle_patcher.transitions.dump(),
+            "patch_verification": self.battle_patcher.verification.dump(),
+            "game_camera": format!("{:?}", self.get_game_camera()),
+            "game_target_camera": format!("{:?}", self.get_game_target_camera()),
+            "game_pan": self.game_pan.as_ref().map(|pan| format!("{:?}", pan)),
+        });
+
+        #[cfg(feature = "game-thread-teleport")]
+        {
+            dump["game_teleport_pending"] = self.game_teleporter.as_ref().map(|t| t.is_pending()).into();
         }
+
+        dump
+    }
+
+    /// Verify our patch locations and the camera addresses while the battle is still loading.
+    ///
+    /// Any issues are logged up front, and the pages are faulted in before the first frame we control the camera.
+    unsafe fn prewarm(&mut self) {
+        let started = Instant::now();
+
+        if memory::is_accessible(data::battle_cam_addr() as usize)
+            && memory::is_accessible(data::battle_cam_target_addr() as usize)
+        {
+            std::hint::black_box((*self.get_game_camera(), *self.get_game_target_camera()));
+        } else {
+            error_report::report(
+                "The game's camera isn't accessible, does the `patch_profile` match this game executable?",
+            );
+        }
+
+        log::debug!("Prepared battle state while loading in {:?}", started.elapsed());
+    }
+
+    /// See [BattleCamera::resync_camera].
+    pub unsafe fn resync_camera(&mut self) {
+        self.velocity = Velocity::default();
+        self.smoother.reset();
+        self.scroll_zoom.clear();
+        self.sync_transition = None;
+        self.game_pan = None;
+        self.sync_custom_camera();
+    }
+
+    /// Reset all transient camera state and re-sync with the game's camera, see [BattleCamera::reset].
+    ///
+    /// If our custom camera was in control its patches are removed and re-applied from a clean state.
+    pub unsafe fn reset(&mut self) {
+        let was_applied = matches!(self.battle_patcher.state, BattlePatchState::Applied);
+        self.battle_patcher.change_state(BattlePatchState::NotApplied);
+
+        self.auto_director = None;
+        self.dolly = false;
+        self.last_cursor_pos_freecam = None;
+        self.mouse_look_toggled = false;
+        self.last_cursor_pos_drag = None;
+        self.look_filter = Default::default();
+        self.last_look_input = None;
+        self.keyboard_look_ramp = Default::default();
+        self.resync_camera();
+
+        if was_applied {
+            self.change_battle_state(false);
+        }
+    }
+
+    /// Remember the camera the game itself wrote for [Self::restore_vanilla_camera], should be called every tick.
+    unsafe fn record_vanilla_camera(&mut self) {
+        if matches!(self.battle_patcher.state, BattlePatchState::Applied) {
+            self.vanilla_camera_stale = true;
+        } else if self.vanilla_camera_stale {
+            // The patches were only just removed, give the game a tick to overwrite our values with its own.
+            self.vanilla_camera_stale = false;
+        } else {
+            *self.remote_data.vanilla_camera.as_mut() = BattleUnitCameraTeleport {
+                camera: *self.get_game_camera(),
+                target: *self.get_game_target_camera(),
+                sequence: 0,
+            };
+        }
+    }
+
+    /// Hand the camera back to the game as the game itself last left it, if our custom camera was in control since.
+    ///
+    /// The game stops updating the camera once the battle is left, which could otherwise leave it pointing somewhere
+    /// odd for the post-battle screen.
+    unsafe fn restore_vanilla_camera(&mut self) {
+        let vanilla = *self.remote_data.vanilla_camera.as_ref();
+        if !self.vanilla_camera_stale || vanilla == BattleUnitCameraTeleport::default() {
+            return;
+        }
+
+        log::debug!("Restoring the vanilla camera: {:?}", vanilla);
+        *self.get_game_camera() = vanilla.camera;
+        *self.get_game_target_camera() = vanilla.target;
+        self.vanilla_camera_stale = false;
     }
 
     pub unsafe fn change_camera_state(&mut self, enabled: bool) {
@@ -143,28 +846,111 @@ impl BattleState {
     pub unsafe fn run(
         &mut self,
         scroll: &mut MouseManager,
-        key_man: &mut KeyboardManager,
+        key_man: &mut InputManager,
         t_delta: Duration,
         conf: &mut FreecamConfig,
     ) -> anyhow::Result<()> {
+        if self.bc_handle_view_reset() {
+            return Ok(());
+        }
+        if self.start_view.is_none() {
+            self.start_view = Some((*self.get_game_camera(), *self.get_game_target_camera()));
+        }
+
         if conf.force_ttw_camera {
+            if self.original_camera_type.is_none() {
+                self.original_camera_type = Some(
+                    *self
+                        .battle_patcher
+                        .patcher
+                        .read(data::battle_cam_conf_type_addr().cast::<u32>()),
+                );
+            }
             // Always ensure we're on the TotalWar cam
             self.battle_patcher
                 .patcher
-                .write(data::BATTLE_CAM_CONF_TYPE_ADDR, BattleCameraType::TotalWar);
+                .write(data::battle_cam_conf_type_addr(), BattleCameraType::TotalWar);
+        } else {
+            self.restore_camera_type();
         }
 
-        if !conf.camera.custom_camera_enabled {
+        self.timings.start_tick();
+        let result = if conf.safe_mode || self.battle_patcher.is_rolled_back() || !conf.camera.custom_camera_enabled {
             self.run_battle_no_custom(scroll, key_man, t_delta, conf)
         } else {
-            self.run_battle_custom_camera(scroll, key_man, t_delta, conf)
+            let result = self.run_battle_custom_camera(scroll, key_man, t_delta, conf);
+            self.bc_sync_audio_listener(conf);
+            result
+        };
+        self.timings.end_tick();
+
+        result
+    }
+
+    /// Hand the camera to the game and move it back to where the game placed it at the start of the battle, recovering
+    /// from wherever the user flew off to.
+    ///
+    /// This is the view captured on our first tick rather than a default computed by the game itself, as no function
+    /// for the latter is known. Without a captured view the game simply keeps its camera where it is.
+    ///
+    /// Our camera resyncs with the game's once it had some time to settle, see [Self::bc_handle_view_reset].
+    pub unsafe fn reset_view(&mut self) {
+        if self.view_reset.is_some() {
+            return;
+        }
+
+        let was_applied = matches!(self.battle_patcher.state, BattlePatchState::Applied);
+        self.battle_patcher.change_state(BattlePatchState::NotApplied);
+        self.auto_director = None;
+
+        if let Some((camera, target)) = self.start_view {
+            overlay::notify("Moving the camera back to where the battle started");
+            *self.get_game_camera() = camera;
+            *self.get_game_target_camera() = target;
+        }
+
+        self.view_reset = Some((Instant::now(), was_applied));
+    }
+
+    /// Resync with the game's camera once it settled after [Self::reset_view], re-applying our patches if they were
+    /// applied before. Returns `true` while the game is still in control.
+    unsafe fn bc_handle_view_reset(&mut self) -> bool {
+        let Some((started, was_applied)) = self.view_reset else {
+            return false;
+        };
+        if started.elapsed() < VIEW_RESET_SETTLE_TIME {
+            return true;
+        }
+
+        self.view_reset = None;
+        self.resync_camera();
+        if was_applied {
+            self.change_battle_state(false);
+        }
+
+        false
+    }
+
+    /// Move the game's audio listener along with our custom camera, see [FreecamConfig::audio_listener_address].
+    ///
+    /// The game only moves the listener when it moves the camera itself, so while our patches are applied it'd otherwise
+    /// remain wherever the game last put it.
+    unsafe fn bc_sync_audio_listener(&mut self, conf: &FreecamConfig) {
+        let Some(address) = conf.audio_listener_address else {
+            return;
+        };
+
+        if matches!(self.battle_patcher.state, BattlePatchState::Applied) {
+            self.battle_patcher
+                .patcher
+                .write(address as *mut data::AudioListenerPos, self.custom_camera.pos.into());
         }
     }
 
     pub unsafe fn run_battle_no_custom(
         &mut self,
         mouse_man: &mut MouseManager,
-        key_man: &mut KeyboardManager,
+        key_man: &mut InputManager,
         _t_delta: Duration,
         conf: &mut FreecamConfig,
     ) -> anyhow::Result<()> {
@@ -172,7 +958,7 @@ impl BattleState {
         let camera_pos = self.get_game_camera();
         let mut acceleration = Acceleration::default();
 
-        let (mut pitch, mut yaw) = calculate_pitch_yaw(camera_pos, target_pos);
+        let (mut pitch, mut yaw) = WorldPos::from(*camera_pos).pitch_yaw_towards(&(*target_pos).into());
 
         let mut point = POINT::default();
         GetCursorPos(&mut point)?;
@@ -186,66 +972,143 @@ impl BattleState {
         pitch += self.velocity.pitch;
         yaw += self.velocity.yaw;
 
-        self.velocity.pitch *= conf.camera.rotate_smoothing;
-        self.velocity.yaw *= conf.camera.rotate_smoothing;
+        self.velocity.pitch *= conf.camera.look_smoothing;
+        self.velocity.yaw *= conf.camera.look_smoothing;
 
         // Write to the addresses
-        write_pitch_yaw(camera_pos, target_pos, pitch, yaw);
+        let game_distance = game_target_distance(*camera_pos, *target_pos).unwrap_or(DEFAULT_TARGET_DISTANCE);
+        write_pitch_yaw(
+            camera_pos,
+            target_pos,
+            conf.camera.clamp_pitch(pitch),
+            yaw,
+            conf.camera.target_distance(game_distance),
+        );
         Ok(())
     }
 
     unsafe fn run_battle_custom_camera(
         &mut self,
         scroll: &mut MouseManager,
-        key_man: &mut KeyboardManager,
-        _t_delta: Duration,
+        key_man: &mut InputManager,
+        t_delta: Duration,
         conf: &mut FreecamConfig,
     ) -> anyhow::Result<()> {
         let camera_pos = self.get_game_camera();
         let mut acceleration = Acceleration::default();
         let (horizontal_speed, vertical_speed) = calculate_speed_multipliers(conf, key_man);
+        let landing_multiplier = self.landing_zone_multiplier(conf);
+        let (horizontal_speed, vertical_speed) = (
+            horizontal_speed * landing_multiplier,
+            vertical_speed * landing_multiplier,
+        );
 
         let mut point = POINT::default();
         GetCursorPos(&mut point)?;
 
         // If some external source modified it with our consent we should probably update our camera.
         // This can happen when the user double clicked on the map or a unit and started panning towards them.
-        if (self.custom_camera.x - camera_pos.x_coord).abs() > f32::EPSILON
-            || (self.custom_camera.y - camera_pos.y_coord).abs() > f32::EPSILON
-            || (self.custom_camera.z - camera_pos.z_coord).abs() > f32::EPSILON
+        let written = self.shaken_camera().pos;
+        if !self.captures.unit_card_teleport
+            && matches!(self.battle_patcher.state, BattlePatchState::Applied)
+            && WorldPos::from(*camera_pos).distance(&written) > GAME_CAMERA_MOVE_THRESHOLD
         {
-            self.sync_custom_camera();
-            // Track the last time we had to sync the data for use in a hack in `bc_restrict_coordinates`.
-            self.last_sync_time = Some(Instant::now());
+            // Without the intercept the game moves the camera itself, as only it writes to the camera while we're in
+            // control. Handled as a teleport by `bc_handle_camera_teleport` later this tick, rather than as a pan.
+            let teleport_location = self.remote_data.teleport_location.as_mut();
+            teleport_location.publish(*camera_pos, *self.get_game_target_camera());
+        } else if (written.x - camera_pos.x).abs() > f32::EPSILON
+            || (written.y - camera_pos.y).abs() > f32::EPSILON
+            || (written.z - camera_pos.z).abs() > f32::EPSILON
+        {
+            let target = self.game_camera_state();
+
+            // Some mods write garbage to the camera, which is simply overwritten by our next write instead.
+            if target.is_finite() {
+                self.game_camera_invalid = false;
+                self.remote_data
+                    .remote_z
+                    .store(target.pos.z.to_bits(), Ordering::SeqCst);
+
+                match &mut self.game_pan {
+                    // The game is still moving the camera, keep easing from our original position.
+                    Some(pan) => {
+                        pan.target = target;
+                        pan.last_write = Instant::now();
+                    }
+                    None => {
+                        self.game_pan = Some(GamePan {
+                            from: self.custom_camera.clone(),
+                            target,
+                            started: Instant::now(),
+                            last_write: Instant::now(),
+                        })
+                    }
+                }
+            } else {
+                if !self.game_camera_invalid {
+                    log::warn!("Ignoring invalid game camera: {:?}", target);
+                }
+                self.game_camera_invalid = true;
+            }
+        }
+
+        self.bc_follow_game_pan(conf);
+        self.bc_apply_sync_transition();
+        self.timings.mark("sync");
+
+        // Spectator mode, takes over all other movement while active.
+        if self.bc_handle_auto_director(key_man, conf) {
+            self.bc_update_shake(key_man, conf, t_delta);
+            self.write_full_custom_cam(camera_pos, conf);
+            return Ok(());
         }
 
         // Handle camera teleportation
-        self.bc_handle_camera_teleport(camera_pos);
+        self.bc_handle_camera_teleport(camera_pos, conf);
+        self.timings.mark("teleport");
 
         // Handle scroll
-        self.bc_handle_scroll(scroll, conf);
+        self.bc_handle_scroll(scroll, conf, &mut acceleration, t_delta, key_man.is_blocked());
+        self.timings.mark("scroll");
 
         // Adjust based on free-cam movement
         self.bc_handle_freecam_rotate(key_man, scroll, conf, &mut acceleration, point, true);
+        self.timings.mark("mouse look");
 
         // Camera movement
+        self.bc_handle_dolly_toggle(key_man, conf);
         self.bc_move_camera(key_man, conf, &mut acceleration);
+        if !key_man.is_blocked() {
+            self.bc_handle_edge_scroll(scroll, conf, &mut acceleration, point);
+        }
+        self.bc_handle_drag_pan(key_man, scroll, conf, point);
 
         // Rotation controls
         self.bc_handle_rotation(key_man, conf, &mut acceleration);
+        self.bc_apply_scripted_input(conf, &mut acceleration);
+        self.timings.mark("keyboard input");
 
         // Update velocity based on the new `acceleration`
-        Self::bc_calculate_next_velocity(
-            conf,
-            &mut self.velocity,
-            &acceleration,
-            horizontal_speed,
-            vertical_speed,
-        );
+        if self.dolly {
+            // Exactly the configured speed, regardless of the multipliers, smoothing, and distance to the ground.
+            let distance = conf.camera.dolly_speed * t_delta.as_secs_f32();
+            velocity::dolly_velocity(&mut self.velocity, &acceleration, distance);
+        } else {
+            velocity::calculate_next_velocity(
+                &mut self.velocity,
+                &acceleration,
+                horizontal_speed,
+                vertical_speed,
+                &conf.camera.smoothing(),
+                conf.camera.normalize_diagonal,
+            );
+            self.bc_soften_ground_approach(conf);
+        }
 
         // Modify our velocity depending on how close/far from the ground the camera is.
-        let distance_to_ground_multiplier = if conf.camera.ground_distance_speed {
-            (self.custom_camera.z - self.get_ground_z_level())
+        let distance_to_ground_multiplier = if conf.camera.ground_distance_speed && !self.dolly {
+            (self.custom_camera.pos.z - self.get_ground_z_level())
                 .div(2.)
                 .abs()
                 .add(1.0)
@@ -254,90 +1117,295 @@ impl BattleState {
         } else {
             1.
         };
-        self.custom_camera.x += self.velocity.x * distance_to_ground_multiplier;
-        self.custom_camera.y += self.velocity.y * distance_to_ground_multiplier;
-        self.custom_camera.z += self.velocity.z * distance_to_ground_multiplier;
-        self.custom_camera.pitch += self.velocity.pitch;
+        // The speed limit applies to the actual movement, so account for the multiplier applied below.
+        if !self.dolly {
+            conf.camera.clamp_velocity(
+                &mut self.velocity,
+                t_delta.as_secs_f32() / distance_to_ground_multiplier,
+            );
+        }
+        self.custom_camera.pos.x += self.velocity.x * distance_to_ground_multiplier;
+        self.custom_camera.pos.y += self.velocity.y * distance_to_ground_multiplier;
+        self.custom_camera.pos.z += self.velocity.z * distance_to_ground_multiplier;
+        self.custom_camera.pitch = conf.camera.clamp_pitch(self.custom_camera.pitch + self.velocity.pitch);
         self.custom_camera.yaw += self.velocity.yaw;
+        self.bc_snap_angles(conf);
 
-        Self::bc_smooth_decay_velocity(&mut self.velocity, conf);
+        self.smoother.decay(
+            &mut self.velocity,
+            &conf.camera.smoothing(),
+            &conf.camera.smoothing_models,
+        );
+        self.timings.mark("velocity");
 
         self.bc_restrict_coordinates(&acceleration, conf);
+        self.bc_altitude_readout(conf);
+        self.timings.mark("restrict");
+
+        self.bc_update_shake(key_man, conf, t_delta);
 
         if matches!(self.battle_patcher.state, BattlePatchState::Applied) {
-            self.write_full_custom_cam(camera_pos);
+            self.write_full_custom_cam(camera_pos, conf);
         } else {
             // Update our custom camera values.
             self.sync_custom_camera();
         }
+        self.timings.mark("write");
 
         Ok(())
     }
 
-    /// Handle the case where a user double clicks a unit card, and then presses a movement key to instantly teleport the
-    /// camera toward the given unit.
-    unsafe fn bc_handle_camera_teleport(&mut self, camera_pos: &mut BattleCameraView) {
+    /// Handle the case where a user double clicks a unit card, and then presses a movement key to teleport the camera
+    /// toward the given unit.
+    ///
+    /// The camera either snaps to the unit instantly, or flies there over the `teleport_transition_duration`.
+    unsafe fn bc_handle_camera_teleport(&mut self, camera_pos: &mut BattleCameraView, conf: &FreecamConfig) {
         let teleport_location = self.remote_data.teleport_location.as_mut();
-        // Check if all are different (in case of mid-write check).
-        if teleport_location.is_available() {
-            log::info!("Teleporting camera to: {:#?}", teleport_location);
-            self.custom_camera.x = teleport_location.x;
-            self.custom_camera.y = teleport_location.y;
-            self.custom_camera.z = teleport_location.z;
-
-            let target_pos = BattleCameraTargetView {
-                x_coord: teleport_location.x_target,
-                z_coord: teleport_location.z_target,
-                y_coord: teleport_location.y_target,
-            };
-            let view_struct = BattleCameraView {
-                x_coord: teleport_location.x,
-                z_coord: teleport_location.z,
-                y_coord: teleport_location.y,
-            };
-            let (pitch, yaw) = calculate_pitch_yaw(&view_struct, &target_pos);
-            self.custom_camera.pitch = pitch;
-            self.custom_camera.yaw = yaw;
+        let completed =
+            BattleUnitCameraTeleport::read_completed(&self.remote_data.teleport_location, self.teleports.sequence);
+
+        if teleport_location.is_pending() {
+            let pending_since = *self.teleports.pending_since.get_or_insert_with(Instant::now);
+
+            if completed.is_none() && pending_since.elapsed() > TELEPORT_EXPIRY {
+                self.teleports.expired += 1;
+                self.teleports.pending_since = None;
+                if conf.log_teleport_events {
+                    log::debug!(
+                        "Unit card teleport #{} expired after {:?}, incomplete values: {:?}",
+                        self.teleports.expired,
+                        pending_since.elapsed(),
+                        teleport_location
+                    );
+                }
+                teleport_location.clear();
+            }
+        }
+
+        let Some(teleport) = completed else {
+            return;
+        };
+        self.teleports.sequence = teleport.sequence;
+        // Reset values.
+        teleport_location.clear();
+
+        if !teleport.is_pending() {
+            // Completed after it expired (and was cleared), so there's nothing left to teleport to.
+            return;
+        }
+
+        log::info!("Teleporting camera to: {:#?}", teleport);
+        self.teleports.consumed += 1;
+
+        if let Some(pending_since) = self.teleports.pending_since.take() {
+            if conf.log_teleport_events {
+                log::debug!(
+                    "Unit card teleport #{} consumed {:?} after it was first written, camera: {:?}, target: {:?}",
+                    self.teleports.consumed,
+                    pending_since.elapsed(),
+                    teleport.camera,
+                    teleport.target
+                );
+            }
+        }
+        let camera = WorldPos::from(teleport.camera);
+        let (pitch, yaw) = camera.pitch_yaw_towards(&teleport.target.into());
+        let destination = CustomCameraState {
+            pos: camera,
+            pitch,
+            yaw,
+        };
+
+        if !conf.camera.teleport_transition_duration.is_zero() {
+            // Any leftover momentum would otherwise fight the flight path.
+            self.velocity = Velocity::default();
+            self.smoother.reset();
+            self.scroll_zoom.clear();
+            self.sync_transition = Some(SyncTransition {
+                from: self.custom_camera.clone(),
+                to: destination,
+                started: Instant::now(),
+                duration: conf.camera.teleport_transition_duration,
+                easing: conf.camera.teleport_transition_easing,
+            });
+            self.change_battle_state(false);
+            return;
+        }
+
+        self.custom_camera = destination;
+
+        // Need to update the game height here manually or we risk a race condition where the `z_diff` will make
+        // the camera jump up/down on the next frame.
+        self.write_full_custom_cam(camera_pos, conf);
+        #[cfg(feature = "game-thread-teleport")]
+        if let Some(teleporter) = &self.game_teleporter {
+            // Lets the game update its dependent state (audio listener, LOD, etc.) on its own thread.
+            teleporter.queue_teleport(*camera_pos, *self.get_game_target_camera());
+        }
+        self.force_game_height_eval(conf);
+        // Update for maintaining relative height
+        self.z_diff = self.custom_camera.pos.z - self.get_ground_z_level();
+    }
+
+    /// Move our custom camera along with the game's pan, see [GamePan].
+    ///
+    /// Once the game stops writing to the camera for the `relative_height_panning_delay` the pan is finished, and our
+    /// camera keeps the relative height the game left it at.
+    fn bc_follow_game_pan(&mut self, conf: &FreecamConfig) {
+        let Some(pan) = &self.game_pan else {
+            return;
+        };
+
+        let progress = pan.started.elapsed().as_secs_f32() / conf.camera.sync_transition_duration.as_secs_f32();
+        let eased = progress < 1. && !progress.is_nan();
+
+        self.custom_camera = if eased {
+            pan.from
+                .lerp(&pan.target, conf.camera.sync_transition_easing.apply(progress))
+        } else {
+            pan.target.clone()
+        };
+
+        if !eased && pan.last_write.elapsed() > conf.camera.relative_height_panning_delay {
+            self.z_diff = self.custom_camera.pos.z - self.get_ground_z_level();
+            self.game_pan = None;
+        }
+    }
+
+    /// Ease our custom camera towards a teleport destination, instead of snapping to it instantly.
+    fn bc_apply_sync_transition(&mut self) {
+        let Some(transition) = &self.sync_transition else {
+            return;
+        };
 
-            // Reset values.
-            *teleport_location = Default::default();
+        let progress = transition.started.elapsed().as_secs_f32() / transition.duration.as_secs_f32();
 
-            // Need to update the game height here manually or we risk a race condition where the `z_diff` will make
-            // the camera jump up/down on the next frame.
-            self.write_full_custom_cam(camera_pos);
-            self.force_game_height_eval();
-            // Update for maintaining relative height
-            self.z_diff = self.custom_camera.z - self.get_ground_z_level();
+        if progress >= 1. || progress.is_nan() {
+            self.custom_camera = transition.to.clone();
+            // Maintain the relative height we arrived at, rather than the one from before the teleport.
+            self.z_diff = self.custom_camera.pos.z - self.get_ground_z_level();
+
+            self.sync_transition = None;
+        } else {
+            let t = transition.easing.apply(progress);
+            self.custom_camera = transition.from.lerp(&transition.to, t);
         }
     }
 
-    fn bc_handle_scroll(&mut self, scroll: &mut MouseManager, conf: &FreecamConfig) {
-        let scroll_delta = scroll.get_scroll_delta() * if conf.camera.inverted_scroll { -1 } else { 1 };
-        let is_negative = if scroll_delta != 0 { scroll_delta.abs() / scroll_delta } else { 1 };
-        self.velocity.z += (scroll_delta.pow(2) * is_negative) as f32 * conf.camera.vertical_base_speed / 4.;
+    /// Let the [AutoDirector] move the camera, started and stopped with the `auto_director.key`.
+    ///
+    /// Returns `true` while the auto director is active, during which the user's camera input is ignored.
+    unsafe fn bc_handle_auto_director(&mut self, key_man: &mut InputManager, conf: &FreecamConfig) -> bool {
+        let director_conf = &conf.auto_director;
+
+        if let Some(key) = &director_conf.key {
+            if matches!(key_man.binding_state(key), KeyState::Pressed) {
+                if self.auto_director.take().is_some() {
+                    overlay::notify("Auto director stopped");
+                } else {
+                    overlay::notify("Auto director started");
+                    self.auto_director = Some(AutoDirector::default());
+                    self.change_battle_state(false);
+                }
+                self.velocity = Velocity::default();
+                self.smoother.reset();
+            }
+        }
+
+        let Some(director) = self.auto_director.as_mut() else {
+            return false;
+        };
+
+        let camera = &self.custom_camera;
+        let (pos, pitch, yaw) = director.update(
+            director_conf,
+            (camera.pos, camera.pitch, camera.yaw),
+            PointOfInterest::position,
+        );
+        self.custom_camera = CustomCameraState { pos, pitch, yaw };
+
+        true
+    }
+
+    /// Zoom based on the scrolled notches, shaped by the [crate::config::CameraConfig::scroll_response].
+    ///
+    /// Scrolling while `blocked` (see [InputManager::is_blocked]) is discarded, but earlier zoom steps still play out.
+    fn bc_handle_scroll(
+        &mut self,
+        scroll: &mut MouseManager,
+        conf: &FreecamConfig,
+        acceleration: &mut Velocity,
+        t_delta: Duration,
+        blocked: bool,
+    ) {
+        let camera = &conf.camera;
+        let notches = scroll.get_scroll_delta() * if camera.inverted_scroll { -1. } else { 1. };
+        let notches = if blocked { 0. } else { notches };
+        // Always read, so scrolling done while blocked doesn't all apply at once afterwards.
+        let horizontal = scroll.get_horizontal_scroll_delta() * camera.scroll_speed;
+
+        if horizontal != 0. && !blocked {
+            match camera.horizontal_scroll {
+                HorizontalScroll::Ignore => {}
+                HorizontalScroll::Pan => {
+                    // Capped at the speed of a held key. Without the `normalize_diagonal` a fast scroll would otherwise
+                    // pan several times faster than that, while small (touchpad) scrolls should still pan slower.
+                    let horizontal = horizontal.clamp(-1., 1.);
+                    add_planar_acceleration(self.custom_camera.yaw, 0., -horizontal, acceleration);
+                    self.change_battle_state(false);
+                }
+                HorizontalScroll::Rotate => {
+                    acceleration.yaw -= 0.03 * camera.look_acceleration * camera.keyboard_look_speed * horizontal;
+                    self.change_battle_state(false);
+                }
+            }
+        }
+
+        self.scroll_zoom
+            .push(camera.scroll_response.step(notches, camera.scroll_max_step));
+
+        let zoom = self
+            .scroll_zoom
+            .release(t_delta.as_secs_f32(), camera.scroll_smoothing_window.as_secs_f32());
+        self.velocity.z += zoom * camera.scroll_speed * camera.vertical_base_speed / 4.;
     }
 
     unsafe fn bc_handle_freecam_rotate(
         &mut self,
-        key_man: &mut KeyboardManager,
+        key_man: &mut InputManager,
         mouse_man: &mut MouseManager,
         conf: &mut FreecamConfig,
         acceleration: &mut Velocity,
         point: POINT,
         should_change_b_state: bool,
     ) {
-        let state = key_man.get_key_state(conf.keybinds.freecam_key.into());
-        match state {
+        match self.freecam_key_state(key_man, conf) {
             KeyState::Pressed => {
                 let _ = GetCursorPos(self.last_cursor_pos_freecam.get_or_insert(POINT::default()));
+                self.look_filter.0.reset();
+                self.look_filter.1.reset();
+                self.last_look_input = Some(Instant::now());
                 mouse_man.hide_cursor();
+                mouse_man.clip_cursor();
             }
             KeyState::Down => {
                 if let Some(pos) = self.last_cursor_pos_freecam.as_ref() {
-                    let invert = if conf.camera.inverted { -1.0 } else { 1.0 };
-                    let adjusted_sens = conf.camera.sensitivity * (1. - conf.camera.rotate_smoothing);
-                    acceleration.pitch -= ((invert * (point.y - pos.y) as f32) / 500.) * adjusted_sens;
-                    acceleration.yaw -= ((invert * (point.x - pos.x) as f32) / 500.) * adjusted_sens;
+                    let invert_pitch = if conf.camera.invert_pitch { -1.0 } else { 1.0 };
+                    let invert_yaw = if conf.camera.invert_yaw { -1.0 } else { 1.0 };
+                    let adjusted_sens =
+                        conf.camera.sensitivity * conf.camera.look_acceleration * mouse_scale(mouse_man, conf);
+
+                    let now = Instant::now();
+                    let dt = self
+                        .last_look_input
+                        .replace(now)
+                        .map_or(0., |last| (now - last).as_secs_f32());
+                    let (filter_y, filter_x) = &mut self.look_filter;
+                    let delta_y = filter_y.filter(conf.camera.look_filter, (point.y - pos.y) as f32, dt);
+                    let delta_x = filter_x.filter(conf.camera.look_filter, (point.x - pos.x) as f32, dt);
+
+                    acceleration.pitch -= ((invert_pitch * delta_y) / 500.) * adjusted_sens;
+                    acceleration.yaw -= ((invert_yaw * delta_x) / 500.) * adjusted_sens;
 
                     // Reset the cursor position to our set place.
                     let _ = SetCursorPos(pos.x, pos.y);
@@ -348,10 +1416,119 @@ impl BattleState {
                     }
                 }
             }
+            KeyState::Released => self.end_mouse_look(mouse_man),
+            KeyState::Up => {}
+        }
+    }
+
+    /// The state of the `freecam_key`, treating it as held down for as long as looking around is toggled on in
+    /// [FreecamMode::Toggle].
+    fn freecam_key_state(&mut self, key_man: &mut InputManager, conf: &FreecamConfig) -> KeyState {
+        let state = key_man.binding_state(&conf.keybinds.freecam_key);
+
+        match conf.keybinds.freecam_mode {
+            FreecamMode::Hold => state,
+            FreecamMode::Toggle => {
+                let was_toggled = self.mouse_look_toggled;
+                if matches!(state, KeyState::Pressed) {
+                    self.mouse_look_toggled = !was_toggled;
+                }
+
+                match (was_toggled, self.mouse_look_toggled) {
+                    (false, true) => KeyState::Pressed,
+                    (true, true) => KeyState::Down,
+                    (true, false) => KeyState::Released,
+                    (false, false) => KeyState::Up,
+                }
+            }
+        }
+    }
+
+    /// Move the positions the cursor is reset to while looking around/drag panning into the new `client_rect`.
+    ///
+    /// After a resolution change they could otherwise be outside the window, or even the screen.
+    pub fn fit_cursor_to_window(&mut self, client_rect: RECT) {
+        let centre = POINT {
+            x: (client_rect.left + client_rect.right) / 2,
+            y: (client_rect.top + client_rect.bottom) / 2,
+        };
+
+        for pos in [&mut self.last_cursor_pos_freecam, &mut self.last_cursor_pos_drag]
+            .into_iter()
+            .flatten()
+        {
+            if pos.x < client_rect.left
+                || pos.x >= client_rect.right
+                || pos.y < client_rect.top
+                || pos.y >= client_rect.bottom
+            {
+                log::debug!("Moving the cursor reset position {:?} into the resized window", pos);
+                *pos = centre;
+            }
+        }
+    }
+
+    /// Stop looking around or drag panning with the mouse (if we were), restoring the cursor where it was.
+    pub unsafe fn end_mouse_look(&mut self, mouse_man: &mut MouseManager) {
+        self.mouse_look_toggled = false;
+
+        for pos in [self.last_cursor_pos_freecam.take(), self.last_cursor_pos_drag.take()]
+            .into_iter()
+            .flatten()
+        {
+            let _ = SetCursorPos(pos.x, pos.y);
+            mouse_man.show_cursor();
+            mouse_man.release_cursor_clip();
+        }
+    }
+
+    /// Replacement for the vanilla middle mouse drag panning, moves the camera laterally as if dragging the map.
+    ///
+    /// Directly modifies our velocity, as the `acceleration` is normalised and would lose the drag distance.
+    unsafe fn bc_handle_drag_pan(
+        &mut self,
+        key_man: &mut InputManager,
+        mouse_man: &mut MouseManager,
+        conf: &FreecamConfig,
+        point: POINT,
+    ) {
+        /// World units of velocity per pixel dragged.
+        const DRAG_PAN_SCALE: f32 = 0.15;
+
+        let Some(drag_key) = &conf.keybinds.drag_pan_key else {
+            return;
+        };
+
+        match key_man.binding_state(drag_key) {
+            KeyState::Pressed => {
+                let _ = GetCursorPos(self.last_cursor_pos_drag.get_or_insert(POINT::default()));
+                mouse_man.hide_cursor();
+                mouse_man.clip_cursor();
+            }
+            KeyState::Down => {
+                if let Some(pos) = self.last_cursor_pos_drag.as_ref() {
+                    let scale = DRAG_PAN_SCALE
+                        * conf.camera.drag_pan_speed
+                        * mouse_scale(mouse_man, conf)
+                        * (1. - conf.camera.horizontal_smoothing);
+                    // Dragging the cursor down/right pulls the map along, moving the camera forward/left.
+                    let forward = (point.y - pos.y) as f32 * scale;
+                    let invert = if conf.camera.invert_horizontal_drag { -1.0 } else { 1.0 };
+                    let left = invert * (point.x - pos.x) as f32 * scale;
+                    let yaw = self.custom_camera.yaw;
+
+                    self.velocity.x += yaw.cos() * forward + ((PI / 2.) + yaw).cos() * left;
+                    self.velocity.y += yaw.sin() * forward + ((PI / 2.) + yaw).sin() * left;
+
+                    let _ = SetCursorPos(pos.x, pos.y);
+                    self.change_battle_state(false);
+                }
+            }
             KeyState::Released => {
-                if let Some(pos) = self.last_cursor_pos_freecam.take() {
+                if let Some(pos) = self.last_cursor_pos_drag.take() {
                     let _ = SetCursorPos(pos.x, pos.y);
                     mouse_man.show_cursor();
+                    mouse_man.release_cursor_clip();
                 }
             }
             KeyState::Up => {}
@@ -360,75 +1537,160 @@ impl BattleState {
 
     fn bc_handle_rotation(
         &mut self,
-        key_man: &mut KeyboardManager,
+        key_man: &mut InputManager,
         conf: &mut FreecamConfig,
         acceleration: &mut Velocity,
     ) {
-        let pan_speed = 1. - conf.camera.rotate_smoothing;
-        if key_man.has_pressed(conf.keybinds.rotate_left.into()) {
-            acceleration.yaw += 0.03 * pan_speed;
+        if rotation_acceleration(key_man, conf, &mut self.keyboard_look_ramp, acceleration) {
             self.change_battle_state(false);
         }
-        if key_man.has_pressed(conf.keybinds.rotate_right.into()) {
-            acceleration.yaw -= 0.03 * pan_speed;
-            self.change_battle_state(false);
+    }
+
+    fn bc_apply_scripted_input(&mut self, conf: &FreecamConfig, acceleration: &mut Velocity) {
+        let input = self.scripted_input;
+        if input == ScriptedInput::default() {
+            return;
         }
+
+        add_planar_acceleration(self.custom_camera.yaw, input.forward, 0., acceleration);
+        acceleration.yaw += 0.03 * conf.camera.look_acceleration * conf.camera.keyboard_look_speed * input.rotate;
+        acceleration.z += input.vertical;
+        self.change_battle_state(false);
     }
 
-    fn bc_move_camera(&mut self, key_man: &mut KeyboardManager, conf: &FreecamConfig, acceleration: &mut Velocity) {
-        let yaw = self.custom_camera.yaw;
-        if key_man.has_pressed(conf.keybinds.forward_key.into()) {
-            acceleration.y += yaw.sin();
-            acceleration.x += yaw.cos();
-            self.change_battle_state(false);
+    /// Toggle the dolly mode with the `dolly_key`, in which the camera moves at exactly the `dolly_speed` in the held
+    /// direction, without any acceleration or smoothing. Only lasts for the current battle.
+    fn bc_handle_dolly_toggle(&mut self, key_man: &mut InputManager, conf: &FreecamConfig) {
+        let Some(key) = &conf.keybinds.dolly_key else {
+            return;
+        };
+        if !matches!(key_man.binding_state(key), KeyState::Pressed) {
+            return;
         }
-        if key_man.has_pressed(conf.keybinds.backwards_key.into()) {
-            acceleration.y += (PI + yaw).sin();
-            acceleration.x += (PI + yaw).cos();
+
+        self.dolly = !self.dolly;
+        overlay::notify(if self.dolly { "Dolly mode on" } else { "Dolly mode off" });
+        // Neither mode should inherit the other's momentum.
+        self.velocity = Velocity::default();
+        self.smoother.reset();
+    }
+
+    fn bc_move_camera(&mut self, key_man: &mut InputManager, conf: &FreecamConfig, acceleration: &mut Velocity) {
+        if movement_acceleration(key_man, conf, self.custom_camera.yaw, acceleration) {
             self.change_battle_state(false);
         }
-        if key_man.has_pressed(conf.keybinds.left_key.into()) {
-            acceleration.y += ((PI / 2.) + yaw).sin();
-            acceleration.x += ((PI / 2.) + yaw).cos();
-            self.change_battle_state(false);
+    }
+
+    /// Move the camera while the cursor is near the window's edge, replacing the game's own edge scrolling.
+    fn bc_handle_edge_scroll(
+        &mut self,
+        mouse_man: &MouseManager,
+        conf: &FreecamConfig,
+        acceleration: &mut Velocity,
+        point: POINT,
+    ) {
+        let Some(margin) = conf.camera.edge_scroll_margin else {
+            return;
+        };
+
+        // The cursor is hidden and reset every tick while rotating/dragging, it's not actually at the edge.
+        if self.last_cursor_pos_freecam.is_some() || self.last_cursor_pos_drag.is_some() {
+            return;
         }
-        if key_man.has_pressed(conf.keybinds.right_key.into()) {
-            acceleration.y += ((3. * PI / 2.) + yaw).sin();
-            acceleration.x += ((3. * PI / 2.) + yaw).cos();
+
+        let (forward, left) = mouse_man.edge_scroll_direction(point, margin);
+
+        if forward != 0. || left != 0. {
+            add_planar_acceleration(self.custom_camera.yaw, forward, left, acceleration);
             self.change_battle_state(false);
         }
     }
 
-    fn bc_restrict_coordinates(&mut self, acceleration: &Acceleration, conf: &mut FreecamConfig) {
-        self.custom_camera.x = 900.0f32.min((-900.0f32).max(self.custom_camera.x));
-        self.custom_camera.y = 900.0f32.min((-900.0f32).max(self.custom_camera.y));
-        self.custom_camera.z = 2400.0f32.min(self.custom_camera.z);
+    /// Periodically log the camera's altitude and distance to the ground, for tuning the altitude/ground clip settings.
+    fn bc_altitude_readout(&mut self, conf: &FreecamConfig) {
+        if !conf.camera.altitude_readout
+            || self
+                .last_altitude_readout
+                .is_some_and(|last| last.elapsed() < Duration::from_secs(1))
+        {
+            return;
+        }
+
+        log::info!(
+            "Altitude: {:.2}, distance to ground: {:.2}",
+            self.custom_camera.pos.z,
+            self.custom_camera.pos.z - self.get_ground_z_level()
+        );
+        self.last_altitude_readout = Some(Instant::now());
+    }
 
-        // TODO: Add a new camera position struct which stores the _final_ value of a camera movement through scroll.
-        // Then we can interpolate gradual movement between that state and the current camera position smoothly instead of jittery!
+    /// Softly pull the camera's pitch/yaw towards common angles once the user stops rotating, see
+    /// [crate::config::CameraConfig::angle_snap].
+    ///
+    /// Left alone while easing towards a camera state the game moved us to.
+    fn bc_snap_angles(&mut self, conf: &FreecamConfig) {
+        let Some(snap) = &conf.camera.angle_snap else {
+            return;
+        };
+        if self.sync_transition.is_some() || self.game_pan.is_some() {
+            return;
+        }
 
-        // This `last_sync_time` is not a pretty check (and fragile for poorer performance PCs),
-        // but it helps prevent buggy panning towards a particular point on the map (unit panning seems unaffected whether we have this or not).
-        // The main benefit of this is that we can get rid of double click detection entirely. Hack for a hack...
+        let pitch = snap.apply(self.custom_camera.pitch, self.velocity.pitch);
+        self.custom_camera.pitch = conf.camera.clamp_pitch(pitch);
+        self.custom_camera.yaw = snap.apply(self.custom_camera.yaw, self.velocity.yaw);
+    }
+
+    /// The speed multiplier of the [crate::config::CameraConfig::landing_zone] at the camera's current height.
+    ///
+    /// Always `1.0` while the ground level is unknown.
+    fn landing_zone_multiplier(&self, conf: &FreecamConfig) -> f32 {
+        match &conf.camera.landing_zone {
+            Some(zone) if self.get_ground_z_level() != 0. => {
+                zone.speed_multiplier(self.custom_camera.pos.z - self.get_ground_z_level())
+            }
+            _ => 1.,
+        }
+    }
+
+    /// Gradually slow down downward movement as the camera approaches the `ground_clip_margin`.
+    ///
+    /// Without this a fast zoom-in is halted abruptly by the ground clipping logic in [Self::bc_restrict_coordinates].
+    fn bc_soften_ground_approach(&mut self, conf: &FreecamConfig) {
+        let ground_z = self.get_ground_z_level();
+
+        if !conf.camera.prevent_ground_clipping || self.velocity.z >= 0. || ground_z == 0. {
+            return;
+        }
+
+        let height = self.custom_camera.pos.z - ground_z - conf.camera.ground_clip_margin;
+        self.velocity.z *= bounds::ground_approach_factor(height, conf.camera.ground_slowdown_distance);
+    }
+
+    fn bc_restrict_coordinates(&mut self, acceleration: &Acceleration, conf: &mut FreecamConfig) {
+        self.custom_camera.pos = conf.camera.clamp_to_bounds(self.custom_camera.pos);
+
+        // Transitions and the game's own pans control the height themselves.
         if conf.camera.maintain_relative_height
-            && self
-                .last_sync_time
-                .as_ref()
-                .map(|s| s.elapsed() > conf.camera.relative_height_panning_delay)
-                .unwrap_or(true)
+            && self.captures.remote_z
+            && self.sync_transition.is_none()
+            && self.game_pan.is_none()
         {
-            let new_z_diff = self.custom_camera.z - self.get_ground_z_level();
+            let ground_z = self.get_ground_z_level();
+            let new_z_diff = self.custom_camera.pos.z - ground_z;
 
             if self.velocity.z.abs() > f32::EPSILON {
                 self.z_diff = new_z_diff;
+            } else if let Some(half_height) = conf.camera.relative_height_falloff {
+                // Only partially follow the terrain, and accept the resulting height as the new relative height.
+                let follow = bounds::terrain_follow_factor(self.z_diff, half_height);
+                self.custom_camera.pos.z += (self.z_diff - new_z_diff) * follow;
+                self.z_diff = self.custom_camera.pos.z - ground_z;
             } else if new_z_diff < self.z_diff {
-                self.custom_camera.z += self.z_diff - new_z_diff;
+                self.custom_camera.pos.z += self.z_diff - new_z_diff;
             } else if new_z_diff > self.z_diff {
-                self.custom_camera.z -= new_z_diff - self.z_diff;
+                self.custom_camera.pos.z -= new_z_diff - self.z_diff;
             }
-
-            // Can freely reset it now for a small performance improvement.
-            self.last_sync_time = None;
         }
 
         // If we're below the ground we should probably move up!
@@ -441,9 +1703,9 @@ impl BattleState {
             if self.get_ground_z_level() != 0.
                 && !z_bound.is_nan()
                 && z_bound.is_finite()
-                && ((self.custom_camera.z - self.get_ground_z_level()) < clip_margin)
+                && ((self.custom_camera.pos.z - self.get_ground_z_level()) < clip_margin)
             {
-                self.custom_camera.z = (self.get_ground_z_level() + clip_margin).max(self.custom_camera.z);
+                self.custom_camera.pos.z = (self.get_ground_z_level() + clip_margin).max(self.custom_camera.pos.z);
             }
 
             // Force the game to re-evaluate the ground position relative to the camera and update its Z coordinate.
@@ -456,51 +1718,49 @@ impl BattleState {
                 && (self.velocity.x.abs() > f32::EPSILON || self.velocity.y.abs() > f32::EPSILON)
             {
                 unsafe {
-                    self.force_game_height_eval();
+                    self.force_game_height_eval(conf);
                 }
             }
         }
     }
 
-    unsafe fn force_game_height_eval(&mut self) {
-        let remote_fn: data::CalcDeltaFn = std::mem::transmute(data::CALCULATE_DELTA_Z_TO_GROUND_FN_ADDR);
+    /// Force the game to re-evaluate the ground level below the camera.
+    ///
+    /// Calling the game's code from our thread races with the game itself, so this is guarded. It can be disabled with
+    /// [crate::config::CameraConfig::force_ground_height_eval], is rate limited, and disables itself for the rest of the
+    /// battle if the game's memory looks off. [Self::get_ground_z_level] then relies on the game's own evaluations.
+    unsafe fn force_game_height_eval(&mut self, conf: &FreecamConfig) {
+        if !conf.camera.force_ground_height_eval
+            || !self.captures.remote_z
+            || self.height_eval_disabled
+            || self
+                .last_height_eval
+                .is_some_and(|last| last.elapsed() < conf.camera.ground_height_eval_interval)
+        {
+            return;
+        }
+
+        if !memory::is_accessible(data::calculate_delta_z_to_ground_fn_addr())
+            || !memory::is_accessible(z_fix_delta_ground_addr() as usize)
+        {
+            log::warn!("Ground level function isn't accessible, no longer forcing ground evaluations this battle");
+            self.height_eval_disabled = true;
+            return;
+        }
+
+        let remote_fn: data::CalcDeltaFn = std::mem::transmute(data::calculate_delta_z_to_ground_fn_addr());
         // As far as I can tell in Ghidra this uses up to an offset of 0x8 based on the base pointer, so 3 values.
         // (Specifically, it seems like a delta for the x, z, y coordinates respectively?)
         // Might be wrong, in which case, stack corruption yay!
         let mut delta_maybe = [0.0, 0.0, 0.0];
         // Also, yes, this is completely unsafe when it comes to thread safety.
-        remote_fn(delta_maybe.as_mut_ptr(), Z_FIX_DELTA_GROUND_ADDR, 1.);
-    }
+        remote_fn(delta_maybe.as_mut_ptr(), z_fix_delta_ground_addr(), 1.);
+        self.last_height_eval = Some(Instant::now());
 
-    fn bc_calculate_next_velocity(
-        conf: &FreecamConfig,
-        current_velocity: &mut Velocity,
-        acceleration: &Acceleration,
-        horizontal_speed: f32,
-        vertical_speed: f32,
-    ) {
-        let mut length = (acceleration.x.powi(2) + acceleration.y.powi(2) + acceleration.z.powi(2)).sqrt();
-
-        if length == 0. {
-            length = 1.;
+        if !self.battle_patcher.patcher.read(z_fix_delta_ground_addr()).is_finite() {
+            log::warn!("Ground level evaluation returned garbage, no longer forcing ground evaluations this battle");
+            self.height_eval_disabled = true;
         }
-
-        current_velocity.x +=
-            ((acceleration.x / length) * (horizontal_speed * (1. - conf.camera.horizontal_smoothing))) / 2.;
-        current_velocity.y +=
-            ((acceleration.y / length) * (horizontal_speed * (1. - conf.camera.horizontal_smoothing))) / 2.;
-        current_velocity.z +=
-            ((acceleration.z / length) * (vertical_speed * (1. - conf.camera.vertical_smoothing))) / 2.;
-        current_velocity.pitch += acceleration.pitch;
-        current_velocity.yaw += acceleration.yaw;
-    }
-
-    fn bc_smooth_decay_velocity(velocity: &mut Velocity, conf: &FreecamConfig) {
-        velocity.x *= conf.camera.horizontal_smoothing;
-        velocity.y *= conf.camera.horizontal_smoothing;
-        velocity.z *= conf.camera.vertical_smoothing;
-        velocity.pitch *= conf.camera.rotate_smoothing;
-        velocity.yaw *= conf.camera.rotate_smoothing;
     }
 
     fn change_battle_state(&mut self, paused: bool) {
@@ -516,48 +1776,179 @@ impl BattleState {
     }
 
     unsafe fn sync_custom_camera(&mut self) {
+        let game_camera = self.game_camera_state();
+
+        if !game_camera.is_finite() {
+            // Adopting it would spread the `NaN`s through all our math, and lock up the camera until the next battle.
+            if !self.game_camera_invalid {
+                log::warn!(
+                    "The game's camera contains invalid values ({:?}), restoring our last camera: {:?}",
+                    game_camera,
+                    self.custom_camera
+                );
+                self.invalid_state_recoveries += 1;
+            }
+            self.game_camera_invalid = true;
+            self.bc_recover_invalid_state();
+
+            let camera_pos = self.get_game_camera();
+            write_custom_camera(&self.custom_camera, camera_pos);
+            write_pitch_yaw(
+                camera_pos,
+                self.get_game_target_camera(),
+                self.custom_camera.pitch,
+                self.custom_camera.yaw,
+                self.game_target_distance,
+            );
+            return;
+        }
+
+        self.game_camera_invalid = false;
+        if let Some(distance) = game_target_distance(*self.get_game_camera(), *self.get_game_target_camera()) {
+            self.game_target_distance = distance;
+        }
+        self.custom_camera = game_camera;
+        self.remote_data
+            .remote_z
+            .store(self.custom_camera.pos.z.to_bits(), Ordering::SeqCst);
+        self.bc_recover_invalid_state();
+    }
+
+    /// Restore the last valid camera if any of our state became `NaN` or infinite, or remember the current state as
+    /// valid otherwise.
+    ///
+    /// A single invalid value (e.g., read from the game's memory) would otherwise spread through all our movement math.
+    fn bc_recover_invalid_state(&mut self) {
+        if self.custom_camera.is_finite() && self.velocity.is_finite() && self.z_diff.is_finite() {
+            self.last_valid_camera = (self.custom_camera.clone(), self.z_diff);
+            return;
+        }
+
+        log::warn!(
+            "Camera state became invalid (camera: {:?}, velocity: {:?}, z_diff: {}), restoring {:?}",
+            self.custom_camera,
+            self.velocity,
+            self.z_diff,
+            self.last_valid_camera
+        );
+        self.invalid_state_recoveries += 1;
+        (self.custom_camera, self.z_diff) = self.last_valid_camera.clone();
+        self.velocity = Velocity::default();
+        self.smoother.reset();
+        self.scroll_zoom.clear();
+        self.sync_transition = None;
+        self.game_pan = None;
+    }
+
+    /// The camera state the game last wrote.
+    unsafe fn game_camera_state(&self) -> CustomCameraState {
         let target_pos = self.get_game_target_camera();
         let camera_pos = self.get_game_camera();
 
-        let (pitch, yaw) = calculate_pitch_yaw(camera_pos, target_pos);
+        let camera = WorldPos::from(*camera_pos);
+        let (pitch, yaw) = camera.pitch_yaw_towards(&(*target_pos).into());
 
-        self.custom_camera.x = camera_pos.x_coord;
-        self.custom_camera.y = camera_pos.y_coord;
-        self.custom_camera.z = camera_pos.z_coord;
-        self.remote_data
-            .remote_z
-            .store(self.custom_camera.z.to_bits(), Ordering::SeqCst);
-        self.custom_camera.pitch = pitch;
-        self.custom_camera.yaw = yaw;
+        CustomCameraState {
+            pos: camera,
+            pitch,
+            yaw,
+        }
     }
 
-    unsafe fn write_full_custom_cam(&mut self, camera_pos: &mut BattleCameraView) {
+    unsafe fn write_full_custom_cam(&mut self, camera_pos: &mut BattleCameraView, conf: &FreecamConfig) {
+        self.bc_recover_invalid_state();
+        let camera = self.shaken_camera();
+
         // Important that this runs _before_ pitch/yaw adjustment as they're dependent.
-        write_custom_camera(&self.custom_camera, camera_pos);
+        write_custom_camera(&camera, camera_pos);
 
         let target_pos = self.get_game_target_camera();
-        write_pitch_yaw(camera_pos, target_pos, self.custom_camera.pitch, self.custom_camera.yaw);
+        let pitch = conf.camera.clamp_pitch(camera.pitch);
+        let distance = conf.camera.target_distance(self.game_target_distance);
+        write_pitch_yaw(camera_pos, target_pos, pitch, camera.yaw, distance);
+    }
+
+    /// Our custom camera as it's written to the game, including the current camera shake.
+    fn shaken_camera(&self) -> CustomCameraState {
+        let (_, offset) = self.shake;
+        let pos = self.custom_camera.pos;
+
+        CustomCameraState {
+            pos: WorldPos::new(pos.x + offset.x, pos.y + offset.y, pos.z + offset.z),
+            pitch: self.custom_camera.pitch + offset.pitch,
+            yaw: self.custom_camera.yaw + offset.yaw,
+        }
+    }
+
+    /// Shake the camera when the `shake.key` is pressed, see [crate::config::ShakeConfig].
+    ///
+    /// The shake is only added to the camera written to the game, so it never affects our own movement.
+    unsafe fn bc_update_shake(&mut self, key_man: &mut InputManager, conf: &FreecamConfig, t_delta: Duration) {
+        let (shake, offset) = &mut self.shake;
+
+        if let Some(key) = &conf.shake.key {
+            if matches!(key_man.binding_state(key), KeyState::Pressed) {
+                shake.add_trauma(1.);
+            }
+        }
+
+        *offset = shake.update(&conf.shake.motion, t_delta.as_secs_f32());
     }
 
     /// Return the current ground z-level
     ///
-    /// We don't know the method/values directly, so we simply subtract the current [Z_FIX_DELTA_GROUND_ADDR] from the game's
+    /// We don't know the method/values directly, so we simply subtract the current [z_fix_delta_ground_addr] from the game's
     /// `remote_z` value.
     ///
     /// Note that this depends on the game's code updating these values. See [Self::force_game_height_eval] for forcing it.
+    /// Falls back to the last valid ground level if the values are currently garbage (e.g., mid-write).
+    ///
+    /// Always `0.0` (unknown) if `remote_z` isn't captured, see [RemoteCaptureConfig::remote_z].
     fn get_ground_z_level(&self) -> f32 {
-        unsafe {
+        if !self.captures.remote_z {
+            return 0.;
+        }
+
+        let ground_z = unsafe {
             f32::from_bits(self.remote_data.remote_z.load(Ordering::SeqCst))
-                - *self.battle_patcher.patcher.read(Z_FIX_DELTA_GROUND_ADDR)
+                - *self.battle_patcher.patcher.read(z_fix_delta_ground_addr())
+        };
+
+        if ground_z.is_finite() {
+            self.last_ground_z.set(ground_z);
+            ground_z
+        } else {
+            self.last_ground_z.get()
+        }
+    }
+
+    /// Restore the camera type the user had selected before we forced the TotalWar camera, if any.
+    unsafe fn restore_camera_type(&mut self) {
+        if let Some(original) = self.original_camera_type.take() {
+            log::debug!("Restoring original camera type: {}", original);
+            self.battle_patcher
+                .patcher
+                .write(data::battle_cam_conf_type_addr().cast::<u32>(), original);
         }
     }
 
     unsafe fn get_game_camera<'b>(&self) -> &'b mut BattleCameraView {
-        self.battle_patcher.patcher.mut_read(data::BATTLE_CAM_ADDR)
+        self.battle_patcher.patcher.mut_read(data::battle_cam_addr())
     }
 
     unsafe fn get_game_target_camera<'b>(&self) -> &'b mut BattleCameraTargetView {
-        self.battle_patcher.patcher.mut_read(data::BATTLE_CAM_TARGET_ADDR)
+        self.battle_patcher.patcher.mut_read(data::battle_cam_target_addr())
+    }
+}
+
+impl Drop for BattleState {
+    fn drop(&mut self) {
+        self.timings.log_summary();
+        self.battle_patcher.verification.log_summary();
+
+        unsafe {
+            self.restore_camera_type();
+        }
     }
 }
 
@@ -565,9 +1956,15 @@ pub struct BattlePatcher {
     patcher: LocalPatcher,
     special_patcher: LocalPatcher,
     _dynamic_patches: Vec<DynamicPatch>,
+    /// The patches of the `patcher` and `special_patcher`, verified whenever they're enabled.
+    expected: Vec<ExpectedPatch>,
+    special_expected: Vec<ExpectedPatch>,
+    verification: PatchVerification,
     state: BattlePatchState,
+    transitions: TransitionLog<BattlePatchState>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BattlePatchState {
     /// All patches are applied and full camera control is taken away from the game
     Applied,
@@ -578,38 +1975,137 @@ pub enum BattlePatchState {
 }
 
 impl BattlePatcher {
-    pub fn new(remote_data: &RemoteData) -> Self {
+    pub fn new(
+        remote_data: &RemoteData,
+        captures: RemoteCaptureConfig,
+        disabled_groups: &[PatchGroup],
+        foreign_patches: ForeignPatchHandling,
+    ) -> Self {
         let mut general_patcher = LocalPatcher::new();
         let mut special_patcher = LocalPatcher::new();
+        let mut expected = Vec::new();
+        let mut special_expected = Vec::new();
+
+        // Always initialise our patcher with all the requisite patches, skipping any which would crash the game.
+        let profile = profile::active();
+        if !disabled_groups.is_empty() {
+            log::warn!("Not applying the disabled patch groups: {:?}", disabled_groups);
+        }
+        let locations = profile
+            .patch_locations
+            .iter()
+            .filter(|(group, _)| !group.is_some_and(|group| disabled_groups.contains(&group)))
+            .map(|(_, location)| location)
+            .collect::<Vec<_>>();
+        let (accessible, inaccessible): (Vec<_>, Vec<_>) = locations
+            .iter()
+            .partition(|location| memory::is_accessible(location.address().get()));
+
+        let (original, modified): (Vec<_>, Vec<_>) = accessible
+            .into_iter()
+            .partition(|location| unsafe { patch_locations::is_original(location) });
+        let to_patch = match foreign_patches {
+            ForeignPatchHandling::Warn => original.iter().chain(&modified).collect::<Vec<_>>(),
+            ForeignPatchHandling::Skip => original.iter().collect(),
+        };
 
-        // Always initialise our patcher with all the requisite patches.
-        for patch in patch_locations::PATCH_LOCATIONS_STEAM {
+        for patch in to_patch {
             unsafe {
-                patch_locations::patch_logic(patch, &mut general_patcher);
+                expected.push(patch_locations::patch_logic(
+                    patch.address().get(),
+                    &mut general_patcher,
+                ));
             }
         }
+        if !inaccessible.is_empty() {
+            error_report::report(format!(
+                "{} of {} patch locations aren't accessible and were skipped, does the `patch_profile` match this game \
+                 executable? Skipped: {:?}",
+                inaccessible.len(),
+                locations.len(),
+                inaccessible.iter().map(|location| location.address()).collect::<Vec<_>>()
+            ));
+        }
+        if !modified.is_empty() {
+            error_report::report(format!(
+                "{} of {} patch locations were already modified, likely by another camera mod, and were {}: {:?}",
+                modified.len(),
+                locations.len(),
+                match foreign_patches {
+                    ForeignPatchHandling::Warn => "patched anyway (see `foreign_patches`)",
+                    ForeignPatchHandling::Skip => "skipped",
+                },
+                modified.iter().map(|location| location.address()).collect::<Vec<_>>()
+            ));
+        }
+
+        if captures.remote_z {
+            expected.extend(patches::apply_general_z_remote_patch(&mut general_patcher, remote_data));
+        } else {
+            log::info!("Not capturing `remote_z`, relative height and ground clipping prevention are disabled");
+        }
 
-        patches::apply_general_z_remote_patch(&mut general_patcher, remote_data);
         // Special (dynamic) patches.
-        let (teleport_patch, target_write_patch) = unsafe {
-            let (teleport_patch, target_write_patch) =
-                patches::create_unit_card_teleport_patch(remote_data.teleport_location.get_mut_ptr())
-                    .expect("Failed to create teleport patch");
-            teleport_patch.apply_to_patcher(&mut special_patcher);
-            target_write_patch.apply_to_patcher(&mut special_patcher);
-
-            (teleport_patch, target_write_patch)
+        let dynamic_patches = if captures.unit_card_teleport {
+            match unsafe { patches::create_unit_card_teleport_patch(remote_data.teleport_location.get_mut_ptr()) } {
+                Ok((teleport_patch, target_write_patch)) => {
+                    special_expected.push(teleport_patch.apply_to_patcher(&mut special_patcher));
+                    special_expected.push(target_write_patch.apply_to_patcher(&mut special_patcher));
+
+                    vec![teleport_patch, target_write_patch]
+                }
+                Err(e) => {
+                    error_report::report(format!(
+                        "Failed to create the unit card teleport patch, the game will move the camera itself: {}",
+                        e
+                    ));
+                    Vec::new()
+                }
+            }
+        } else {
+            log::info!("Not intercepting unit card teleports, the game will move the camera itself");
+            Vec::new()
         };
 
         Self {
             patcher: general_patcher,
             special_patcher,
-            _dynamic_patches: vec![teleport_patch, target_write_patch],
+            _dynamic_patches: dynamic_patches,
+            expected,
+            special_expected,
+            verification: PatchVerification::default(),
+            state: BattlePatchState::NotApplied,
+            transitions: TransitionLog::new("Patch state"),
+        }
+    }
+
+    /// Create a patcher without any patches, for the [FreecamConfig::safe_mode].
+    pub fn unpatched() -> Self {
+        Self {
+            patcher: LocalPatcher::new(),
+            special_patcher: LocalPatcher::new(),
+            _dynamic_patches: Vec::new(),
+            expected: Vec::new(),
+            special_expected: Vec::new(),
+            verification: PatchVerification::default(),
             state: BattlePatchState::NotApplied,
+            transitions: TransitionLog::new("Patch state"),
         }
     }
 
+    /// Enable or disable our patches, verifying any newly enabled ones actually ended up in memory.
+    ///
+    /// Once a verification fails all patches are rolled back, and stay [BattlePatchState::NotApplied] for the rest of
+    /// the battle.
     pub unsafe fn change_state(&mut self, new_state: BattlePatchState) {
+        if self.verification.is_rolled_back() {
+            return;
+        }
+        if new_state != self.state {
+            self.transitions.record(self.state, new_state);
+        }
+        let old_state = self.state;
+
         match self.state {
             BattlePatchState::Applied => match new_state {
                 BattlePatchState::Applied => {}
@@ -642,47 +2138,196 @@ impl BattlePatcher {
             },
         }
         self.state = new_state;
+
+        let general_enabled = new_state == BattlePatchState::Applied && old_state != BattlePatchState::Applied;
+        let special_enabled = new_state != BattlePatchState::NotApplied && old_state == BattlePatchState::NotApplied;
+        self.verify_enabled(general_enabled, special_enabled);
+    }
+
+    /// Whether our patches failed to apply and were rolled back, see [Self::change_state].
+    pub fn is_rolled_back(&self) -> bool {
+        self.verification.is_rolled_back()
     }
+
+    unsafe fn verify_enabled(&mut self, general: bool, special: bool) {
+        if !general && !special {
+            return;
+        }
+
+        let mut mismatches = Vec::new();
+        if general {
+            mismatches.extend(verification::verify(&self.expected));
+        }
+        if special {
+            mismatches.extend(verification::verify(&self.special_expected));
+        }
+        self.verification.record(&mismatches);
+
+        if mismatches.is_empty() {
+            return;
+        }
+
+        // Without the camera patches the game fights our custom camera, and without our custom camera nothing handles
+        // the special patches' captures. Everything is rolled back as a result.
+        self.patcher.disable_all_patches();
+        self.special_patcher.disable_all_patches();
+        self.transitions.record(self.state, BattlePatchState::NotApplied);
+        self.state = BattlePatchState::NotApplied;
+        self.verification.set_rolled_back();
+
+        let details = mismatches
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n  ");
+        log::error!("Patches which failed to apply:\n  {}", details);
+        error_report::report(format!(
+            "{} of our patches didn't apply, possibly due to an overlay (e.g. RTSS) changing the memory protection. \
+             All patches were rolled back, and only mouse-look is available for the rest of this battle.",
+            mismatches.len()
+        ));
+    }
+}
+
+/// Point the camera at the given `pitch` and `yaw`, which should already be clamped to the configured limits, by
+/// placing the target `distance` units away from the camera.
+///
+/// The pitch is still kept short of vertical, as the game would otherwise lose track of the yaw.
+fn write_pitch_yaw(
+    camera_pos: &BattleCameraView,
+    target_pos: &mut BattleCameraTargetView,
+    pitch: f32,
+    yaw: f32,
+    distance: f32,
+) {
+    let pitch = bounds::clamp_pitch_to(pitch, -bounds::VERTICAL_PITCH, bounds::VERTICAL_PITCH);
+
+    *target_pos = WorldPos::from(*camera_pos).look_target(pitch, yaw, distance).into();
 }
 
-fn write_pitch_yaw(camera_pos: &BattleCameraView, target_pos: &mut BattleCameraTargetView, mut pitch: f32, yaw: f32) {
-    pitch = pitch.max(-(PI / 2.) * 0.9);
-    pitch = pitch.min((PI / 2.) * 0.9);
+/// How far the game placed its `target` from its `camera`, `None` if it's invalid or too close to point the camera.
+pub fn game_target_distance(camera: impl Into<WorldPos>, target: impl Into<WorldPos>) -> Option<f32> {
+    let distance = camera.into().distance(&target.into());
 
-    target_pos.x_coord = (yaw.cos() * pitch.cos() * 1000.) + camera_pos.x_coord;
-    target_pos.y_coord = (yaw.sin() * pitch.cos() * 1000.) + camera_pos.y_coord;
-    target_pos.z_coord = (pitch.sin() * 1000.) + camera_pos.z_coord;
+    (distance.is_finite() && distance >= MIN_TARGET_DISTANCE).then_some(distance)
 }
 
 fn write_custom_camera(custom_cam: &CustomCameraState, camera_pos: &mut BattleCameraView) {
-    camera_pos.x_coord = custom_cam.x;
-    camera_pos.y_coord = custom_cam.y;
-    camera_pos.z_coord = custom_cam.z;
+    *camera_pos = custom_cam.pos.into();
 }
 
-fn calculate_pitch_yaw(camera_pos: &BattleCameraView, target_pos: &BattleCameraTargetView) -> (f32, f32) {
-    let length = ((target_pos.x_coord - camera_pos.x_coord).powi(2)
-        + (target_pos.y_coord - camera_pos.y_coord).powi(2)
-        + (target_pos.z_coord - camera_pos.z_coord).powi(2))
-    .sqrt();
+/// Add the rotation requested through the keyboard to `acceleration`.
+///
+/// Returns `true` if any rotation key was held.
+pub(crate) fn rotation_acceleration(
+    key_man: &mut InputManager,
+    conf: &FreecamConfig,
+    ramp: &mut KeyboardLookRamp,
+    acceleration: &mut Velocity,
+) -> bool {
+    let keys = &conf.keybinds;
+    let rotation = key_man.axis(&keys.rotate_right, &keys.rotate_left, keys.opposing_keys);
+    let pitch = key_man.optional_axis(keys.pitch_down.as_ref(), keys.pitch_up.as_ref(), keys.opposing_keys);
+    let held = [
+        Some(&keys.rotate_left),
+        Some(&keys.rotate_right),
+        keys.pitch_up.as_ref(),
+        keys.pitch_down.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .any(|binding| key_man.binding_down(binding));
+
+    let speed =
+        0.03 * conf.camera.look_acceleration * conf.camera.keyboard_look_speed * ramp.multiplier(&conf.camera, held);
+    acceleration.yaw += speed * rotation;
+    acceleration.pitch += speed * pitch;
+
+    held
+}
 
-    let mut pitch = ((target_pos.z_coord - camera_pos.z_coord) / length).asin();
-    let mut yaw =
-        ((target_pos.y_coord - camera_pos.y_coord) / length).atan2((target_pos.x_coord - camera_pos.x_coord) / length);
+/// Tracks how long the rotation keys have been held, see [crate::config::CameraConfig::keyboard_look_ramp].
+#[derive(Debug, Default)]
+pub(crate) struct KeyboardLookRamp {
+    held_since: Option<Instant>,
+}
 
-    if pitch.is_nan() {
-        pitch = 0.;
+impl KeyboardLookRamp {
+    /// The multiplier for the keyboard rotation speed, given whether any rotation key is currently `held`.
+    fn multiplier(&mut self, camera: &CameraConfig, held: bool) -> f32 {
+        if !held {
+            self.held_since = None;
+            return 1.;
+        }
+
+        let held_for = self.held_since.get_or_insert_with(Instant::now).elapsed();
+
+        if camera.keyboard_look_ramp.is_zero() {
+            return 1.;
+        }
+
+        let progress = (held_for.as_secs_f32() / camera.keyboard_look_ramp.as_secs_f32()).min(1.);
+        1. + (camera.keyboard_look_max_speed - 1.) * progress
     }
-    if yaw.is_nan() {
-        yaw = 0.;
+}
+
+/// Add the movement requested through the keyboard to `acceleration`, with horizontal movement relative to the camera's
+/// `yaw`.
+///
+/// Returns `true` if any movement key was held.
+pub(crate) fn movement_acceleration(
+    key_man: &mut InputManager,
+    conf: &FreecamConfig,
+    yaw: f32,
+    acceleration: &mut Velocity,
+) -> bool {
+    let keys = &conf.keybinds;
+    let mut forward = key_man.axis(&keys.backwards_key, &keys.forward_key, keys.opposing_keys);
+    let mut left = key_man.axis(&keys.right_key, &keys.left_key, keys.opposing_keys);
+    let vertical = key_man.optional_axis(keys.down_key.as_ref(), keys.up_key.as_ref(), keys.opposing_keys);
+    let mut moved = [&keys.forward_key, &keys.backwards_key, &keys.left_key, &keys.right_key]
+        .into_iter()
+        .chain(keys.up_key.iter().chain(&keys.down_key))
+        .any(|binding| key_man.binding_down(binding));
+
+    if conf.camera.arrow_keys_move_camera {
+        let [up, down, arrow_left, arrow_right] = &*ARROW_KEYS;
+        // Clamped so holding both `W` and `Up` isn't faster than either on its own.
+        forward = (forward + key_man.axis(down, up, keys.opposing_keys)).clamp(-1., 1.);
+        left = (left + key_man.axis(arrow_right, arrow_left, keys.opposing_keys)).clamp(-1., 1.);
+        moved |= ARROW_KEYS.iter().any(|binding| key_man.binding_down(binding));
     }
 
-    (pitch, yaw)
+    add_planar_acceleration(
+        yaw,
+        forward * conf.camera.forward_speed_scale,
+        left * conf.camera.strafe_speed_scale,
+        acceleration,
+    );
+    // Scaled by the `vertical_base_speed` along with the scroll wheel's movement.
+    acceleration.z += vertical;
+
+    moved
+}
+
+/// Add a `forward`/`left` movement relative to the camera's `yaw` to `acceleration`.
+fn add_planar_acceleration(yaw: f32, forward: f32, left: f32, acceleration: &mut Velocity) {
+    acceleration.y += yaw.sin() * forward + ((PI / 2.) + yaw).sin() * left;
+    acceleration.x += yaw.cos() * forward + ((PI / 2.) + yaw).cos() * left;
+}
+
+/// The multiplier for cursor movement in pixels, see [crate::config::CameraConfig::resolution_independent_mouse].
+fn mouse_scale(mouse_man: &MouseManager, conf: &FreecamConfig) -> f32 {
+    if conf.camera.resolution_independent_mouse {
+        mouse_man.display_scale()
+    } else {
+        1.
+    }
 }
 
-fn calculate_speed_multipliers(conf: &FreecamConfig, key_man: &mut KeyboardManager) -> (f32, f32) {
-    let has_fast = key_man.has_pressed(conf.keybinds.fast_key.into());
-    let has_slow = key_man.has_pressed(conf.keybinds.slow_key.into());
+pub(crate) fn calculate_speed_multipliers(conf: &FreecamConfig, key_man: &mut InputManager) -> (f32, f32) {
+    let has_fast = key_man.binding_down(&conf.keybinds.fast_key);
+    let has_slow = key_man.binding_down(&conf.keybinds.slow_key);
 
     let multiplier = if has_fast {
         conf.camera.fast_multiplier
@@ -1,5 +1,8 @@
 use rust_hooking_utils::patching::LocalPatcher;
 
+use crate::battle_cam::profile::PatchLocation;
+use crate::battle_cam::verification::ExpectedPatch;
+
 /// All locations where writes to camera coordinates occur.
 ///
 /// These patches can be disabled when needed to allow base-game functionality to happen (such as panning towards units upon double clicking).
@@ -49,12 +52,104 @@ pub const PATCH_LOCATIONS_STEAM: [usize; 63] = [
     0x0094E9BC, 0x008F9055,
 ];
 
-pub unsafe fn patch_logic(address: usize, patcher: &mut LocalPatcher) {
-    let length = if (*patcher.read(address as *const u8)) == 0xF3 { 5 } else { 3 };
-    //The 243 or F3 byte means that the operation in total is 5 bytes long.
-    //Otherwise the operation is 3 bytes long. This works for this program as these are the only possibilities
-    let to_patch = vec![0x90; length];
+/// How the [PATCH_LOCATIONS_STEAM] are divided into [PatchGroup]s, in order.
+pub const PATCH_GROUPS_STEAM: [(PatchGroup, usize); 6] = [
+    (PatchGroup::CameraX, 11),
+    (PatchGroup::CameraY, 11),
+    (PatchGroup::CameraZ, 10),
+    (PatchGroup::TargetX, 9),
+    (PatchGroup::TargetY, 9),
+    (PatchGroup::TargetZ, 13),
+];
+
+/// A named set of patches, which can be disabled as a whole to track down incompatibilities with other mods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub enum PatchGroup {
+    /// The game's writes to the camera position, which only have an effect if the `patch_profile` groups them.
+    CameraX,
+    CameraY,
+    CameraZ,
+    /// The game's writes to the camera target, which only have an effect if the `patch_profile` groups them.
+    TargetX,
+    TargetY,
+    TargetZ,
+    /// Intercepting double clicks on unit cards, see [crate::config::RemoteCaptureConfig::unit_card_teleport].
+    UnitCardTeleport,
+    /// Capturing the ground level, see [crate::config::RemoteCaptureConfig::remote_z].
+    RemoteZ,
+}
+
+impl PatchGroup {
+    /// Whether this group consists of [crate::battle_cam::profile::PatchProfile::patch_locations].
+    pub fn is_write_group(self) -> bool {
+        !matches!(self, PatchGroup::UnitCardTeleport | PatchGroup::RemoteZ)
+    }
+}
+
+/// What to do with patch locations which no longer contain the game's original instruction, see [is_original].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum ForeignPatchHandling {
+    /// Report the modified locations, but patch them anyway.
+    #[default]
+    Warn,
+    /// Report the modified locations, and leave them alone.
+    ///
+    /// The other mod then stays in control of those camera writes.
+    Skip,
+}
+
+/// The first bytes of the instructions found at every patch location, see [nop_patch].
+///
+/// `movss [..], xmm` for the 5 byte instructions, `fstp dword [..]` or `mov [..], reg` for the 3 byte ones. Only used
+/// for locations whose original bytes the profile doesn't specify.
+const ORIGINAL_OPCODES: [&[u8]; 3] = [&[0xF3, 0x0F, 0x11], &[0xD9], &[0x89]];
+
+/// Whether the instruction at the `location` is still the game's original camera write.
+///
+/// Other camera mods tend to overwrite the same instructions with `NOP`s or a jump to their own code, which we'd
+/// otherwise corrupt by patching them again. The instruction is compared exactly with the original bytes in the
+/// profile if it has them, or with the bytes it had when we patched it earlier. Without either, it only has to start
+/// with one of the [ORIGINAL_OPCODES].
+///
+/// # Safety
+/// `address..address + 5` must be readable.
+pub unsafe fn is_original(location: &PatchLocation) -> bool {
+    let address = location.address().cast::<u8>();
+    let current = std::slice::from_raw_parts(address, 5);
+
+    match crate::shutdown::original_bytes(address)
+        .as_deref()
+        .or(location.original())
+    {
+        Some(original) => current.starts_with(original),
+        None => ORIGINAL_OPCODES.iter().any(|opcode| current.starts_with(opcode)),
+    }
+}
+
+/// Whether the `original` bytes form a single instruction of the length [nop_patch] would replace.
+pub fn is_valid_original(original: &[u8]) -> bool {
+    original
+        .first()
+        .is_some_and(|first_byte| nop_patch(*first_byte).len() == original.len())
+}
+
+pub unsafe fn patch_logic(address: usize, patcher: &mut LocalPatcher) -> ExpectedPatch {
+    let to_patch = nop_patch(*patcher.read(address as *const u8));
 
     // Don't immediately activate the patches, causes crashes.
+    crate::shutdown::track_patch(address as *const u8, to_patch.len());
     patcher.patch(address as *mut u8, &to_patch, false);
+
+    ExpectedPatch {
+        address,
+        bytes: to_patch.into(),
+    }
+}
+
+/// Create the `NOP` patch for the instruction starting with the given `first_byte`.
+pub fn nop_patch(first_byte: u8) -> Vec<u8> {
+    //The 243 or F3 byte means that the operation in total is 5 bytes long.
+    //Otherwise the operation is 3 bytes long. This works for this program as these are the only possibilities
+    let length = if first_byte == 0xF3 { 5 } else { 3 };
+    vec![0x90; length]
 }
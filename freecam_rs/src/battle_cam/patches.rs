@@ -1,4 +1,7 @@
+use crate::battle_cam::coords::GamePos;
 use crate::battle_cam::data::GameCell;
+use crate::battle_cam::profile::{self, NonNullPtr};
+use crate::battle_cam::verification::ExpectedPatch;
 use iced_x86::code_asm::{dword_ptr, eax, ebx, esi, esp, CodeAssembler};
 use rust_hooking_utils::patching::LocalPatcher;
 use std::fmt::{Debug, Formatter};
@@ -12,6 +15,9 @@ use std::sync::Arc;
 pub struct RemoteData {
     /// Contains the values for a camera teleport. Relevant for when a unit card is double clicked (and a user presses a movement button after).
     pub teleport_location: Arc<GameCell<BattleUnitCameraTeleport>>,
+    /// The camera and target the game itself last wrote (while our custom camera wasn't in control), all `0.0` if it
+    /// hasn't yet. Restored once the battle is left, see [crate::battle_cam::BattleState::restore_vanilla_camera].
+    pub vanilla_camera: Arc<GameCell<BattleUnitCameraTeleport>>,
     /// The `remote_z` value is the value that the game _would've_ written to the camera's `z` coordinate if those writes
     /// weren't patched out. We instead redirect those writes to this variable to make use of it later to calculate the
     /// ground's `z` coordinates. Note that this `remote_z` seems to completely ignore the values we write to the rendered camera's address.
@@ -25,6 +31,7 @@ impl Debug for RemoteData {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RemoteData")
             .field("teleport_location", self.teleport_location.as_ref())
+            .field("vanilla_camera", self.vanilla_camera.as_ref())
             .field("remote_z", &f32::from_bits(self.remote_z.load(Ordering::SeqCst)))
             .finish()
     }
@@ -34,26 +41,51 @@ impl Debug for RemoteData {
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
 #[repr(C)]
 pub struct BattleUnitCameraTeleport {
-    pub x: f32,
-    pub z: f32,
-    pub y: f32,
-    pub x_target: f32,
-    pub z_target: f32,
-    pub y_target: f32,
+    pub camera: GamePos,
+    pub target: GamePos,
+    /// Incremented after every complete write of the `camera` and `target`, both by the game's (patched) code and by
+    /// [Self::publish]. Never reset, so it can be compared against the last teleport we consumed.
+    pub sequence: u32,
 }
 
 impl BattleUnitCameraTeleport {
-    /// Check whether there is a teleport 'command' ready.
+    /// Write a teleport 'command' from our own code, to be consumed like the game's unit card teleports.
+    pub fn publish(&mut self, camera: GamePos, target: GamePos) {
+        self.camera = camera;
+        self.target = target;
+        self.sequence = self.sequence.wrapping_add(1);
+    }
+
+    /// Whether any values were written since the last [Self::clear], whether or not the write has completed yet.
+    pub fn is_pending(&self) -> bool {
+        self.camera != GamePos::default() || self.target != GamePos::default()
+    }
+
+    /// Reset the `camera` and `target` once consumed, keeping the `sequence`.
+    pub fn clear(&mut self) {
+        self.camera = GamePos::default();
+        self.target = GamePos::default();
+    }
+
+    /// Copy the teleport 'command' in the `cell`, if one completed since the one with the `last_sequence`.
+    ///
+    /// The game writes the values without any synchronisation, so the copy is only trusted if the `sequence` is the
+    /// same before and after making it. Otherwise another teleport was written in the meantime, and the next call
+    /// will pick it up once it's complete.
     ///
-    /// Will check that _all_ items are no longer equal to ``0.0`. This doesn't eliminate the potential race condition
-    /// between the game code and our code, but it does make it less likely!
-    pub fn is_available(&self) -> bool {
-        self.x != 0.
-            && self.y != 0.
-            && self.z != 0.
-            && self.x_target != 0.
-            && self.y_target != 0.
-            && self.z_target != 0.
+    /// # Safety
+    /// The `cell` has to be the one the unit card teleport patch writes to, see [create_unit_card_teleport_patch].
+    pub unsafe fn read_completed(cell: &GameCell<Self>, last_sequence: u32) -> Option<Self> {
+        let teleport = cell.get_ptr();
+        let before = std::ptr::read_volatile(std::ptr::addr_of!((*teleport).sequence));
+        if before == last_sequence {
+            return None;
+        }
+
+        let copy = std::ptr::read_volatile(teleport);
+        let after = std::ptr::read_volatile(std::ptr::addr_of!((*teleport).sequence));
+
+        (before == after).then_some(copy)
     }
 }
 
@@ -71,8 +103,14 @@ impl DynamicPatch {
     /// Apply this patch to the given patcher.
     ///
     /// Starts out disabled.
-    pub unsafe fn apply_to_patcher(&self, patcher: &mut LocalPatcher) {
+    pub unsafe fn apply_to_patcher(&self, patcher: &mut LocalPatcher) -> ExpectedPatch {
+        crate::shutdown::track_patch(self.patch_addr as *const u8, self.source_loc.len());
         patcher.patch(self.patch_addr as *mut u8, &self.source_loc, false);
+
+        ExpectedPatch {
+            address: self.patch_addr,
+            bytes: self.source_loc.clone(),
+        }
     }
 }
 
@@ -80,7 +118,7 @@ impl DynamicPatch {
 pub unsafe fn create_unit_card_teleport_patch(
     teleport_struct_addr: *mut BattleUnitCameraTeleport,
 ) -> anyhow::Result<(DynamicPatch, DynamicPatch)> {
-    const PATCH_ADDR: usize = 0x8F8E8B;
+    let patch_addr = profile::active().unit_card_teleport_location.get();
     // The assembler executing the code we want
     let mut a = CodeAssembler::new(32)?;
     let teleport_struct_addr = teleport_struct_addr as usize;
@@ -111,8 +149,14 @@ pub unsafe fn create_unit_card_teleport_patch(
     // Restore `eax`
     a.pop(eax)?;
 
+    // Only now is the teleport complete, see `BattleUnitCameraTeleport::read_completed`.
+    // The flags are preserved, as the `mov`s we replaced didn't touch them.
+    a.pushfd()?;
+    a.inc(dword_ptr(teleport_struct_addr + 24))?;
+    a.popfd()?;
+
     // Jump back to our patch location, but now towards the `pop ebx`
-    a.mov(ebx, (PATCH_ADDR + 8) as u32)?;
+    a.mov(ebx, (patch_addr + 8) as u32)?;
     a.jmp(ebx)?;
 
     let dynamic_code = a.assemble(0x0)?.into_boxed_slice();
@@ -129,13 +173,13 @@ pub unsafe fn create_unit_card_teleport_patch(
     ];
 
     let teleport_intercept = DynamicPatch {
-        patch_addr: PATCH_ADDR,
+        patch_addr,
         source_loc: Box::new(source_jump),
         dynamic_code,
     };
     // 11 NOPS for removing the writes to `target_view` addresses at 0x8F8EB7
     let target_view = DynamicPatch {
-        patch_addr: 0x8F8EB7,
+        patch_addr: profile::active().unit_card_target_write_location.get(),
         source_loc: Box::new([0x90; 17]),
         dynamic_code: Box::new([]),
     };
@@ -146,11 +190,10 @@ pub unsafe fn create_unit_card_teleport_patch(
 /// Create and apply the (static) [crate::battle_cam::RemoteData::remote_z] patch.
 ///
 /// See the documentation [here](crate::battle_cam::RemoteData::remote_z) for more information.
-pub fn apply_general_z_remote_patch(patcher: &mut LocalPatcher, remote_data: &RemoteData) {
+pub fn apply_general_z_remote_patch(patcher: &mut LocalPatcher, remote_data: &RemoteData) -> [ExpectedPatch; 2] {
     // One of the `movss` which moved values to the battlecam address _anyway_
     // We have 15 bytes of `nops` atm at that address.
-    const FIRST_WRITE_ADDR: usize = 0x008F8C6C;
-    const SECOND_WRITE_ADDR: usize = 0x008F9439;
+    let [first_write_addr, second_write_addr] = profile::active().remote_z_write_locations.map(NonNullPtr::cast::<u8>);
     let address = (remote_data.remote_z.as_ptr() as u32).to_le_bytes();
 
     // 0:  52                      push   edx
@@ -161,8 +204,26 @@ pub fn apply_general_z_remote_patch(patcher: &mut LocalPatcher, remote_data: &Re
         0x52, 0xBA, address[0], address[1], address[2], address[3], 0xF3, 0x0F, 0x11, 0x0A, 0x5A,
     ];
 
-    unsafe { patcher.patch(FIRST_WRITE_ADDR as *mut u8, &assembly_patch, false) }
+    unsafe {
+        crate::shutdown::track_patch(first_write_addr, assembly_patch.len());
+        patcher.patch(first_write_addr, &assembly_patch, false)
+    }
+    let first = ExpectedPatch {
+        address: first_write_addr as usize,
+        bytes: assembly_patch.into(),
+    };
     // 6:  f3 0f 11 02             movss  DWORD PTR [edx],xmm0
     assembly_patch[9] = 0x02;
-    unsafe { patcher.patch(SECOND_WRITE_ADDR as *mut u8, &assembly_patch, false) }
+    unsafe {
+        crate::shutdown::track_patch(second_write_addr, assembly_patch.len());
+        patcher.patch(second_write_addr, &assembly_patch, false)
+    }
+
+    [
+        first,
+        ExpectedPatch {
+            address: second_write_addr as usize,
+            bytes: assembly_patch.into(),
+        },
+    ]
 }
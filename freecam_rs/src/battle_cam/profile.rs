@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::num::NonZeroUsize;
+
+use once_cell::sync::OnceCell;
+
+use crate::battle_cam::patch_locations::{PatchGroup, PATCH_GROUPS_STEAM, PATCH_LOCATIONS_STEAM};
+
+/// The profile in use for this process, see [set_active].
+static ACTIVE_PROFILE: OnceCell<PatchProfile> = OnceCell::new();
+
+/// A non-null game address, (de)serialised as a hex string such as `"0x0193F364"`.
+///
+/// Plain numbers are accepted as well, for compatibility with the other address fields in the config.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NonNullPtr(NonZeroUsize);
+
+impl NonNullPtr {
+    /// Create a new pointer from a known good `address`.
+    ///
+    /// # Panics
+    /// If the `address` is `0`.
+    pub const fn new(address: usize) -> Self {
+        match NonZeroUsize::new(address) {
+            Some(address) => Self(address),
+            None => panic!("Game address can't be null"),
+        }
+    }
+
+    pub const fn get(self) -> usize {
+        self.0.get()
+    }
+
+    pub const fn cast<T>(self) -> *mut T {
+        self.0.get() as *mut T
+    }
+}
+
+impl Debug for NonNullPtr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#010X}", self.get())
+    }
+}
+
+impl serde::Serialize for NonNullPtr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:?}", self))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for NonNullPtr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum RawAddress {
+            Hex(String),
+            Number(usize),
+        }
+
+        let address = match RawAddress::deserialize(deserializer)? {
+            RawAddress::Number(address) => address,
+            RawAddress::Hex(hex) => {
+                let digits = hex.trim_start_matches("0x").trim_start_matches("0X");
+                usize::from_str_radix(digits, 16)
+                    .map_err(|e| D::Error::custom(format!("Invalid hex address `{}`: {}", hex, e)))?
+            }
+        };
+
+        NonZeroUsize::new(address)
+            .map(Self)
+            .ok_or_else(|| D::Error::custom("Game address can't be null"))
+    }
+}
+
+/// Raw instruction bytes, (de)serialised as a hex string such as `"F3 0F 11 40 04"`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct InstructionBytes(Box<[u8]>);
+
+impl InstructionBytes {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Debug for InstructionBytes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02X?}", self.0)
+    }
+}
+
+impl serde::Serialize for InstructionBytes {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let hex = self.0.iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>();
+        serializer.serialize_str(&hex.join(" "))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for InstructionBytes {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let hex = String::deserialize(deserializer)?;
+        hex.split_whitespace()
+            .map(|byte| u8::from_str_radix(byte, 16))
+            .collect::<Result<_, _>>()
+            .map(Self)
+            .map_err(|e| D::Error::custom(format!("Invalid instruction bytes `{}`: {}", hex, e)))
+    }
+}
+
+/// One of the [PatchProfile::patch_locations], optionally along with the game's original instruction there.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum PatchLocation {
+    /// Just the address, whose instruction is only checked for a known camera write opcode, see
+    /// [crate::battle_cam::patch_locations::is_original].
+    Address(NonNullPtr),
+    /// The address along with the exact bytes of the game's instruction there, anything else counts as modified.
+    Verified {
+        address: NonNullPtr,
+        original: InstructionBytes,
+    },
+}
+
+impl PatchLocation {
+    pub fn address(&self) -> NonNullPtr {
+        match self {
+            PatchLocation::Address(address) | PatchLocation::Verified { address, .. } => *address,
+        }
+    }
+
+    /// The exact bytes of the game's instruction, if known.
+    pub fn original(&self) -> Option<&[u8]> {
+        match self {
+            PatchLocation::Address(_) => None,
+            PatchLocation::Verified { original, .. } => Some(original.as_bytes()),
+        }
+    }
+}
+
+/// All game addresses the freecam patches and reads, for one specific game executable.
+///
+/// The built-in [PatchProfile::steam] profile is used by default. Other executables (e.g., the Kingdoms expansion) can
+/// be supported by supplying their addresses through the config, without having to recompile.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PatchProfile {
+    /// Purely informative, shown in the logs.
+    pub name: String,
+    /// All instructions writing to the camera coordinates, see [PATCH_LOCATIONS_STEAM].
+    pub patch_locations: PatchLocations,
+    /// The two `movss` writes redirected to [crate::battle_cam::RemoteData::remote_z].
+    pub remote_z_write_locations: [NonNullPtr; 2],
+    /// The camera write redirected when the user double clicks a unit card.
+    pub unit_card_teleport_location: NonNullPtr,
+    /// The camera target writes which are removed when the user double clicks a unit card.
+    pub unit_card_target_write_location: NonNullPtr,
+    /// See [crate::battle_cam::data::calculate_delta_z_to_ground_fn_addr].
+    pub calculate_delta_z_to_ground_fn: NonNullPtr,
+    /// See [crate::battle_cam::data::z_fix_delta_ground_addr].
+    pub z_fix_delta_ground: NonNullPtr,
+    /// See [crate::battle_cam::data::battle_ongoing_addr].
+    pub battle_ongoing: NonNullPtr,
+    /// See [crate::battle_cam::data::battle_cam_conf_type_addr].
+    pub battle_cam_conf_type: NonNullPtr,
+    /// See [crate::battle_cam::data::battle_cam_addr].
+    pub battle_cam: NonNullPtr,
+    /// See [crate::battle_cam::data::battle_cam_target_addr].
+    pub battle_cam_target: NonNullPtr,
+    /// The units taking part in the battle, see [UnitList].
+    ///
+    /// Not yet known for any executable, `None` limits the auto director to the selected unit.
+    pub unit_list: Option<UnitList>,
+}
+
+/// The [PatchProfile::patch_locations], optionally divided into [PatchGroup]s so they can be disabled individually.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum PatchLocations {
+    /// The locations listed per group, e.g. `{ "CameraX": [..], "TargetX": [..] }`.
+    Grouped(BTreeMap<PatchGroup, Vec<PatchLocation>>),
+    /// A plain list of locations, which can't be disabled individually.
+    Ungrouped(Vec<PatchLocation>),
+}
+
+impl PatchLocations {
+    /// Every location, along with the group it belongs to (if any).
+    pub fn iter(&self) -> impl Iterator<Item = (Option<PatchGroup>, &PatchLocation)> + '_ {
+        let (grouped, ungrouped) = match self {
+            PatchLocations::Grouped(groups) => (Some(groups), None),
+            PatchLocations::Ungrouped(locations) => (None, Some(locations)),
+        };
+
+        grouped
+            .into_iter()
+            .flatten()
+            .flat_map(|(group, locations)| locations.iter().map(|location| (Some(*group), location)))
+            .chain(ungrouped.into_iter().flatten().map(|location| (None, location)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_grouped(&self) -> bool {
+        matches!(self, PatchLocations::Grouped(_))
+    }
+}
+
+/// The game's list of all units in the current battle, used to find points of interest for the
+/// [crate::battle_cam::auto_director::AutoDirector].
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct UnitList {
+    /// The pointer to the array of unit pointers.
+    pub units: NonNullPtr,
+    /// The number of units (`u32`) in the array.
+    pub count: NonNullPtr,
+    /// The offset of a unit's position from its pointer, assumed to share the camera's layout.
+    pub position_offset: usize,
+    /// The offset of the number of the unit's soldiers (`u32`) currently fighting in melee.
+    pub melee_count_offset: usize,
+    /// The offset of a flag (`u8`) which is non-zero if the unit is a general's bodyguard.
+    pub general_flag_offset: usize,
+}
+
+impl PatchProfile {
+    /// The addresses for the Steam version of `medieval2.exe`.
+    ///
+    /// The original bytes of its patch locations haven't been recorded yet, so they're only checked by opcode.
+    pub fn steam() -> Self {
+        Self {
+            name: "Steam".to_string(),
+            patch_locations: {
+                let mut locations = PATCH_LOCATIONS_STEAM
+                    .into_iter()
+                    .map(|address| PatchLocation::Address(NonNullPtr::new(address)));
+                let groups = PATCH_GROUPS_STEAM
+                    .into_iter()
+                    .map(|(group, count)| (group, locations.by_ref().take(count).collect()))
+                    .collect();
+
+                PatchLocations::Grouped(groups)
+            },
+            remote_z_write_locations: [NonNullPtr::new(0x008F8C6C), NonNullPtr::new(0x008F9439)],
+            unit_card_teleport_location: NonNullPtr::new(0x008F8E8B),
+            unit_card_target_write_location: NonNullPtr::new(0x008F8EB7),
+            calculate_delta_z_to_ground_fn: NonNullPtr::new(0x0094EA00),
+            z_fix_delta_ground: NonNullPtr::new(0x0193F364),
+            battle_ongoing: NonNullPtr::new(0x0193D683),
+            battle_cam_conf_type: NonNullPtr::new(0x01639F14),
+            battle_cam: NonNullPtr::new(0x0193D598),
+            battle_cam_target: NonNullPtr::new(0x0193D5DC),
+            unit_list: None,
+        }
+    }
+}
+
+impl Default for PatchProfile {
+    fn default() -> Self {
+        Self::steam()
+    }
+}
+
+/// Set the profile to use for the rest of this process' lifetime.
+///
+/// Can only be set once, as patches created with the old addresses could otherwise linger. Returns `false` if a
+/// different profile was already active.
+pub fn set_active(profile: &PatchProfile) -> bool {
+    let active = ACTIVE_PROFILE.get_or_init(|| {
+        log::info!(
+            "Using patch profile `{}` ({} patch locations)",
+            profile.name,
+            profile.patch_locations.len()
+        );
+        profile.clone()
+    });
+
+    active == profile
+}
+
+/// The active profile, defaulting to [PatchProfile::steam] if none was set.
+pub fn active() -> &'static PatchProfile {
+    ACTIVE_PROFILE.get_or_init(PatchProfile::steam)
+}
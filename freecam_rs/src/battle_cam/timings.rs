@@ -0,0 +1,101 @@
+use std::time::{Duration, Instant};
+
+/// Measures how long each phase of a battle tick takes, see [crate::config::FreecamConfig::measure_tick_timings].
+///
+/// A summary is logged once the battle ends. Does nothing if disabled.
+#[derive(Debug)]
+pub struct TickTimings {
+    enabled: bool,
+    /// In the order the phases were first seen.
+    phases: Vec<(&'static str, PhaseTiming)>,
+    ticks: u64,
+    tick_total: Duration,
+    tick_started: Option<Instant>,
+    last_mark: Option<Instant>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct PhaseTiming {
+    count: u64,
+    total: Duration,
+    max: Duration,
+}
+
+impl TickTimings {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            phases: Vec::new(),
+            ticks: 0,
+            tick_total: Duration::ZERO,
+            tick_started: None,
+            last_mark: None,
+        }
+    }
+
+    /// Start timing a new tick.
+    pub fn start_tick(&mut self) {
+        if self.enabled {
+            let now = Instant::now();
+            self.tick_started = Some(now);
+            self.last_mark = Some(now);
+        }
+    }
+
+    /// Attribute the time since the previous mark (or the start of the tick) to the given `phase`.
+    pub fn mark(&mut self, phase: &'static str) {
+        let Some(last_mark) = self.last_mark else {
+            return;
+        };
+        let now = Instant::now();
+        let elapsed = now - last_mark;
+        self.last_mark = Some(now);
+
+        let index = match self.phases.iter().position(|(name, _)| *name == phase) {
+            Some(index) => index,
+            None => {
+                self.phases.push((phase, PhaseTiming::default()));
+                self.phases.len() - 1
+            }
+        };
+        let timing = &mut self.phases[index].1;
+        timing.count += 1;
+        timing.total += elapsed;
+        timing.max = timing.max.max(elapsed);
+    }
+
+    /// Finish timing the current tick.
+    pub fn end_tick(&mut self) {
+        if let Some(started) = self.tick_started.take() {
+            self.ticks += 1;
+            self.tick_total += started.elapsed();
+        }
+        self.last_mark = None;
+    }
+
+    /// Log the average and worst case time of every phase over all measured ticks.
+    pub fn log_summary(&self) {
+        if !self.enabled || self.ticks == 0 {
+            return;
+        }
+
+        let average = |total: Duration, count: u64| total.div_f64(count.max(1) as f64);
+        let mut summary = format!(
+            "Tick timings over {} ticks: {:?} on average",
+            self.ticks,
+            average(self.tick_total, self.ticks)
+        );
+
+        for (name, timing) in &self.phases {
+            summary += &format!(
+                "\n  {:<16} avg {:?}, max {:?} ({} samples)",
+                name,
+                average(timing.total, timing.count),
+                timing.max,
+                timing.count
+            );
+        }
+
+        log::info!("{}", summary);
+    }
+}
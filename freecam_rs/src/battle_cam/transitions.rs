@@ -0,0 +1,49 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::time::Instant;
+
+/// How many transitions a [TransitionLog] remembers.
+const MAX_TRANSITIONS: usize = 16;
+
+/// The most recent transitions of a state machine, included in state dumps.
+///
+/// Makes it possible to diagnose the camera getting stuck in a state after the fact.
+#[derive(Debug)]
+pub struct TransitionLog<T> {
+    name: &'static str,
+    entries: VecDeque<(T, T, Instant)>,
+}
+
+impl<T: Debug + Copy> TransitionLog<T> {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            entries: VecDeque::with_capacity(MAX_TRANSITIONS),
+        }
+    }
+
+    /// Log and remember the given transition, discarding the oldest one if we're at capacity.
+    pub fn record(&mut self, from: T, to: T) {
+        log::debug!("{} transition: {:?} -> {:?}", self.name, from, to);
+        crate::crash_report::record_transition(self.name, &from, &to);
+
+        if self.entries.len() == MAX_TRANSITIONS {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((from, to, Instant::now()));
+    }
+
+    /// Create a snapshot of all remembered transitions, oldest first.
+    pub fn dump(&self) -> serde_json::Value {
+        self.entries
+            .iter()
+            .map(|(from, to, at)| {
+                serde_json::json!({
+                    "from": format!("{:?}", from),
+                    "to": format!("{:?}", to),
+                    "ago": at.elapsed(),
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt::{Display, Formatter};
+
+/// The bytes a patch writes at its address, to verify it actually ended up in the game's memory.
+#[derive(Debug, Clone)]
+pub struct ExpectedPatch {
+    pub address: usize,
+    pub bytes: Box<[u8]>,
+}
+
+/// A patch which isn't in memory after it was enabled, see [verify].
+#[derive(Debug, Clone)]
+pub struct PatchMismatch {
+    address: usize,
+    expected: Box<[u8]>,
+    /// [None] if the memory isn't accessible at all.
+    actual: Option<Box<[u8]>>,
+}
+
+impl Display for PatchMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.actual {
+            Some(actual) => write!(
+                f,
+                "{:#X}: expected {:02X?}, found {:02X?}",
+                self.address, self.expected, actual
+            ),
+            None => write!(f, "{:#X}: no longer accessible", self.address),
+        }
+    }
+}
+
+/// Return every patch whose bytes aren't (exactly) in memory.
+///
+/// A patch whose write silently failed, e.g. because an overlay (like RTSS) changed the page protection in the
+/// meantime, otherwise leaves the game with only half of our patches.
+///
+/// # Safety
+/// The `patches` should be enabled.
+pub unsafe fn verify(patches: &[ExpectedPatch]) -> Vec<PatchMismatch> {
+    patches
+        .iter()
+        .filter_map(|patch| {
+            // A patch can straddle a page boundary, so both its first and last byte have to be accessible.
+            let last_byte = patch.address + patch.bytes.len().saturating_sub(1);
+            let actual = (crate::memory::is_accessible(patch.address) && crate::memory::is_accessible(last_byte))
+                .then(|| std::slice::from_raw_parts(patch.address as *const u8, patch.bytes.len()));
+
+            (actual != Some(&*patch.bytes)).then(|| PatchMismatch {
+                address: patch.address,
+                expected: patch.bytes.clone(),
+                actual: actual.map(Into::into),
+            })
+        })
+        .collect()
+}
+
+/// How often our patches were enabled and verified during a battle, included in state dumps.
+///
+/// A summary is logged once the battle ends.
+#[derive(Debug, Default)]
+pub struct PatchVerification {
+    /// How many times patches were enabled and verified.
+    verifications: u64,
+    /// How many of those found at least one [PatchMismatch].
+    failures: u64,
+    /// The mismatches of the last failed verification.
+    last_mismatches: Vec<PatchMismatch>,
+    /// Set once all patches were rolled back, after which they're never enabled again this battle.
+    rolled_back: bool,
+}
+
+impl PatchVerification {
+    /// Remember the result of a single verification.
+    pub fn record(&mut self, mismatches: &[PatchMismatch]) {
+        self.verifications += 1;
+
+        if !mismatches.is_empty() {
+            self.failures += 1;
+            self.last_mismatches = mismatches.to_vec();
+        }
+    }
+
+    pub fn set_rolled_back(&mut self) {
+        self.rolled_back = true;
+    }
+
+    pub fn is_rolled_back(&self) -> bool {
+        self.rolled_back
+    }
+
+    /// Create a snapshot for a state dump.
+    pub fn dump(&self) -> serde_json::Value {
+        serde_json::json!({
+            "verifications": self.verifications,
+            "failures": self.failures,
+            "rolled_back": self.rolled_back,
+            "last_mismatches": self.last_mismatches.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        })
+    }
+
+    pub fn log_summary(&self) {
+        if self.verifications == 0 {
+            return;
+        }
+
+        log::info!(
+            "Patches were verified {} times, of which {} failed{}",
+            self.verifications,
+            self.failures,
+            if self.rolled_back { " and were rolled back" } else { "" }
+        );
+    }
+}
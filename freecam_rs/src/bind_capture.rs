@@ -0,0 +1,206 @@
+//! Records the next pressed key combination and writes it into the config, see [BindCapture].
+//!
+//! Saves users from looking up virtual-key codes to fill in the config by hand.
+
+use std::path::Path;
+
+use rust_hooking_utils::raw_input::key_manager::KeyState;
+use rust_hooking_utils::raw_input::virtual_keys::VirtualKey;
+
+use crate::config::{BindCaptureConfig, FreecamConfig, CONFIG_FILE_NAME};
+use crate::input::{InputManager, KeyChord};
+use crate::{config, keybind_conflicts, overlay};
+
+/// Keys which are always recorded first in a chord, in this order.
+const MODIFIER_KEYS: [&str; 3] = ["VK_CONTROL", "VK_SHIFT", "VK_MENU"];
+
+/// Any other key which can be captured, in addition to the letters, digits, function keys, and numpad digits.
+const OTHER_KEYS: [&str; 32] = [
+    "VK_SPACE",
+    "VK_TAB",
+    "VK_RETURN",
+    "VK_BACK",
+    "VK_INSERT",
+    "VK_DELETE",
+    "VK_HOME",
+    "VK_END",
+    "VK_PRIOR",
+    "VK_NEXT",
+    "VK_UP",
+    "VK_DOWN",
+    "VK_LEFT",
+    "VK_RIGHT",
+    "VK_PAUSE",
+    "VK_MULTIPLY",
+    "VK_ADD",
+    "VK_SUBTRACT",
+    "VK_DECIMAL",
+    "VK_DIVIDE",
+    "VK_OEM_1",
+    "VK_OEM_2",
+    "VK_OEM_3",
+    "VK_OEM_4",
+    "VK_OEM_5",
+    "VK_OEM_6",
+    "VK_OEM_7",
+    "VK_OEM_PLUS",
+    "VK_OEM_MINUS",
+    "VK_MBUTTON",
+    "VK_XBUTTON1",
+    "VK_XBUTTON2",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum CaptureState {
+    Idle,
+    /// Waiting for the keys which started the capture to be released, so they aren't recorded.
+    Releasing,
+    /// The indices (into [BindCapture::keys]) of all keys held down since the first one was pressed.
+    Recording(Vec<usize>),
+}
+
+/// Captures a key combination for the [BindCaptureConfig::action].
+///
+/// All keys held down together are recorded, with the capture finishing once they're all released. Pressing `Escape`
+/// cancels the capture instead.
+pub struct BindCapture {
+    state: CaptureState,
+    /// All keys which can be captured, modifiers first.
+    keys: Vec<VirtualKey>,
+}
+
+impl BindCapture {
+    pub fn new() -> Self {
+        let names = MODIFIER_KEYS
+            .iter()
+            .chain(&OTHER_KEYS)
+            .map(|name| name.to_string())
+            .chain(('0'..='9').chain('A'..='Z').map(|key| format!("VK_{}", key)))
+            .chain((1..=24).map(|n| format!("VK_F{}", n)))
+            .chain((0..=9).map(|n| format!("VK_NUMPAD{}", n)));
+        let keys = names
+            .filter_map(|name| serde_json::from_value::<VirtualKey>(name.into()).ok())
+            .collect();
+
+        Self {
+            state: CaptureState::Idle,
+            keys,
+        }
+    }
+
+    /// Whether a key combination is currently being captured, during which all other input should be ignored.
+    pub fn is_capturing(&self) -> bool {
+        self.state != CaptureState::Idle
+    }
+
+    /// Start or continue capturing, returning `true` once a binding was written to the config.
+    pub fn update(&mut self, conf: &BindCaptureConfig, config_directory: &Path, key_man: &mut InputManager) -> bool {
+        if self.state == CaptureState::Idle {
+            if let Some(key) = &conf.key {
+                if matches!(key_man.binding_state(key), KeyState::Pressed) {
+                    overlay::notify(format!(
+                        "Press the keys to bind to `{}`, or Escape to cancel",
+                        conf.action
+                    ));
+                    self.state = CaptureState::Releasing;
+                }
+            }
+
+            return false;
+        }
+
+        if key_man.has_pressed(VirtualKey::VK_ESCAPE.to_virtual_key()) {
+            overlay::notify("Cancelled the key capture");
+            self.state = CaptureState::Idle;
+            return false;
+        }
+
+        let held = (0..self.keys.len())
+            .filter(|&i| key_man.has_pressed(self.keys[i].to_virtual_key()))
+            .collect::<Vec<_>>();
+
+        match &mut self.state {
+            CaptureState::Idle => false,
+            CaptureState::Releasing => {
+                if held.is_empty() {
+                    self.state = CaptureState::Recording(Vec::new());
+                }
+                false
+            }
+            CaptureState::Recording(recorded) if held.is_empty() => {
+                if recorded.is_empty() {
+                    return false;
+                }
+
+                recorded.sort_unstable();
+                let chord = KeyChord(recorded.iter().map(|&i| self.keys[i]).collect());
+                self.state = CaptureState::Idle;
+
+                match write_binding(config_directory, &conf.action, &chord) {
+                    Ok(()) => {
+                        overlay::notify(format!("Bound `{}` to `{:?}`", conf.action, chord));
+                        true
+                    }
+                    Err(e) => {
+                        crate::error_report::report(format!(
+                            "Failed to bind `{}` to `{:?}`: {}",
+                            conf.action, chord, e
+                        ));
+                        false
+                    }
+                }
+            }
+            CaptureState::Recording(recorded) => {
+                for key in held {
+                    if !recorded.contains(&key) {
+                        recorded.push(key);
+                    }
+                }
+                false
+            }
+        }
+    }
+}
+
+/// The JSON pointer to the given `action`, a path of fields and list indices separated by dots.
+pub fn action_pointer(action: &str) -> String {
+    format!("/{}", action.replace('.', "/"))
+}
+
+/// Replace the binding of the `action` in the config file with the given `chord`.
+///
+/// The rest of the config is kept as is, though any missing fields are filled in like when loading the config.
+pub fn write_binding(config_directory: &Path, action: &str, chord: &KeyChord) -> anyhow::Result<()> {
+    let path = config_directory.join(CONFIG_FILE_NAME);
+    let current = config::upgrade_config(serde_json::from_slice(&std::fs::read(&path)?)?);
+    // Most bindings are written as a chord, but the `*_keys` options are plain lists of keys.
+    let representations = [serde_json::to_value(chord)?, serde_json::to_value(&chord.0)?];
+    let mut last_error = None;
+
+    for representation in representations {
+        let mut updated = current.clone();
+        let Some(binding) = updated.pointer_mut(&action_pointer(action)) else {
+            anyhow::bail!("The config doesn't contain `{}`", action);
+        };
+        *binding = representation;
+
+        // Never write a config which would fail to load on the following reload.
+        let loaded = serde_json::from_value::<FreecamConfig>(updated.clone())
+            .map_err(anyhow::Error::from)
+            .and_then(|mut conf| {
+                config::validate_config(&conf)?;
+                keybind_conflicts::check(&mut conf)
+            });
+
+        match loaded {
+            Ok(()) => {
+                let mut file = std::fs::File::create(&path)?;
+                serde_json::to_writer_pretty(&mut file, &updated)?;
+                return Ok(());
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| anyhow::anyhow!("`{}` isn't a key binding", action)))
+}
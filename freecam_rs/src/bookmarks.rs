@@ -0,0 +1,93 @@
+use std::path::{Path, PathBuf};
+
+use rust_hooking_utils::raw_input::key_manager::KeyState;
+
+use crate::battle_cam::coords::WorldPos;
+use crate::battle_cam::BattleCamera;
+use crate::config::BookmarkConfig;
+use crate::input::InputManager;
+use crate::overlay;
+
+pub const BOOKMARKS_FILE_NAME: &str = "freecam_bookmarks.json";
+
+/// A saved camera pose.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Bookmark {
+    /// Absolute map coordinates, only meaningful on the map the bookmark was saved on.
+    ///
+    /// Older bookmarks called this the `offset`, which was always relative to the map's origin.
+    #[serde(alias = "offset")]
+    pub pos: WorldPos,
+    pub pitch: f32,
+    pub yaw: f32,
+}
+
+/// Saves and recalls [Bookmark]s, persisted to [BOOKMARKS_FILE_NAME] next to the config.
+pub struct BookmarkManager {
+    path: PathBuf,
+    slots: Vec<Option<Bookmark>>,
+}
+
+impl BookmarkManager {
+    pub fn new(config_directory: impl AsRef<Path>) -> Self {
+        let path = config_directory.as_ref().join(BOOKMARKS_FILE_NAME);
+        let slots = match std::fs::read(&path) {
+            Ok(file) => serde_json::from_slice(&file).unwrap_or_else(|e| {
+                log::warn!("Failed to load bookmarks from {:?}, starting without any: {}", path, e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        Self { path, slots }
+    }
+
+    /// Save or recall a bookmark if any of the slot keys was pressed.
+    pub fn update(&mut self, conf: &BookmarkConfig, key_man: &mut InputManager, battle_cam: &mut BattleCamera) {
+        for (slot, key) in conf.slots.iter().enumerate() {
+            if !matches!(key_man.binding_state(key), KeyState::Pressed) {
+                continue;
+            }
+
+            if key_man.binding_down(&conf.save_modifier) {
+                self.save(slot, battle_cam);
+            } else {
+                self.recall(slot, battle_cam);
+            }
+        }
+    }
+
+    fn save(&mut self, slot: usize, battle_cam: &mut BattleCamera) {
+        let Some((pos, pitch, yaw)) = battle_cam.camera_pose() else {
+            return;
+        };
+
+        if self.slots.len() <= slot {
+            self.slots.resize(slot + 1, None);
+        }
+        self.slots[slot] = Some(Bookmark { pos, pitch, yaw });
+        overlay::notify(format!("Saved bookmark {}", slot + 1));
+        log::debug!("Bookmark {}: {:?}", slot + 1, self.slots[slot]);
+
+        if let Err(e) = self.persist() {
+            log::error!("Failed to save bookmarks to {:?}: {}", self.path, e);
+        }
+    }
+
+    fn recall(&self, slot: usize, battle_cam: &mut BattleCamera) {
+        let Some(bookmark) = self.slots.get(slot).and_then(Option::as_ref) else {
+            overlay::notify(format!("Bookmark {} hasn't been saved yet", slot + 1));
+            return;
+        };
+        if battle_cam.teleport_to(bookmark.pos, bookmark.pitch, bookmark.yaw) {
+            overlay::notify(format!("Moving to bookmark {}", slot + 1));
+        }
+    }
+
+    fn persist(&self) -> anyhow::Result<()> {
+        let mut file = std::fs::File::create(&self.path)?;
+        serde_json::to_writer_pretty(&mut file, &self.slots)?;
+
+        Ok(())
+    }
+}
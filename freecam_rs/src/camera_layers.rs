@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+
+use crate::battle_cam::BattleCamera;
+use crate::config::{CameraConfig, FreecamConfig};
+
+/// The components which can temporarily replace the `camera` settings, in the order their layers are applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CameraLayer {
+    /// The [FreecamConfig::camera_profiles] entry selected through the HTTP API.
+    Profile,
+}
+
+/// Owns the `camera` settings of the config, with every component overriding them pushing its own layer on top.
+///
+/// [FreecamConfig::camera] always holds the settings of the last active layer in [CameraLayer] order, or the config's
+/// own settings if there are none. Components only ever push or pop their own layer, so their overrides can overlap
+/// without any of them restoring outdated settings.
+pub struct CameraLayers {
+    /// The `camera` settings as loaded from the config.
+    base: CameraConfig,
+    layers: BTreeMap<CameraLayer, CameraConfig>,
+}
+
+impl CameraLayers {
+    pub fn new(conf: &FreecamConfig) -> Self {
+        Self {
+            base: conf.camera.clone(),
+            layers: BTreeMap::new(),
+        }
+    }
+
+    /// Push the `layer`, replacing its previous settings if it was already active.
+    pub fn push(
+        &mut self,
+        layer: CameraLayer,
+        settings: CameraConfig,
+        conf: &mut FreecamConfig,
+        battle_cam: &mut BattleCamera,
+    ) {
+        self.layers.insert(layer, settings);
+        self.apply(conf, battle_cam);
+    }
+
+    /// Remove the `layer`, returning whether it was active.
+    pub fn pop(&mut self, layer: CameraLayer, conf: &mut FreecamConfig, battle_cam: &mut BattleCamera) -> bool {
+        let removed = self.layers.remove(&layer).is_some();
+        if removed {
+            self.apply(conf, battle_cam);
+        }
+
+        removed
+    }
+
+    /// Take the `camera` settings of a reloaded `conf` as the new base, dropping all layers.
+    ///
+    /// Components push their layer again if it still applies.
+    pub fn reset(&mut self, conf: &FreecamConfig) {
+        self.base = conf.camera.clone();
+        self.layers.clear();
+    }
+
+    fn apply(&self, conf: &mut FreecamConfig, battle_cam: &mut BattleCamera) {
+        // Every layer replaces all settings, so only the topmost one matters.
+        let camera = self.layers.values().next_back().unwrap_or(&self.base).clone();
+
+        let previous_enabled = conf.camera.custom_camera_enabled;
+        conf.camera = camera;
+
+        if conf.camera.custom_camera_enabled != previous_enabled {
+            battle_cam.set_custom_camera(conf.camera.custom_camera_enabled);
+        }
+    }
+}
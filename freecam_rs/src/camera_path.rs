@@ -0,0 +1,395 @@
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use rust_hooking_utils::raw_input::key_manager::KeyState;
+
+use crate::battle_cam::coords::{angle_difference, WorldPos};
+use crate::battle_cam::easing::Easing;
+use crate::battle_cam::BattleCamera;
+use crate::config::CameraPathConfig;
+use crate::input::{InputManager, KeyBinding};
+
+pub const CAMERA_PATH_DIR: &str = "camera_paths";
+/// The current version of the [CameraPath] file format, bumped on every incompatible change.
+pub const CAMERA_PATH_VERSION: u32 = 1;
+
+/// A camera shot, consisting of keyframes the camera moves through over time.
+///
+/// Stored as JSON in the `camera_paths` folder next to the config, these files can be shared with others.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CameraPath {
+    /// The [CAMERA_PATH_VERSION] the path was saved with, paths from before it was versioned are version `1`.
+    #[serde(default = "first_version")]
+    pub version: u32,
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl Default for CameraPath {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+/// A single camera pose along a [CameraPath].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Keyframe {
+    /// Seconds since the start of the path.
+    pub time: f32,
+    pub pos: WorldPos,
+    pub pitch: f32,
+    pub yaw: f32,
+    pub source: KeyframeSource,
+}
+
+/// Where a [Keyframe] came from, so recorded vanilla motion can be told apart from hand-placed keyframes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum KeyframeSource {
+    /// Recorded from the game's own camera, see [VanillaPathRecorder].
+    Vanilla,
+    /// Placed by the user.
+    Custom,
+}
+
+impl CameraPath {
+    pub fn new(keyframes: Vec<Keyframe>) -> Self {
+        Self {
+            version: CAMERA_PATH_VERSION,
+            keyframes,
+        }
+    }
+
+    /// Load a path saved with [Self::save] (possibly by someone else), rejecting paths we can't play.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let camera_path: Self = serde_json::from_slice(&std::fs::read(path)?)?;
+
+        if camera_path.version > CAMERA_PATH_VERSION {
+            anyhow::bail!(
+                "The path was saved by a newer version of the freecam (version {}, this version supports up to {})",
+                camera_path.version,
+                CAMERA_PATH_VERSION
+            )
+        }
+        if camera_path.keyframes.is_empty() {
+            anyhow::bail!("The path doesn't contain any keyframes")
+        }
+        let finite = camera_path.keyframes.iter().all(|keyframe| {
+            keyframe.time.is_finite()
+                && keyframe.pos.is_finite()
+                && keyframe.pitch.is_finite()
+                && keyframe.yaw.is_finite()
+        });
+        if !finite {
+            anyhow::bail!("The path contains keyframes with invalid (NaN or infinite) values")
+        }
+        if camera_path.keyframes.windows(2).any(|pair| pair[1].time < pair[0].time) {
+            anyhow::bail!("The keyframes of the path should be sorted by their `time`")
+        }
+
+        Ok(camera_path)
+    }
+
+    /// The `(position, pitch, yaw)` at `time` seconds into the path, `None` if the path has no keyframes.
+    ///
+    /// Interpolates between the surrounding keyframes according to the `easing`, taking the shortest path for the yaw.
+    /// Before the first and after the last keyframe the path stays at that keyframe's pose.
+    pub fn sample(&self, time: f32, easing: Easing) -> Option<(WorldPos, f32, f32)> {
+        let next = self.keyframes.iter().position(|keyframe| keyframe.time > time);
+        let (from, to) = match next {
+            Some(0) => {
+                let first = self.keyframes.first()?;
+                (first, first)
+            }
+            Some(next) => (&self.keyframes[next - 1], &self.keyframes[next]),
+            None => {
+                let last = self.keyframes.last()?;
+                (last, last)
+            }
+        };
+
+        let span = to.time - from.time;
+        let t = if span > 0. { easing.apply((time - from.time) / span) } else { 1. };
+
+        Some((
+            from.pos.lerp(&to.pos, t),
+            from.pitch + (to.pitch - from.pitch) * t,
+            from.yaw + angle_difference(from.yaw, to.yaw) * t,
+        ))
+    }
+
+    /// The time of the last keyframe, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0., |keyframe| keyframe.time)
+    }
+
+    /// Save the path as a timestamped JSON file in the given `directory`.
+    pub fn save(&self, directory: &Path, prefix: &str) -> anyhow::Result<PathBuf> {
+        std::fs::create_dir_all(directory)?;
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis();
+        let path = directory.join(format!("{}_{}.json", prefix, timestamp));
+
+        let mut file = std::fs::File::create(&path)?;
+        serde_json::to_writer_pretty(&mut file, self)?;
+
+        Ok(path)
+    }
+}
+
+/// Records the game's own camera motion (e.g., the deployment fly-in) into a [CameraPath].
+///
+/// Only samples while the game controls the camera, any moment our custom camera takes over is left out.
+pub struct VanillaPathRecorder {
+    directory: PathBuf,
+    recording: Option<Recording>,
+}
+
+struct Recording {
+    path: CameraPath,
+    started: Instant,
+    last_sample: Option<Instant>,
+}
+
+impl VanillaPathRecorder {
+    pub fn new(config_directory: impl AsRef<Path>) -> Self {
+        Self {
+            directory: config_directory.as_ref().join(CAMERA_PATH_DIR),
+            recording: None,
+        }
+    }
+
+    /// Start/stop recording if the configured key was pressed, and sample the game's camera while recording.
+    pub fn update(&mut self, conf: &CameraPathConfig, key_man: &mut InputManager, battle_cam: &mut BattleCamera) {
+        if let Some(key) = &conf.record_vanilla_key {
+            if matches!(key_man.binding_state(key), KeyState::Pressed) {
+                self.toggle();
+            }
+        }
+
+        let Some(recording) = self.recording.as_mut() else {
+            return;
+        };
+
+        if recording
+            .last_sample
+            .is_some_and(|last| last.elapsed() < conf.record_sample_interval)
+        {
+            return;
+        }
+
+        if let Some((pos, pitch, yaw)) = battle_cam.vanilla_camera_pose() {
+            recording.path.keyframes.push(Keyframe {
+                time: recording.started.elapsed().as_secs_f32(),
+                pos,
+                pitch,
+                yaw,
+                source: KeyframeSource::Vanilla,
+            });
+            recording.last_sample = Some(Instant::now());
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Start recording, or stop and save the current recording.
+    pub fn toggle(&mut self) {
+        match self.recording.take() {
+            None => {
+                crate::overlay::notify("Recording the game's camera motion");
+                self.recording = Some(Recording {
+                    path: CameraPath::default(),
+                    started: Instant::now(),
+                    last_sample: None,
+                });
+            }
+            Some(recording) if recording.path.keyframes.is_empty() => {
+                log::warn!("Stopped recording, but the game didn't control the camera so nothing was recorded")
+            }
+            Some(recording) => match recording.path.save(&self.directory, "vanilla") {
+                Ok(path) => crate::overlay::notify(format!(
+                    "Saved {} recorded keyframes to: {:?}",
+                    recording.path.keyframes.len(),
+                    path
+                )),
+                Err(e) => log::error!("Failed to save recorded camera path: {}", e),
+            },
+        }
+    }
+}
+
+/// The shareable camera paths in the `camera_paths` folder: plays them back, and exports keyframes placed by the user as
+/// a new path.
+pub struct CameraPathLibrary {
+    directory: PathBuf,
+    /// Every path which could be loaded, by file name.
+    paths: Vec<(String, CameraPath)>,
+    /// The index into `paths` to play next.
+    next: usize,
+    playback: Option<Playback>,
+    /// The keyframes placed so far with the `keyframe_key`, see [Self::place_keyframe].
+    placed: CameraPath,
+}
+
+struct Playback {
+    name: String,
+    path: CameraPath,
+    started: Instant,
+}
+
+impl CameraPathLibrary {
+    pub fn new(config_directory: impl AsRef<Path>) -> Self {
+        let mut library = Self {
+            directory: config_directory.as_ref().join(CAMERA_PATH_DIR),
+            paths: Vec::new(),
+            next: 0,
+            playback: None,
+            placed: CameraPath::default(),
+        };
+        library.reload();
+
+        library
+    }
+
+    /// Handle the playback, reload, and keyframe keys, and move the camera along the path being played.
+    pub fn update(&mut self, conf: &CameraPathConfig, key_man: &mut InputManager, battle_cam: &mut BattleCamera) {
+        if pressed(key_man, &conf.reload_key) {
+            self.reload();
+            crate::overlay::notify(format!("Loaded {} camera paths", self.paths.len()));
+        }
+        if pressed(key_man, &conf.keyframe_key) {
+            self.place_keyframe(conf, battle_cam);
+        }
+        if pressed(key_man, &conf.export_key) {
+            self.export();
+        }
+        if pressed(key_man, &conf.play_key) {
+            if self.playback.is_some() {
+                self.stop();
+            } else {
+                self.play_next();
+            }
+        }
+
+        let Some(playback) = &self.playback else {
+            return;
+        };
+        let elapsed = playback.started.elapsed().as_secs_f32();
+        let finished = elapsed > playback.path.duration();
+        let placed = playback
+            .path
+            .sample(elapsed, conf.playback_easing)
+            .is_some_and(|(pos, pitch, yaw)| battle_cam.set_camera_pose(pos, pitch, yaw));
+
+        if !placed {
+            log::debug!(
+                "Stopped playing camera path {:?}, we're no longer in a battle",
+                playback.name
+            );
+            self.playback = None;
+        } else if finished {
+            crate::overlay::notify(format!("Finished playing camera path {:?}", playback.name));
+            self.playback = None;
+        }
+    }
+
+    /// Stop playing the current camera path, if any.
+    pub fn stop(&mut self) {
+        if let Some(playback) = self.playback.take() {
+            crate::overlay::notify(format!("Stopped playing camera path {:?}", playback.name));
+        }
+    }
+
+    /// Load every `.json` path in the `camera_paths` folder, skipping (and logging) any we can't play.
+    pub fn reload(&mut self) {
+        self.paths.clear();
+        self.next = 0;
+
+        let Ok(entries) = std::fs::read_dir(&self.directory) else {
+            return;
+        };
+        let mut files = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+            .collect::<Vec<_>>();
+        files.sort();
+
+        for file in files {
+            let name = file.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            match CameraPath::load(&file) {
+                Ok(path) => self.paths.push((name, path)),
+                Err(e) => log::warn!("Skipping camera path {:?}: {}", file, e),
+            }
+        }
+
+        log::debug!("Loaded {} camera paths from {:?}", self.paths.len(), self.directory);
+    }
+
+    fn play_next(&mut self) {
+        let Some((name, path)) = self.paths.get(self.next) else {
+            crate::overlay::notify(format!("There are no camera paths in {:?}", self.directory));
+            return;
+        };
+
+        crate::overlay::notify(format!("Playing camera path {:?} ({:.1}s)", name, path.duration()));
+        self.playback = Some(Playback {
+            name: name.clone(),
+            path: path.clone(),
+            started: Instant::now(),
+        });
+        self.next = (self.next + 1) % self.paths.len();
+    }
+
+    /// Add the current camera pose as a keyframe, the `keyframe_interval` after the previously placed keyframe.
+    fn place_keyframe(&mut self, conf: &CameraPathConfig, battle_cam: &mut BattleCamera) {
+        let Some((pos, pitch, yaw)) = battle_cam.camera_pose() else {
+            return;
+        };
+        let time = self
+            .placed
+            .keyframes
+            .last()
+            .map_or(0., |last| last.time + conf.keyframe_interval.as_secs_f32());
+
+        self.placed.keyframes.push(Keyframe {
+            time,
+            pos,
+            pitch,
+            yaw,
+            source: KeyframeSource::Custom,
+        });
+        crate::overlay::notify(format!("Placed keyframe {}", self.placed.keyframes.len()));
+    }
+
+    /// Save the placed keyframes as a new camera path, and start placing a new one.
+    fn export(&mut self) {
+        if self.placed.keyframes.is_empty() {
+            crate::overlay::notify("Place some keyframes before exporting a camera path");
+            return;
+        }
+
+        match self.placed.save(&self.directory, "custom") {
+            Ok(path) => {
+                crate::overlay::notify(format!(
+                    "Saved {} keyframes to: {:?}",
+                    self.placed.keyframes.len(),
+                    path
+                ));
+                self.placed = CameraPath::default();
+                self.reload();
+            }
+            Err(e) => log::error!("Failed to save camera path: {}", e),
+        }
+    }
+}
+
+fn pressed(key_man: &mut InputManager, key: &Option<KeyBinding>) -> bool {
+    key.as_ref()
+        .is_some_and(|key| matches!(key_man.binding_state(key), KeyState::Pressed))
+}
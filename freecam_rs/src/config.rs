@@ -1,127 +1,864 @@
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::path::Path;
 use std::time::Duration;
 
 use anyhow::Context;
+use log::LevelFilter;
 use rust_hooking_utils::raw_input::virtual_keys::VirtualKey;
 
+use crate::battle_cam::auto_director::PointOfInterest;
+use crate::battle_cam::bounds;
+use crate::battle_cam::bounds::LandingZone;
+use crate::battle_cam::coords::WorldPos;
+use crate::battle_cam::easing::Easing;
+use crate::battle_cam::filter::LookFilter;
+use crate::battle_cam::patch_locations::{is_valid_original, ForeignPatchHandling, PatchGroup};
+use crate::battle_cam::profile::{PatchLocations, PatchProfile};
+use crate::battle_cam::scroll::ScrollResponse;
+use crate::battle_cam::shake::ShakeMotion;
+use crate::battle_cam::smoothing::SmoothingModels;
+use crate::battle_cam::snap::AngleSnap;
+use crate::battle_cam::velocity::{self, Smoothing, Velocity};
+use crate::battle_cam::MIN_TARGET_DISTANCE;
+use crate::bind_capture::action_pointer;
+use crate::error_report::ErrorDisplay;
+use crate::executable_check::ExecutableCheck;
+use crate::input::{FreecamMode, KeyBinding, KeyChord, OpposingKeys};
+use crate::keybind_conflicts::ConflictHandling;
+use crate::mouse::HorizontalScroll;
+use crate::overlay::OverlayConfig;
+
 pub const CONFIG_FILE_NAME: &str = "freecam_config.json";
+/// Where a config which couldn't be upgraded is moved to, so the user's values aren't lost.
+pub const CONFIG_BACKUP_FILE_NAME: &str = "freecam_config.backup.json";
+/// The current config schema version, should be incremented whenever a migration is added to [MIGRATIONS].
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Migrations for renamed/moved fields, `MIGRATIONS[i]` upgrades a config from version `i` to `i + 1`.
+///
+/// New fields don't need a migration, they're filled in with their defaults automatically.
+const MIGRATIONS: [fn(&mut serde_json::Value); CONFIG_VERSION as usize] = [
+    // 0 -> 1: `rotate_smoothing` was split into `look_smoothing` and `look_acceleration`.
+    // The acceleration used to be derived from the smoothing, so keep that to turn the camera just like before.
+    |conf| {
+        if let Some(camera) = conf.get_mut("camera").and_then(|c| c.as_object_mut()) {
+            if let Some(smoothing) = camera.remove("rotate_smoothing") {
+                if let Some(acceleration) = smoothing.as_f64().and_then(|s| serde_json::Number::from_f64(1. - s)) {
+                    camera.insert("look_acceleration".into(), acceleration.into());
+                }
+                camera.insert("look_smoothing".into(), smoothing);
+            }
+        }
+    },
+    // 1 -> 2: `inverted` was split into `invert_pitch` and `invert_yaw`.
+    |conf| {
+        if let Some(camera) = conf.get_mut("camera").and_then(|c| c.as_object_mut()) {
+            if let Some(inverted) = camera.remove("inverted") {
+                camera.insert("invert_pitch".into(), inverted.clone());
+                camera.insert("invert_yaw".into(), inverted);
+            }
+        }
+    },
+];
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
 pub struct FreecamConfig {
+    /// The schema version of this config, used to upgrade older configs. Shouldn't be changed manually.
+    pub version: u32,
     /// Whether to open a console for logging
     pub console: bool,
-    /// How often to run our simple update loop.
+    /// If set, shows a live-updating camera status line at the bottom of the `console`, refreshed at this interval.
+    ///
+    /// Only shown while the freecam runs normally, not in the `observer_mode`.
+    pub console_status_interval: Option<Duration>,
+    /// The minimum level of log messages to write to the console and `freecam.log` file.
+    pub log_level: LevelFilter,
+    /// The maximum size of a single log file in KB before it is rotated.
+    pub log_max_file_size_kb: u64,
+    /// How many old log files to keep around.
+    pub log_max_files: u32,
+    /// How often to run our simple update loop, see [crate::scheduler::UpdateScheduler].
     pub update_rate: u16,
+    /// Whether to update the camera once per frame rendered by the game instead of at the `update_rate`.
+    ///
+    /// Requires the `present_fn_address`. Note that, like the `update_rate`, the frame rate then affects camera speed.
+    pub vsync_to_game: bool,
+    /// The game address of `IDirect3DDevice9::Present`, hooked for [Self::vsync_to_game].
+    pub present_fn_address: Option<usize>,
     /// If set, will allow the config to be reloaded during gameplay by providing the given key codes.
     pub reload_config_keys: Option<Vec<VirtualKey>>,
+    /// If set, will dump the full current camera state to a timestamped JSON file next to the config when the given keys are pressed.
+    ///
+    /// Useful for attaching to bug reports.
+    pub dump_state_keys: Option<Vec<VirtualKey>>,
     /// Any camera other than the `TotalWarCamera` (index 0) tends to bug out when going to a different unit.
     ///
     /// Forcing an override on every game start seems the most logical.
+    /// The original camera type is restored when this is disabled or the battle ends.
     pub force_ttw_camera: bool,
+    /// If set, toggles [Self::force_ttw_camera] at runtime when the given keys are pressed.
+    pub toggle_force_ttw_camera_keys: Option<Vec<VirtualKey>>,
+    /// If set, recovers from a glitched camera when the given keys are pressed.
+    ///
+    /// Re-syncs the camera with the game, discards all velocity, stops the auto director and any path recording or
+    /// playback, shows the cursor, and re-applies all patches.
+    pub reset_keys: Option<Vec<VirtualKey>>,
+    /// If set, moves the camera back to the view the battle started with when the given keys are pressed.
+    ///
+    /// This is the game's camera as it was when the battle loaded, not a default position. The game keeps control for
+    /// a moment afterwards, and the freecam continues from wherever the camera ends up.
+    pub reset_view_keys: Option<Vec<VirtualKey>>,
+    /// If set, turns the entire freecam off (and back on) at runtime when the given keys are pressed.
+    ///
+    /// While off all patches are removed and the game's own camera is in full control, e.g., for multiplayer battles.
+    pub toggle_freecam_keys: Option<Vec<VirtualKey>>,
     /// Whether the base game's middle mouse functionality should be blocked during battles.
     ///
     /// Setting this to `true` allows the use of middle mouse button for the freecam.
     pub block_game_middle_mouse_functionality: bool,
+    /// Optional game addresses used to detect the loading, deployment, and end-of-battle phases.
+    ///
+    /// While loading the battle state is prepared ahead of time. During deployment and after the battle has ended all
+    /// camera patches are removed to not interfere with the game's own camera.
+    pub battle_phase_addresses: Option<BattlePhaseAddresses>,
+    /// If set, the freecam only runs (and patches the game) if the game executable passes this check.
+    ///
+    /// Shows a message box instead when it doesn't, e.g. after injecting into a different Total War title.
+    pub executable_check: Option<ExecutableCheck>,
+    /// All game addresses the freecam relies on, defaults to those of the Steam version.
+    ///
+    /// Can be changed to support other game executables, requires a game restart.
+    pub patch_profile: PatchProfile,
+    /// Which game values are redirected into the freecam's memory, disable these if they conflict with other mods.
+    pub remote_captures: RemoteCaptureConfig,
+    /// Groups of patches which shouldn't be applied, to find out which one conflicts with another mod.
+    ///
+    /// Applies from the next battle onwards.
+    pub disabled_patch_groups: Vec<PatchGroup>,
+    /// What to do with camera writes which were already modified, e.g. by another camera mod, checked every battle.
+    pub foreign_patches: ForeignPatchHandling,
+    /// Whether to log every intercepted unit card teleport (and whether it was used or expired) at debug level.
+    ///
+    /// Useful for diagnosing double clicking a unit card not moving the camera.
+    pub log_teleport_events: bool,
+    /// Whether to measure how long each part of the camera update takes, logging a summary once the battle ends.
+    ///
+    /// Only useful for performance tuning, applies from the next battle onwards.
+    pub measure_tick_timings: bool,
+    /// Whether to run a scripted camera sequence at the start of every battle, logging the camera's state throughout.
+    ///
+    /// Useful for validating a new `patch_profile` or game version, see [crate::self_test].
+    pub self_test: bool,
+    /// Optional game address of the audio listener's position, assumed to have the same layout as the camera position.
+    ///
+    /// If set, the listener is moved along with the freecam so battle audio follows the camera.
+    pub audio_listener_address: Option<usize>,
+    /// If set, a secondary keyboard (or macro pad) can be used as a dedicated camera control surface.
+    pub director: Option<DirectorConfig>,
     pub keybinds: KeybindsConfig,
+    /// How errors the freecam can recover from (e.g., a patch or hook which failed) are shown to the user.
+    pub error_display: ErrorDisplay,
+    /// Short on-screen messages about what the freecam is doing, e.g., when a bookmark is saved.
+    pub overlay: OverlayConfig,
+    /// What to do when the same keys are bound to multiple actions, see [crate::keybind_conflicts].
+    pub keybind_conflicts: ConflictHandling,
     pub camera: CameraConfig,
+    /// Named alternatives for the `camera` settings, which can be switched to through the `http_api_port`.
+    ///
+    /// Any settings left out use their defaults.
+    pub camera_profiles: BTreeMap<String, CameraConfig>,
+    /// If set, serves a small HTTP API on `localhost` at the given port for remote control apps.
+    pub http_api_port: Option<u16>,
+    /// Whether to publish the camera's state to shared memory for OBS plugins/overlays, see [crate::shared_memory].
+    pub shared_memory_telemetry: bool,
+    /// Whether to run without any patches or input hooks, only reading the game's camera/battle state.
+    ///
+    /// The state is still published through the `http_api_port`, `shared_memory_telemetry`, and state dumps, but the
+    /// camera can't be controlled. Requires a game restart.
+    pub observer_mode: bool,
+    /// Whether to never patch the game's code, for executables where our patches crash the game.
+    ///
+    /// Only the mouse-look of the game's own camera (as without the `custom_camera_enabled`) is then available. Applies
+    /// from the next battle onwards.
+    pub safe_mode: bool,
+    pub window: WindowConfig,
+    pub screenshot: ScreenshotConfig,
+    pub camera_paths: CameraPathConfig,
+    pub bookmarks: BookmarkConfig,
+    pub shake: ShakeConfig,
+    pub auto_director: AutoDirectorConfig,
+    pub bind_capture: BindCaptureConfig,
+    pub scripting: ScriptingConfig,
+    /// Features which may not work (fully) yet.
+    pub experimental: ExperimentalConfig,
 }
 
 impl Default for FreecamConfig {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             console: false,
+            console_status_interval: None,
+            log_level: LevelFilter::Info,
+            log_max_file_size_kb: 5 * 1024,
+            log_max_files: 3,
             update_rate: 144,
+            vsync_to_game: false,
+            present_fn_address: None,
             reload_config_keys: Some(vec![VirtualKey::VK_CONTROL, VirtualKey::VK_SHIFT, VirtualKey::VK_R]),
+            dump_state_keys: Some(vec![VirtualKey::VK_CONTROL, VirtualKey::VK_SHIFT, VirtualKey::VK_F9]),
             keybinds: Default::default(),
+            keybind_conflicts: ConflictHandling::Warn,
+            error_display: ErrorDisplay::MessageBox,
+            overlay: Default::default(),
             camera: Default::default(),
+            camera_profiles: BTreeMap::new(),
+            http_api_port: None,
+            shared_memory_telemetry: false,
+            observer_mode: false,
+            safe_mode: false,
+            window: Default::default(),
+            screenshot: Default::default(),
+            camera_paths: Default::default(),
+            bookmarks: Default::default(),
+            shake: Default::default(),
+            auto_director: Default::default(),
+            bind_capture: Default::default(),
+            scripting: Default::default(),
+            experimental: Default::default(),
             force_ttw_camera: true,
+            toggle_force_ttw_camera_keys: None,
+            toggle_freecam_keys: None,
+            reset_keys: Some(vec![VirtualKey::VK_CONTROL, VirtualKey::VK_SHIFT, VirtualKey::VK_HOME]),
+            reset_view_keys: Some(vec![VirtualKey::VK_CONTROL, VirtualKey::VK_SHIFT, VirtualKey::VK_END]),
             block_game_middle_mouse_functionality: true,
+            director: None,
+            battle_phase_addresses: None,
+            audio_listener_address: None,
+            log_teleport_events: false,
+            measure_tick_timings: false,
+            self_test: false,
+            executable_check: None,
+            patch_profile: PatchProfile::steam(),
+            remote_captures: Default::default(),
+            disabled_patch_groups: Vec::new(),
+            foreign_patches: ForeignPatchHandling::Warn,
         }
     }
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+#[serde(default)]
 pub struct CameraConfig {
     pub custom_camera_enabled: bool,
-    /// Whether camera rotation is inverted or not.
-    pub inverted: bool,
+    /// Whether moving the mouse up/down while rotating the camera is inverted.
+    pub invert_pitch: bool,
+    /// Whether moving the mouse left/right while rotating the camera is inverted.
+    pub invert_yaw: bool,
+    /// Whether dragging the mouse left/right with the `drag_pan_key` is inverted.
+    pub invert_horizontal_drag: bool,
     /// Whether the mouse scroll is inverted or not
     pub inverted_scroll: bool,
+    /// How far a single notch of the mouse wheel zooms.
+    pub scroll_speed: f32,
+    /// How the notches scrolled in a single update translate into zoom.
+    pub scroll_response: ScrollResponse,
+    /// The largest zoom step a single update of scrolling can cause, keeps fast flicks from sending the camera flying.
+    pub scroll_max_step: f32,
+    /// Spreads each zoom step out over roughly this duration, for smoother zooming. Zero applies steps immediately.
+    pub scroll_smoothing_window: Duration,
+    /// What scrolling horizontally does, scaled by `scroll_speed`.
+    pub horizontal_scroll: HorizontalScroll,
     /// Whether to adapt movement/scroll speed to be based on how far from the ground the camera is.
     ///
     /// Similar to the Warhammer TTW camera.
     pub ground_distance_speed: bool,
     pub sensitivity: f32,
-    pub rotate_smoothing: f32,
+    /// Whether mouse look and drag panning are scaled by the monitor's resolution (and DPI scaling), relative to a
+    /// 1080p monitor, so the same `sensitivity` turns the camera equally far for the same fraction of the screen.
+    pub resolution_independent_mouse: bool,
+    /// How much of the pitch/yaw velocity is kept each tick, in the range `0..1`. Higher values mean more inertia.
+    pub look_smoothing: f32,
+    /// How strongly mouse/keyboard input accelerates the pitch/yaw. Higher values mean a more responsive camera.
+    pub look_acceleration: f32,
+    /// How fast the rotation keybinds (`rotate_left`, `pitch_up`, etc.) turn the camera.
+    pub keyboard_look_speed: f32,
+    /// How long the rotation keybinds have to be held to reach the `keyboard_look_max_speed`.
+    ///
+    /// A zero duration turns at a constant speed.
+    pub keyboard_look_ramp: Duration,
+    /// The multiplier for the `keyboard_look_speed` reached after holding a rotation keybind for `keyboard_look_ramp`.
+    pub keyboard_look_max_speed: f32,
+    /// The filter applied to raw mouse movement while rotating the camera, for smoothing out jittery mice.
+    pub look_filter: LookFilter,
+    /// The lowest pitch (in degrees, negative is looking down) the camera can look at.
+    pub min_pitch: f32,
+    /// The highest pitch (in degrees) the camera can look at.
+    pub max_pitch: f32,
+    /// Whether to ignore the `min_pitch`/`max_pitch`, allowing the camera to look (almost) straight down or up.
+    pub full_vertical_look: bool,
+    /// If set, gently pulls the pitch/yaw towards common angles (e.g., level with the horizon) after rotating.
+    pub angle_snap: Option<AngleSnap>,
+    /// How far along the view direction the game's camera target is placed.
+    pub target_distance: TargetDistance,
     pub vertical_smoothing: f32,
     pub horizontal_smoothing: f32,
+    /// How the `horizontal_smoothing`, `vertical_smoothing`, and `look_smoothing` slow the camera down.
+    pub smoothing_models: SmoothingModels,
     pub horizontal_base_speed: f32,
     pub vertical_base_speed: f32,
+    /// If set, the maximum horizontal speed of the camera in units per second, regardless of multipliers and the
+    /// update rate.
+    pub max_horizontal_velocity: Option<f32>,
+    /// If set, the maximum vertical speed of the camera in units per second.
+    pub max_vertical_velocity: Option<f32>,
     pub slow_multiplier: f32,
     pub fast_multiplier: f32,
+    /// If set, the camera automatically slows down while it's within this zone above the ground.
+    ///
+    /// Requires `remote_z` to be captured, as it depends on the ground level.
+    pub landing_zone: Option<LandingZone>,
+    /// The exact speed (in units per second) the camera moves at in dolly mode, see `dolly_key`.
+    pub dolly_speed: f32,
+    /// How fast the camera pans while dragging with the `drag_pan_key`.
+    pub drag_pan_speed: f32,
+    /// Whether moving diagonally (or horizontally and vertically at once) is as fast as moving in a single direction.
+    ///
+    /// If not, the speeds of every direction add up, making diagonal movement faster.
+    pub normalize_diagonal: bool,
+    /// The speed of moving forward/backward with the movement keys, relative to the `horizontal_base_speed`.
+    pub forward_speed_scale: f32,
+    /// The speed of moving left/right with the movement keys, relative to the `horizontal_base_speed`.
+    pub strafe_speed_scale: f32,
+    /// Whether the arrow keys move the custom camera, in addition to the movement keybinds.
+    pub arrow_keys_move_camera: bool,
+    /// Whether the arrow keys should be hidden from the game during battles, so they don't move the game's own camera.
+    pub block_game_arrow_keys: bool,
+    /// If set, the custom camera moves while the cursor is within this many pixels of the window's edge.
+    pub edge_scroll_margin: Option<u32>,
     /// Whether to remain at a consistent height level above the terrain when moving the camera.
     pub maintain_relative_height: bool,
+    /// If set, the camera follows the terrain less the higher it flies, halving every this many units above the ground.
+    ///
+    /// Keeps low flights hugging the terrain, while high flights don't bounce over every hill. Requires
+    /// `maintain_relative_height`, `None` always follows the terrain fully.
+    pub relative_height_falloff: Option<f32>,
+    /// How long after the game's last write to the camera its pan (e.g., towards a unit) is considered finished.
+    ///
+    /// The relative height is left alone during the game's pans, afterwards the height the pan ended at is kept.
     pub relative_height_panning_delay: Duration,
+    /// How long to take to ease towards a camera position set by the game (e.g., when it pans towards a unit).
+    ///
+    /// Afterwards the camera follows the game's pan directly. A zero duration snaps to the game's camera instantly.
+    pub sync_transition_duration: Duration,
+    /// The easing curve to use for the `sync_transition_duration`.
+    pub sync_transition_easing: Easing,
+    /// How long to take to fly to a unit after double clicking its unit card.
+    ///
+    /// The flight follows the `teleport_transition_easing` exactly, the velocity smoothing and speed settings don't apply
+    /// to it. A zero duration teleports the camera instantly.
+    pub teleport_transition_duration: Duration,
+    /// The easing curve to use for the `teleport_transition_duration`.
+    pub teleport_transition_easing: Easing,
+    /// The lowest altitude the camera can move to, regardless of the ground level. `None` for no limit.
+    pub min_altitude: Option<f32>,
+    /// The highest altitude the camera can move to.
+    pub max_altitude: f32,
+    /// Whether to log the camera's altitude and distance to the ground every second, useful for tuning these settings.
+    pub altitude_readout: bool,
+    /// Experimental: whether to call the game's ground level function ourselves while the camera drifts, see
+    /// `prevent_ground_clipping`.
+    ///
+    /// This races with the game's own thread and can crash the game, so it's off by default.
+    pub force_ground_height_eval: bool,
+    /// The minimum time between calls to the game's ground level function.
+    pub ground_height_eval_interval: Duration,
     /// Whether to try to prevent the camera from clipping through the ground.
     pub prevent_ground_clipping: bool,
     /// How much of a difference there should _at least_ be between the ground level and the current camera position
     ///
     /// Setting this higher ensures less ground clipping will occur, but you won't be able to zoom in as much.
     pub ground_clip_margin: f32,
+    /// How far above the `ground_clip_margin` the camera starts slowing down when moving towards the ground.
+    ///
+    /// Set to `0` to only stop once the margin is reached.
+    pub ground_slowdown_distance: f32,
+}
+
+impl CameraConfig {
+    pub fn smoothing(&self) -> Smoothing {
+        Smoothing {
+            horizontal: self.horizontal_smoothing,
+            vertical: self.vertical_smoothing,
+            rotate: self.look_smoothing,
+        }
+    }
+
+    /// Limit the `velocity` to the `max_horizontal_velocity`/`max_vertical_velocity`, given the duration of a tick.
+    pub fn clamp_velocity(&self, velocity: &mut Velocity, tick_seconds: f32) {
+        velocity::clamp_velocity(
+            velocity,
+            self.max_horizontal_velocity
+                .map_or(f32::INFINITY, |max| max * tick_seconds),
+            self.max_vertical_velocity
+                .map_or(f32::INFINITY, |max| max * tick_seconds),
+        );
+    }
+
+    /// The distance from the camera to place the game's camera target at.
+    ///
+    /// * `game_distance` - The distance the game placed its own target at, see [TargetDistance::Game].
+    pub fn target_distance(&self, game_distance: f32) -> f32 {
+        match self.target_distance {
+            TargetDistance::Fixed { distance } => distance,
+            TargetDistance::Game => game_distance,
+        }
+    }
+
+    /// Clamp the given pitch (in radians) to the configured `min_pitch`/`max_pitch`, or just short of vertical with
+    /// `full_vertical_look`.
+    pub fn clamp_pitch(&self, pitch: f32) -> f32 {
+        if self.full_vertical_look {
+            bounds::clamp_pitch_to(pitch, -bounds::VERTICAL_PITCH, bounds::VERTICAL_PITCH)
+        } else {
+            bounds::clamp_pitch_to(pitch, self.min_pitch.to_radians(), self.max_pitch.to_radians())
+        }
+    }
+
+    /// Clamp the given position to the map area and the configured altitude range.
+    pub fn clamp_to_bounds(&self, pos: WorldPos) -> WorldPos {
+        bounds::clamp_to_bounds(pos, self.min_altitude.unwrap_or(f32::NEG_INFINITY), self.max_altitude)
+    }
 }
 
 impl Default for CameraConfig {
     fn default() -> Self {
         Self {
             custom_camera_enabled: true,
-            inverted: false,
+            invert_pitch: false,
+            invert_yaw: false,
+            invert_horizontal_drag: false,
             inverted_scroll: true,
+            scroll_speed: 1.0,
+            scroll_response: ScrollResponse::Exponential { exponent: 2.0 },
+            scroll_max_step: 16.0,
+            scroll_smoothing_window: Duration::ZERO,
+            horizontal_scroll: HorizontalScroll::Ignore,
             ground_distance_speed: true,
             sensitivity: 1.0,
-            rotate_smoothing: 0.75,
+            resolution_independent_mouse: false,
+            look_smoothing: 0.75,
+            look_acceleration: 0.25,
+            keyboard_look_speed: 1.0,
+            keyboard_look_ramp: Duration::ZERO,
+            keyboard_look_max_speed: 3.0,
+            look_filter: LookFilter::None,
+            min_pitch: -81.0,
+            max_pitch: 81.0,
+            full_vertical_look: false,
+            angle_snap: None,
+            target_distance: TargetDistance::Fixed { distance: 1000. },
             vertical_smoothing: 0.92,
             horizontal_smoothing: 0.92,
+            smoothing_models: SmoothingModels::default(),
             horizontal_base_speed: 1.0,
             vertical_base_speed: 1.0,
+            max_horizontal_velocity: None,
+            max_vertical_velocity: None,
             fast_multiplier: 3.5,
+            drag_pan_speed: 1.0,
+            normalize_diagonal: true,
+            forward_speed_scale: 1.0,
+            strafe_speed_scale: 1.0,
+            arrow_keys_move_camera: true,
+            block_game_arrow_keys: false,
+            edge_scroll_margin: None,
             maintain_relative_height: true,
+            relative_height_falloff: None,
             slow_multiplier: 0.2,
+            landing_zone: None,
+            dolly_speed: 10.,
+            min_altitude: None,
+            max_altitude: bounds::MAX_HEIGHT,
+            altitude_readout: false,
+            force_ground_height_eval: false,
+            ground_height_eval_interval: Duration::from_millis(10),
             prevent_ground_clipping: true,
             ground_clip_margin: 1.3,
+            ground_slowdown_distance: 5.0,
             relative_height_panning_delay: Duration::from_millis(25),
+            sync_transition_duration: Duration::from_millis(150),
+            sync_transition_easing: Easing::EaseInOut,
+            teleport_transition_duration: Duration::ZERO,
+            teleport_transition_easing: Easing::EaseInOut,
         }
     }
 }
 
+/// How to find the game's main window, and what to do when it's minimized and restored.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct WindowConfig {
+    /// The title of the game's main window, where `*` matches any text (case-insensitive).
+    ///
+    /// Only windows of the game's own process are considered, so running multiple instances of the game works.
+    pub title_pattern: String,
+    /// Whether to stop updating the camera while minimized.
+    pub minimize_pause_updates: bool,
+    /// Whether to keep our camera patches applied while minimized.
+    pub minimize_keep_patches: bool,
+    /// Whether to show the cursor and release any cursor clipping when minimized.
+    pub minimize_release_cursor: bool,
+    /// Whether to re-sync our camera with the game's camera (and discard any velocity) when restored.
+    pub restore_resync_camera: bool,
+    /// How long to wait after a restore before the camera responds to input again.
+    pub restore_grace_period: Duration,
+    /// Whether to keep updating the camera while another window is focused, ignoring any input.
+    ///
+    /// Keeps unit card teleports and the HTTP API working while alt-tabbed, e.g., for window capture.
+    pub background_updates: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            title_pattern: "M*".to_string(),
+            minimize_pause_updates: true,
+            minimize_keep_patches: true,
+            minimize_release_cursor: true,
+            restore_resync_camera: false,
+            restore_grace_period: Duration::ZERO,
+            background_updates: false,
+        }
+    }
+}
+
+/// Cinematic screenshots, saved as `.bmp` files to the `screenshots` folder next to the config.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct ScreenshotConfig {
+    /// The key to take a screenshot with, disabled if `None`.
+    pub key: Option<KeyBinding>,
+    /// The game's own keys for toggling its HUD, pressed before and after taking the screenshot.
+    pub hide_hud_chord: Option<KeyChord>,
+    /// How long to wait for the game to render a frame without HUD.
+    pub hide_hud_delay: Duration,
+    /// If set, black bars are added to the top and bottom of the screenshot to reach this aspect ratio (e.g., `2.39`).
+    pub letterbox_aspect_ratio: Option<f32>,
+}
+
+impl Default for ScreenshotConfig {
+    fn default() -> Self {
+        Self {
+            key: None,
+            hide_hud_chord: None,
+            hide_hud_delay: Duration::from_millis(100),
+            letterbox_aspect_ratio: None,
+        }
+    }
+}
+
+/// Camera paths, saved as `.json` files to the `camera_paths` folder next to the config.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct CameraPathConfig {
+    /// The key to start/stop recording the game's own camera motion, disabled if `None`.
+    pub record_vanilla_key: Option<KeyBinding>,
+    /// How often to record a keyframe while recording.
+    pub record_sample_interval: Duration,
+    /// The key to play the next path in the `camera_paths` folder (in alphabetical order), or stop the playing path.
+    pub play_key: Option<KeyBinding>,
+    /// The key to reload the paths in the `camera_paths` folder, e.g., after adding paths shared by someone else.
+    pub reload_key: Option<KeyBinding>,
+    /// The key to add the current camera pose as a keyframe to a new path.
+    pub keyframe_key: Option<KeyBinding>,
+    /// The key to save the keyframes placed with the `keyframe_key` as a new path.
+    pub export_key: Option<KeyBinding>,
+    /// How long the camera takes to move between two keyframes placed with the `keyframe_key`.
+    pub keyframe_interval: Duration,
+    /// The easing curve between keyframes when playing a path.
+    pub playback_easing: Easing,
+}
+
+impl Default for CameraPathConfig {
+    fn default() -> Self {
+        Self {
+            record_vanilla_key: None,
+            record_sample_interval: Duration::from_millis(50),
+            play_key: None,
+            reload_key: None,
+            keyframe_key: None,
+            export_key: None,
+            keyframe_interval: Duration::from_secs(3),
+            playback_easing: Easing::Linear,
+        }
+    }
+}
+
+/// Camera pose bookmarks, saved to `freecam_bookmarks.json` next to the config.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct BookmarkConfig {
+    /// One key per bookmark slot, pressing it flies the camera to the bookmark saved in that slot.
+    pub slots: Vec<KeyBinding>,
+    /// Hold this while pressing a slot's key to save the current camera pose to that slot instead.
+    pub save_modifier: KeyBinding,
+}
+
+impl Default for BookmarkConfig {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            save_modifier: VirtualKey::VK_CONTROL.into(),
+        }
+    }
+}
+
+/// Procedural camera shake for cinematic footage, only added to the camera the game sees.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ShakeConfig {
+    /// If set, pressing this shakes the camera at full strength.
+    pub key: Option<KeyBinding>,
+    #[serde(flatten)]
+    pub motion: ShakeMotion,
+}
+
+/// Moves the camera between points of interest on its own, e.g., for spectating AI battles or idling on stream.
+///
+/// See [crate::battle_cam::auto_director::AutoDirector].
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+#[serde(default)]
+pub struct AutoDirectorConfig {
+    /// If set, pressing this starts the auto director, pressing it again hands the camera back to the user.
+    pub key: Option<KeyBinding>,
+    /// The points of interest to visit, in order. Any which can't be found are skipped.
+    pub points_of_interest: Vec<PointOfInterest>,
+    /// How long the camera takes to move to the next point of interest.
+    pub transition_duration: Duration,
+    /// The easing curve to use for the `transition_duration`.
+    pub transition_easing: Easing,
+    /// How long the camera stays at (and orbits) each point of interest.
+    pub dwell_duration: Duration,
+    /// How far the camera orbits around a point of interest while dwelling there, in degrees.
+    ///
+    /// Negative values orbit in the other direction.
+    pub orbit_degrees: f32,
+    /// The horizontal distance between the camera and the point of interest.
+    pub distance: f32,
+    /// How far above the point of interest the camera is placed.
+    pub height: f32,
+}
+
+impl Default for AutoDirectorConfig {
+    fn default() -> Self {
+        Self {
+            key: None,
+            points_of_interest: vec![PointOfInterest::LargestMelee, PointOfInterest::General],
+            transition_duration: Duration::from_secs(4),
+            transition_easing: Easing::EaseInOut,
+            dwell_duration: Duration::from_secs(12),
+            orbit_degrees: 30.,
+            distance: 60.,
+            height: 25.,
+        }
+    }
+}
+
+/// Records the next pressed key combination and saves it to the config, see [crate::bind_capture].
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+#[serde(default)]
+pub struct BindCaptureConfig {
+    /// If set, pressing this starts capturing a new binding for the `action`.
+    pub key: Option<KeyBinding>,
+    /// The config option to bind, with nested fields and list indices separated by dots.
+    ///
+    /// E.g., `keybinds.forward_key` or `bookmarks.slots.0`.
+    pub action: String,
+}
+
+impl Default for BindCaptureConfig {
+    fn default() -> Self {
+        Self {
+            key: None,
+            action: "keybinds.freecam_key".to_string(),
+        }
+    }
+}
+
+/// User scripts controlling the camera, loaded from the `scripts` folder next to the config, see [crate::scripting].
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+#[serde(default)]
+pub struct ScriptingConfig {
+    /// Whether to load and run the scripts. They're reloaded together with the config.
+    pub enabled: bool,
+    /// How many operations a single script call may take before it's aborted, guarding against endless loops freezing
+    /// the camera. `0` removes the limit.
+    pub max_operations: u64,
+}
+
+impl Default for ScriptingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_operations: 100_000,
+        }
+    }
+}
+
+/// Where the game's camera target is placed when we point the camera, see [CameraConfig::target_distance].
+///
+/// Besides the view direction the game uses the target for its audio listener and level of detail, so a target far
+/// away from where the game would put it can make the battle's ambient audio pan oddly.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq)]
+pub enum TargetDistance {
+    /// Always place the target this many units along the view direction.
+    Fixed { distance: f32 },
+    /// Keep the target as far from the camera as the game had it when we took control, only changing its direction.
+    Game,
+}
+
+/// The values the game's code writes into our memory (`RemoteData`) instead of its own.
+///
+/// Disabling a capture removes its patch, with the features depending on it degrading instead of breaking.
+/// Changes apply from the next battle onwards.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct RemoteCaptureConfig {
+    /// Capture the `z` the game would've given the camera, needed to find the ground level.
+    ///
+    /// Without it `maintain_relative_height` and `prevent_ground_clipping` have no effect.
+    pub remote_z: bool,
+    /// Intercept the camera move after double clicking a unit card, needed for `teleport_transition_duration`.
+    ///
+    /// Without it the game moves the camera itself, after which our camera simply continues from there.
+    pub unit_card_teleport: bool,
+}
+
+impl RemoteCaptureConfig {
+    /// No captures at all, for the [FreecamConfig::safe_mode].
+    pub fn none() -> Self {
+        Self {
+            remote_z: false,
+            unit_card_teleport: false,
+        }
+    }
+
+    /// Turn off the captures belonging to any of the `disabled` groups.
+    pub fn without(mut self, disabled: &[PatchGroup]) -> Self {
+        self.remote_z &= !disabled.contains(&PatchGroup::RemoteZ);
+        self.unit_card_teleport &= !disabled.contains(&PatchGroup::UnitCardTeleport);
+        self
+    }
+}
+
+impl Default for RemoteCaptureConfig {
+    fn default() -> Self {
+        Self {
+            remote_z: true,
+            unit_card_teleport: true,
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct ExperimentalConfig {
+    /// If set, unit card teleports are additionally executed by calling the game's own camera function on the game thread.
+    pub game_thread_teleport: Option<GameThreadTeleportConfig>,
+}
+
+/// Addresses (as decimal numbers) required for [crate::battle_cam::game_thread::GameThreadTeleporter].
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+pub struct GameThreadTeleportConfig {
+    /// The game's own "set camera position" function.
+    pub set_camera_fn: usize,
+    /// An argument-less function the game calls once per frame, which is hooked to run the teleport on the game thread.
+    pub tick_fn: usize,
+}
+
+impl Default for ExperimentalConfig {
+    fn default() -> Self {
+        Self {
+            game_thread_teleport: None,
+        }
+    }
+}
+
+/// Addresses of flags which are `!= 0` while the battle is in the given phase.
+///
+/// These haven't been confirmed for all game versions, hence why they're left to the user.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct BattlePhaseAddresses {
+    /// Set while the battle's loading screen is shown.
+    pub loading: Option<usize>,
+    pub deployment: Option<usize>,
+    pub battle_ended: Option<usize>,
+}
+
+/// Configuration for a secondary 'director' keyboard.
+///
+/// All configured [KeybindsConfig] keys can be pressed on this device as well.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+pub struct DirectorConfig {
+    /// (Part of) the Raw Input device name of the keyboard, for example `VID_046D&PID_C31C`. Case-insensitive.
+    ///
+    /// All keyboard device names are logged at debug level once they're first used.
+    pub device_name: String,
+    /// Whether key presses on the director device should be hidden from the game.
+    pub swallow_keys: bool,
+}
+
 /// All keys that need to be pressed for a speed state to be selected.
 ///
 /// Expects [virtual key codes](https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes).
+/// Each binding can be a single key, a chord such as `"VK_CONTROL+VK_W"`, or a list of alternative chords.
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
 pub struct KeybindsConfig {
-    pub fast_key: VirtualKey,
-    pub slow_key: VirtualKey,
-    pub freecam_key: VirtualKey,
-    pub forward_key: VirtualKey,
-    pub backwards_key: VirtualKey,
-    pub left_key: VirtualKey,
-    pub right_key: VirtualKey,
-    pub rotate_left: VirtualKey,
-    pub rotate_right: VirtualKey,
+    pub fast_key: KeyBinding,
+    pub slow_key: KeyBinding,
+    pub freecam_key: KeyBinding,
+    /// Whether the `freecam_key` has to be held down to look around, or toggles looking around.
+    pub freecam_mode: FreecamMode,
+    pub forward_key: KeyBinding,
+    pub backwards_key: KeyBinding,
+    pub left_key: KeyBinding,
+    pub right_key: KeyBinding,
+    pub rotate_left: KeyBinding,
+    pub rotate_right: KeyBinding,
+    /// Optional keys to look up and down with, for controlling the camera without a mouse.
+    pub pitch_up: Option<KeyBinding>,
+    pub pitch_down: Option<KeyBinding>,
+    /// Optional keys to move straight up and down with, at the `vertical_base_speed`.
+    pub up_key: Option<KeyBinding>,
+    pub down_key: Option<KeyBinding>,
+    /// Optional key to toggle dolly mode, which moves the camera at exactly the `dolly_speed` without any acceleration
+    /// or smoothing, for steady tracking shots.
+    pub dolly_key: Option<KeyBinding>,
+    /// Optional key to hold while dragging the mouse to pan the camera, e.g. `VK_MBUTTON` like the base game.
+    ///
+    /// Can't be the same as `freecam_key`.
+    pub drag_pan_key: Option<KeyBinding>,
+    /// What to do when opposing keys (e.g., `forward_key` and `backwards_key`) are held at the same time.
+    pub opposing_keys: OpposingKeys,
 }
 
 impl Default for KeybindsConfig {
     fn default() -> Self {
         Self {
-            fast_key: VirtualKey::VK_SHIFT,
-            slow_key: VirtualKey::VK_MENU,
-            freecam_key: VirtualKey::VK_MBUTTON,
-            forward_key: VirtualKey::VK_W,
-            backwards_key: VirtualKey::VK_S,
-            left_key: VirtualKey::VK_A,
-            right_key: VirtualKey::VK_D,
-            rotate_left: VirtualKey::VK_Q,
-            rotate_right: VirtualKey::VK_E,
+            fast_key: VirtualKey::VK_SHIFT.into(),
+            slow_key: VirtualKey::VK_MENU.into(),
+            freecam_key: VirtualKey::VK_MBUTTON.into(),
+            freecam_mode: FreecamMode::Hold,
+            forward_key: VirtualKey::VK_W.into(),
+            backwards_key: VirtualKey::VK_S.into(),
+            left_key: VirtualKey::VK_A.into(),
+            right_key: VirtualKey::VK_D.into(),
+            rotate_left: VirtualKey::VK_Q.into(),
+            rotate_right: VirtualKey::VK_E.into(),
+            pitch_up: None,
+            pitch_down: None,
+            up_key: None,
+            down_key: None,
+            dolly_key: None,
+            drag_pan_key: None,
+            opposing_keys: OpposingKeys::Cancel,
         }
     }
 }
@@ -130,14 +867,66 @@ pub fn load_config(directory: impl AsRef<Path>) -> anyhow::Result<FreecamConfig>
     let path = directory.as_ref().join(CONFIG_FILE_NAME);
     let file = std::fs::read(&path)?;
 
-    if let Ok(conf) = serde_json::from_slice(&file) {
-        validate_config(&conf)?;
-        Ok(conf)
-    } else {
-        std::fs::remove_file(&path)?;
-        create_initial_config(directory.as_ref())?;
-        let file = std::fs::read(&path)?;
-        serde_json::from_slice(&file).context("Couldn't load config.")
+    let parsed = serde_json::from_slice::<serde_json::Value>(&file)
+        .ok()
+        .and_then(|original| {
+            let upgraded = upgrade_config(original.clone());
+            let conf = serde_json::from_value::<FreecamConfig>(upgraded.clone()).ok()?;
+            Some((conf, original != upgraded))
+        });
+
+    match parsed {
+        Some((conf, was_upgraded)) => {
+            if was_upgraded {
+                // Ensure the user can see (and tweak) any new fields.
+                let mut file = std::fs::File::create(&path)?;
+                serde_json::to_writer_pretty(&mut file, &conf)?;
+            }
+
+            validate_config(&conf)?;
+            Ok(conf)
+        }
+        None => {
+            // Keep the user's values around for them to copy over manually.
+            std::fs::rename(&path, directory.as_ref().join(CONFIG_BACKUP_FILE_NAME))?;
+            create_initial_config(directory.as_ref())?;
+            let file = std::fs::read(&path)?;
+            serde_json::from_slice(&file).context("Couldn't load config.")
+        }
+    }
+}
+
+/// Upgrade the given (possibly outdated) config to the current [CONFIG_VERSION].
+///
+/// Runs all relevant [MIGRATIONS], after which any missing fields are filled with their defaults.
+pub fn upgrade_config(mut conf: serde_json::Value) -> serde_json::Value {
+    let version = conf.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+
+    for migration in MIGRATIONS.iter().skip(version) {
+        migration(&mut conf);
+    }
+
+    let mut upgraded = serde_json::to_value(FreecamConfig::default()).expect("Default config is always serializable");
+    merge_json(&mut upgraded, conf);
+    upgraded["version"] = CONFIG_VERSION.into();
+
+    upgraded
+}
+
+/// Recursively overwrite all values in `target` with those present in `source`.
+fn merge_json(target: &mut serde_json::Value, source: serde_json::Value) {
+    match (target, source) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(source)) => {
+            for (key, value) in source {
+                match target.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, source) => *target = source,
     }
 }
 
@@ -154,27 +943,250 @@ pub fn create_initial_config(directory: impl AsRef<Path>) -> anyhow::Result<()>
 }
 
 pub fn validate_config(conf: &FreecamConfig) -> anyhow::Result<()> {
-    if conf.camera.vertical_smoothing.abs() >= 1. {
+    if conf.patch_profile.patch_locations.is_empty() {
+        anyhow::bail!("The `patch_profile` should contain at least one patch location!")
+    }
+    if !conf.patch_profile.patch_locations.is_grouped() {
+        let write_groups = conf
+            .disabled_patch_groups
+            .iter()
+            .filter(|group| group.is_write_group())
+            .collect::<Vec<_>>();
+        if !write_groups.is_empty() {
+            anyhow::bail!(
+                "Can't disable {:?}, the `patch_locations` of the `patch_profile` aren't divided into groups!",
+                write_groups
+            )
+        }
+    }
+    if let Some((_, location)) = conf
+        .patch_profile
+        .patch_locations
+        .iter()
+        .find(|(_, location)| location.original().is_some_and(|original| !is_valid_original(original)))
+    {
         anyhow::bail!(
-            "Smoothening values should be in the range 0..1. Vertical smoothing was `{}`!",
-            conf.camera.vertical_smoothing
+            "The `original` bytes of patch location {:?} should be a single 3 byte, or 5 byte `F3` instruction!",
+            location.address()
         )
     }
-    if conf.camera.horizontal_smoothing.abs() >= 1. {
+    if let PatchLocations::Grouped(groups) = &conf.patch_profile.patch_locations {
+        if let Some(group) = groups.keys().find(|group| !group.is_write_group()) {
+            anyhow::bail!("The `patch_locations` can't contain a `{:?}` group!", group)
+        }
+    }
+    validate_shake(&conf.shake)?;
+    let director = &conf.auto_director;
+    if !(director.distance >= 0. && director.height.is_finite() && director.orbit_degrees.is_finite()) {
         anyhow::bail!(
-            "Smoothening values should be in the range 0..1. Horizontal smoothing was `{}`!",
-            conf.camera.horizontal_smoothing
+            "The auto director `distance` can't be negative, and its `height` and `orbit_degrees` should be finite, \
+             were `{}`, `{}`, and `{}`!",
+            director.distance,
+            director.height,
+            director.orbit_degrees
         )
     }
-    if conf.camera.rotate_smoothing.abs() >= 1. {
+    if conf.bind_capture.key.is_some() {
+        let exists = serde_json::to_value(conf)
+            .ok()
+            .is_some_and(|value| value.pointer(&action_pointer(&conf.bind_capture.action)).is_some());
+        if !exists {
+            anyhow::bail!(
+                "The bind capture `action` `{}` doesn't exist in the config!",
+                conf.bind_capture.action
+            )
+        }
+    }
+    validate_camera(&conf.camera)?;
+    for (name, camera) in &conf.camera_profiles {
+        validate_camera(camera).map_err(|e| anyhow::anyhow!("Invalid camera profile `{}`: {}", name, e))?;
+    }
+    if conf.keybinds.drag_pan_key.as_ref() == Some(&conf.keybinds.freecam_key) {
+        anyhow::bail!("The `drag_pan_key` can't be the same as the `freecam_key`, change one of them!")
+    }
+    if conf.screenshot.letterbox_aspect_ratio.is_some_and(|ratio| ratio <= 0.) {
         anyhow::bail!(
-            "Smoothening values should be in the range 0..1. Rotate smoothing was `{}`!",
-            conf.camera.rotate_smoothing
+            "Letterbox aspect ratio should be positive, was `{:?}`!",
+            conf.screenshot.letterbox_aspect_ratio
         )
     }
+    if conf.window.title_pattern.trim().is_empty() {
+        anyhow::bail!("The window `title_pattern` can't be empty, use `*` to match any title!")
+    }
+    if conf.console_status_interval.is_some_and(|interval| interval.is_zero()) {
+        anyhow::bail!("The `console_status_interval` should be above 0!")
+    }
+    if conf.camera_paths.keyframe_interval.is_zero() {
+        anyhow::bail!("The camera path `keyframe_interval` should be above 0!")
+    }
+    if conf.vsync_to_game && conf.present_fn_address.is_none() {
+        anyhow::bail!("`vsync_to_game` requires the `present_fn_address` to be set!")
+    }
     if conf.update_rate < 30 {
         anyhow::bail!("Update rate must be at least 30, was {}", conf.update_rate)
     }
 
     Ok(())
 }
+
+fn validate_shake(shake: &ShakeConfig) -> anyhow::Result<()> {
+    let motion = &shake.motion;
+    if !(motion.amplitude >= 0. && motion.rotation_amplitude >= 0.) {
+        anyhow::bail!(
+            "The shake `amplitude` and `rotation_amplitude` can't be negative, were `{}` and `{}`!",
+            motion.amplitude,
+            motion.rotation_amplitude
+        )
+    }
+    if !(motion.frequency > 0. && motion.decay_time > 0.) {
+        anyhow::bail!(
+            "The shake `frequency` and `decay_time` should be positive, were `{}` and `{}`!",
+            motion.frequency,
+            motion.decay_time
+        )
+    }
+
+    Ok(())
+}
+
+fn validate_camera(camera: &CameraConfig) -> anyhow::Result<()> {
+    if let ScrollResponse::Exponential { exponent } = camera.scroll_response {
+        if exponent.is_nan() || exponent <= 0. {
+            anyhow::bail!("The scroll response `exponent` should be positive, was `{}`!", exponent)
+        }
+    }
+    for (name, max) in [
+        ("max_horizontal_velocity", camera.max_horizontal_velocity),
+        ("max_vertical_velocity", camera.max_vertical_velocity),
+    ] {
+        if let Some(max) = max.filter(|max| max.is_nan() || *max <= 0.) {
+            anyhow::bail!("The `{}` should be positive, was `{}`!", name, max)
+        }
+    }
+    for (name, scale) in [
+        ("forward_speed_scale", camera.forward_speed_scale),
+        ("strafe_speed_scale", camera.strafe_speed_scale),
+    ] {
+        if !(0. ..=1.).contains(&scale) {
+            anyhow::bail!("The `{}` should be in the range 0..=1, was `{}`!", name, scale)
+        }
+    }
+    if camera.scroll_max_step.is_nan() || camera.scroll_max_step <= 0. {
+        anyhow::bail!(
+            "The `scroll_max_step` should be positive, was `{}`!",
+            camera.scroll_max_step
+        )
+    }
+    match camera.look_filter {
+        LookFilter::LowPass { smoothing } if !(0. ..1.).contains(&smoothing) => {
+            anyhow::bail!(
+                "Look filter smoothing should be in the range 0..1, but was `{}`!",
+                smoothing
+            )
+        }
+        LookFilter::OneEuro { min_cutoff, beta } if min_cutoff <= 0. || beta < 0. => {
+            anyhow::bail!(
+                "Look filter `min_cutoff` should be positive and `beta` non-negative, but were `{}` and `{}`!",
+                min_cutoff,
+                beta
+            )
+        }
+        _ => {}
+    }
+    if !(-90. ..=90.).contains(&camera.min_pitch) || !(-90. ..=90.).contains(&camera.max_pitch) {
+        anyhow::bail!(
+            "The `min_pitch` and `max_pitch` should be in the range -90..=90, were `{}` and `{}`!",
+            camera.min_pitch,
+            camera.max_pitch
+        )
+    }
+    if camera.min_pitch > camera.max_pitch {
+        anyhow::bail!(
+            "The `min_pitch` should be below the `max_pitch` ({}), was `{}`!",
+            camera.max_pitch,
+            camera.min_pitch
+        )
+    }
+    if let Some(snap) = &camera.angle_snap {
+        if snap.step_degrees.is_nan() || snap.step_degrees <= 0. {
+            anyhow::bail!(
+                "The angle snap `step_degrees` should be positive, was `{}`!",
+                snap.step_degrees
+            )
+        }
+        if snap.range_degrees.is_nan() || snap.range_degrees < 0. {
+            anyhow::bail!(
+                "The angle snap `range_degrees` should be non-negative, was `{}`!",
+                snap.range_degrees
+            )
+        }
+        if !(0. ..=1.).contains(&snap.strength) {
+            anyhow::bail!(
+                "The angle snap `strength` should be in the range 0..=1, was `{}`!",
+                snap.strength
+            )
+        }
+    }
+    if let Some(zone) = &camera.landing_zone {
+        if zone.height.is_nan() || zone.height <= 0. {
+            anyhow::bail!("The landing zone `height` should be positive, was `{}`!", zone.height)
+        }
+        if zone.min_multiplier.is_nan() || zone.min_multiplier <= 0. || zone.min_multiplier > 1. {
+            anyhow::bail!(
+                "The landing zone `min_multiplier` should be above 0 and at most 1, was `{}`!",
+                zone.min_multiplier
+            )
+        }
+    }
+    if let TargetDistance::Fixed { distance } = camera.target_distance {
+        if !(distance.is_finite() && distance >= MIN_TARGET_DISTANCE) {
+            anyhow::bail!(
+                "The `target_distance` should be at least {}, was `{}`!",
+                MIN_TARGET_DISTANCE,
+                distance
+            )
+        }
+    }
+    if !(camera.dolly_speed.is_finite() && camera.dolly_speed > 0.) {
+        anyhow::bail!("The `dolly_speed` should be positive, was `{}`!", camera.dolly_speed)
+    }
+    if camera.vertical_smoothing.abs() >= 1. {
+        anyhow::bail!(
+            "Smoothening values should be in the range 0..1. Vertical smoothing was `{}`!",
+            camera.vertical_smoothing
+        )
+    }
+    if camera.horizontal_smoothing.abs() >= 1. {
+        anyhow::bail!(
+            "Smoothening values should be in the range 0..1. Horizontal smoothing was `{}`!",
+            camera.horizontal_smoothing
+        )
+    }
+    if camera.look_smoothing.abs() >= 1. {
+        anyhow::bail!(
+            "Smoothening values should be in the range 0..1. Look smoothing was `{}`!",
+            camera.look_smoothing
+        )
+    }
+    if !camera.keyboard_look_speed.is_finite() || camera.keyboard_look_speed < 0. {
+        anyhow::bail!(
+            "The `keyboard_look_speed` should be a non-negative number, was `{}`!",
+            camera.keyboard_look_speed
+        )
+    }
+    if !camera.keyboard_look_max_speed.is_finite() || camera.keyboard_look_max_speed < 1. {
+        anyhow::bail!(
+            "The `keyboard_look_max_speed` should be at least `1.0`, was `{}`!",
+            camera.keyboard_look_max_speed
+        )
+    }
+    if camera.min_altitude.is_some_and(|min| min >= camera.max_altitude) {
+        anyhow::bail!(
+            "The `min_altitude` should be below the `max_altitude` ({}), was `{:?}`!",
+            camera.max_altitude,
+            camera.min_altitude
+        )
+    }
+
+    Ok(())
+}
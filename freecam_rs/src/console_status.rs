@@ -0,0 +1,146 @@
+//! A live-updating status line at the bottom of the console, see [FreecamConfig::console_status_interval].
+//!
+//! The line is redrawn in place with a carriage return instead of logging a new line every refresh. Log messages go
+//! through [ConsoleLogWriter], which clears the status line before writing and redraws it afterwards, so the two never
+//! end up on the same line.
+//!
+//! [FreecamConfig::console_status_interval]: crate::config::FreecamConfig::console_status_interval
+
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::battle_cam::{BattleCamera, CameraStatus};
+use crate::config::FreecamConfig;
+
+/// The status line currently shown in the console, shared with the [ConsoleLogWriter] on other threads.
+static STATUS_LINE: Mutex<StatusLine> = Mutex::new(StatusLine {
+    text: String::new(),
+    drawn: false,
+});
+
+struct StatusLine {
+    text: String,
+    /// Whether the `text` is currently the last line in the console.
+    drawn: bool,
+}
+
+impl StatusLine {
+    fn width(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    fn clear(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        if self.drawn {
+            write!(out, "\r{:width$}\r", "", width = self.width())?;
+            self.drawn = false;
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        if !self.text.is_empty() {
+            write!(out, "\r{}", self.text)?;
+            out.flush()?;
+            self.drawn = true;
+        }
+        Ok(())
+    }
+}
+
+/// Refreshes the status line at the configured interval.
+#[derive(Default)]
+pub struct ConsoleStatus {
+    last_refresh: Option<Instant>,
+}
+
+impl ConsoleStatus {
+    pub fn update(&mut self, conf: &FreecamConfig, battle_cam: &mut BattleCamera) {
+        let Some(interval) = conf.console_status_interval.filter(|_| conf.console) else {
+            if self.last_refresh.take().is_some() {
+                hide();
+            }
+            return;
+        };
+        if self.last_refresh.is_some_and(|last| last.elapsed() < interval) {
+            return;
+        }
+
+        self.last_refresh = Some(Instant::now());
+        show(format_status(battle_cam.status().as_ref()));
+    }
+}
+
+impl Drop for ConsoleStatus {
+    fn drop(&mut self) {
+        hide();
+    }
+}
+
+/// Writes log messages to the console without mixing them into the status line.
+pub struct ConsoleLogWriter;
+
+impl Write for ConsoleLogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let Ok(mut status) = STATUS_LINE.lock() else {
+            return std::io::stdout().write(buf);
+        };
+        let mut stdout = std::io::stdout().lock();
+
+        status.clear(&mut stdout)?;
+        let written = stdout.write(buf)?;
+        // A log message is written in several parts, the status line is only redrawn once it's complete.
+        if buf[..written].ends_with(b"\n") {
+            status.draw(&mut stdout)?;
+        }
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::stdout().flush()
+    }
+}
+
+fn show(text: String) {
+    let Ok(mut status) = STATUS_LINE.lock() else {
+        return;
+    };
+    let mut stdout = std::io::stdout().lock();
+
+    // Cleared first, as the new text could be shorter than the old.
+    let _ = status.clear(&mut stdout);
+    status.text = text;
+    let _ = status.draw(&mut stdout);
+}
+
+fn hide() {
+    if let Ok(mut status) = STATUS_LINE.lock() {
+        let _ = status.clear(&mut std::io::stdout().lock());
+        status.text.clear();
+    }
+}
+
+/// Format the `status` with fixed width columns, so the line doesn't jump around as the values change.
+fn format_status(status: Option<&CameraStatus>) -> String {
+    let Some(status) = status else {
+        return "Outside battle".to_string();
+    };
+    let ground = match status.ground_distance {
+        Some(distance) => format!("{:>7.1}", distance),
+        None => format!("{:>7}", "?"),
+    };
+
+    format!(
+        "{:<12} | x {:>8.1} y {:>8.1} z {:>7.1} | pitch {:>6.1}° yaw {:>5.1}° | ground {} | speed {:>6.2} | {:?}",
+        format!("{:?}", status.phase),
+        status.pos.x,
+        status.pos.y,
+        status.pos.z,
+        status.pitch.to_degrees(),
+        status.yaw.to_degrees().rem_euclid(360.),
+        ground,
+        status.speed,
+        status.patch_state,
+    )
+}
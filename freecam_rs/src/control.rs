@@ -0,0 +1,99 @@
+//! Lets other processes (e.g., `freecam_injector`) control the freecam running in a game process, see [ControlEvent].
+//!
+//! Each command is a named auto-reset event suffixed with the game's process id, so multiple running games don't
+//! interfere with each other.
+
+use std::path::{Path, PathBuf};
+
+use windows::core::HSTRING;
+use windows::Win32::Foundation::{CloseHandle, HANDLE, WAIT_OBJECT_0};
+use windows::Win32::System::Threading::{CreateEventW, GetCurrentProcessId, ResetEvent, SetEvent, WaitForSingleObject};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlEvent {
+    /// Reload the config, as if the `reload_config_keys` were pressed.
+    Reload,
+    /// Stop the freecam and remove all its patches and hooks, after which the DLL can safely be unloaded.
+    Detach,
+    /// Set by the freecam once it stopped after a [ControlEvent::Detach]. Unlike the others this event stays set.
+    Stopped,
+}
+
+impl ControlEvent {
+    /// The name of this event for the game process with the given `pid`.
+    pub fn name(self, pid: u32) -> String {
+        let kind = match self {
+            ControlEvent::Reload => "reload",
+            ControlEvent::Detach => "detach",
+            ControlEvent::Stopped => "stopped",
+        };
+
+        format!("Local\\med2_freecam_{}_{}", kind, pid)
+    }
+}
+
+/// The log file of the freecam loaded from `dll_directory`, assuming the game shares our environment variables.
+pub fn log_path(dll_directory: &Path) -> anyhow::Result<PathBuf> {
+    let config_dir = crate::paths::find_config_directory(dll_directory)?;
+
+    Ok(config_dir.path.join(format!("{}.log", crate::logging::LOG_FILE_NAME)))
+}
+
+/// The receiving end of the [ControlEvent]s in the game process.
+///
+/// Sets the [ControlEvent::Stopped] event when dropped, so it should be the first thing created (and thus the last
+/// thing dropped) by the freecam's main loop. Everything dropped before it joins its threads, and the drop itself waits
+/// for any open error message box, so none of our code is running anymore once the event is set.
+pub struct ControlChannel {
+    reload: HANDLE,
+    detach: HANDLE,
+    stopped: HANDLE,
+}
+
+impl ControlChannel {
+    pub fn new() -> anyhow::Result<Self> {
+        let pid = unsafe { GetCurrentProcessId() };
+        let create = |event: ControlEvent, manual_reset: bool| unsafe {
+            CreateEventW(None, manual_reset, false, &HSTRING::from(event.name(pid)))
+        };
+
+        let channel = Self {
+            reload: create(ControlEvent::Reload, false)?,
+            detach: create(ControlEvent::Detach, false)?,
+            stopped: create(ControlEvent::Stopped, true)?,
+        };
+        // The events outlive us if an injector still has them open, which would otherwise leave `stopped` set after
+        // re-injecting the DLL.
+        unsafe { ResetEvent(channel.stopped)? };
+
+        Ok(channel)
+    }
+
+    /// Whether a config reload was requested since the last call.
+    pub fn reload_requested(&self) -> bool {
+        is_signaled(self.reload)
+    }
+
+    /// Whether the freecam should stop, checked once per update.
+    pub fn detach_requested(&self) -> bool {
+        is_signaled(self.detach)
+    }
+}
+
+impl Drop for ControlChannel {
+    fn drop(&mut self) {
+        crate::error_report::wait_for_message_box();
+
+        unsafe {
+            let _ = SetEvent(self.stopped);
+            let _ = CloseHandle(self.reload);
+            let _ = CloseHandle(self.detach);
+            let _ = CloseHandle(self.stopped);
+        }
+    }
+}
+
+/// Check (and for auto-reset events, consume) the event without blocking.
+fn is_signaled(event: HANDLE) -> bool {
+    unsafe { WaitForSingleObject(event, 0) == WAIT_OBJECT_0 }
+}
@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::FreecamConfig;
+
+/// How many of the most recent log lines are included in a crash report.
+const MAX_LOG_LINES: usize = 50;
+/// How many of the most recent state machine transitions are included in a crash report.
+const MAX_TRANSITIONS: usize = 32;
+
+static RECENT_LOG_LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static RECENT_TRANSITIONS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static CONFIG_SUMMARY: Mutex<Option<serde_json::Value>> = Mutex::new(None);
+
+/// Install a panic hook which writes a crash report to the given `directory` whenever one of our threads panics.
+///
+/// Without this a panic in any of our threads silently stops (part of) the freecam, leaving users with nothing but
+/// "the mod just stopped working". The previous hook still runs afterwards.
+pub fn install(directory: impl Into<PathBuf>) {
+    let directory = directory.into();
+    let previous = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "<unknown>".to_string());
+        let location = info.location().map(|l| l.to_string());
+
+        match write_report(&directory, &message, location.as_deref()) {
+            Ok(path) => log::error!(
+                "Panicked at {:?}: {}, wrote crash report to: {:?}",
+                location,
+                message,
+                path
+            ),
+            Err(e) => log::error!(
+                "Panicked at {:?}: {}, failed to write crash report: {}",
+                location,
+                message,
+                e
+            ),
+        }
+
+        previous(info);
+    }));
+}
+
+/// Remember a state machine transition for inclusion in crash reports, called by every `TransitionLog`.
+pub fn record_transition(name: &str, from: &impl Debug, to: &impl Debug) {
+    if let Ok(mut transitions) = RECENT_TRANSITIONS.lock() {
+        if transitions.len() == MAX_TRANSITIONS {
+            transitions.pop_front();
+        }
+        transitions.push_back(format!("{}: {:?} -> {:?}", name, from, to));
+    }
+}
+
+/// Remember the most relevant settings of the (re)loaded config for inclusion in crash reports.
+pub fn set_config_summary(conf: &FreecamConfig) {
+    let summary = serde_json::json!({
+        "version": conf.version,
+        "update_rate": conf.update_rate,
+        "vsync_to_game": conf.vsync_to_game,
+        "custom_camera_enabled": conf.camera.custom_camera_enabled,
+        "safe_mode": conf.safe_mode,
+        "force_ttw_camera": conf.force_ttw_camera,
+        "patch_profile": conf.patch_profile.name,
+        "remote_captures": format!("{:?}", conf.remote_captures),
+        "director": conf.director.is_some(),
+    });
+
+    if let Ok(mut current) = CONFIG_SUMMARY.lock() {
+        *current = Some(summary);
+    }
+}
+
+fn write_report(directory: &Path, message: &str, location: Option<&str>) -> anyhow::Result<PathBuf> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let path = directory.join(format!("freecam_crash_{}.json", timestamp));
+    let thread = std::thread::current();
+
+    // The panic could have happened while one of these was locked, never block on them.
+    let recent = |lines: &Mutex<VecDeque<String>>| lines.try_lock().map(|l| l.iter().cloned().collect::<Vec<_>>()).ok();
+
+    let report = serde_json::json!({
+        "timestamp": timestamp,
+        "version": env!("CARGO_PKG_VERSION"),
+        "thread": thread.name().map(str::to_string).unwrap_or_else(|| format!("{:?}", thread.id())),
+        "message": message,
+        "location": location,
+        "backtrace": std::backtrace::Backtrace::force_capture().to_string(),
+        "transitions": recent(&RECENT_TRANSITIONS),
+        "config": CONFIG_SUMMARY.try_lock().ok().and_then(|c| c.clone()),
+        "log": recent(&RECENT_LOG_LINES),
+    });
+
+    let mut file = std::fs::File::create(&path)?;
+    serde_json::to_writer_pretty(&mut file, &report)?;
+
+    Ok(path)
+}
+
+/// A log sink which keeps the last [MAX_LOG_LINES] log lines around for crash reports.
+#[derive(Default)]
+pub struct RecentLogWriter {
+    partial: String,
+}
+
+impl Write for RecentLogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.partial.push_str(&String::from_utf8_lossy(buf));
+
+        while let Some(end) = self.partial.find('\n') {
+            let line = self.partial.drain(..=end).collect::<String>();
+
+            if let Ok(mut lines) = RECENT_LOG_LINES.lock() {
+                if lines.len() == MAX_LOG_LINES {
+                    lines.pop_front();
+                }
+                lines.push_back(line.trim_end().to_string());
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::battle_cam::BattleCamera;
+use crate::config::FreecamConfig;
+
+/// Write the full current state (camera, patches, config) to a timestamped JSON file in `directory`.
+///
+/// Returns the path of the written file.
+pub fn write_state_dump(
+    directory: impl AsRef<Path>,
+    conf: &FreecamConfig,
+    battle_cam: &BattleCamera,
+) -> anyhow::Result<PathBuf> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let path = directory.as_ref().join(format!("freecam_dump_{}.json", timestamp));
+
+    let dump = serde_json::json!({
+        "timestamp": timestamp,
+        "version": env!("CARGO_PKG_VERSION"),
+        "camera": battle_cam.dump_state(),
+        "config": conf,
+    });
+
+    let mut file = std::fs::File::create(&path)?;
+    serde_json::to_writer_pretty(&mut file, &dump)?;
+
+    Ok(path)
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_hooking_utils::patching::process::Window;
+use windows::Win32::Foundation::{HANDLE, HMODULE, LPARAM, LRESULT, WPARAM};
+use windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY;
+use windows::Win32::UI::Input::{
+    GetRawInputData, GetRawInputDeviceInfoW, RegisterRawInputDevices, HRAWINPUT, RAWINPUT, RAWINPUTDEVICE,
+    RAWINPUTHEADER, RIDEV_INPUTSINK, RIDI_DEVICENAME, RID_INPUT, RIM_TYPEKEYBOARD,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    CallNextHookEx, CreateWindowExW, DestroyWindow, DispatchMessageW, GetWindowThreadProcessId, PeekMessageW,
+    SetWindowsHookExW, UnhookWindowsHookEx, HC_ACTION, HWND_MESSAGE, MSG, PM_REMOVE, RI_KEY_BREAK, WH_KEYBOARD,
+    WINDOW_EX_STYLE, WINDOW_STYLE, WM_INPUT,
+};
+
+use crate::config::DirectorConfig;
+
+/// Tracks the key state of a single, secondary, keyboard (the 'director' device) through Raw Input.
+///
+/// This allows a second keyboard or macro pad to act as a dedicated camera control surface, whilst the primary keyboard
+/// continues to be used for normal gameplay.
+pub struct DirectorInput {
+    keys: Arc<DirectorKeys>,
+    shutdown: std::sync::mpsc::SyncSender<()>,
+    /// Joined on drop, as the thread runs our code and window procedure until it has seen the shutdown.
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl DirectorInput {
+    /// Start listening for Raw Input events of the device described by `config`.
+    ///
+    /// If [DirectorConfig::swallow_keys] is set a keyboard hook is installed on the game's window thread to prevent the
+    /// game from seeing any key presses from the director device.
+    pub fn new(config: &DirectorConfig, main_window: Window, module_handle: HMODULE) -> anyhow::Result<Self> {
+        let (send_shutdown, recv_shutdown) = std::sync::mpsc::sync_channel(1);
+        // Shared with the keyboard hook, which can only access global state. Re-used across config reloads.
+        let keys = SWALLOW_STATE
+            .get_or_init(|| Box::new(Arc::new(DirectorKeys::default())))
+            .clone();
+
+        let device_name = config.device_name.to_lowercase();
+        let swallow_keys = config.swallow_keys;
+        let thread_keys = keys.clone();
+
+        let thread = std::thread::spawn(move || unsafe {
+            // A message-only window is enough to receive `WM_INPUT` messages, no need for a custom window class.
+            let window = CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                windows::core::w!("STATIC"),
+                windows::core::w!("FreecamDirector"),
+                WINDOW_STYLE::default(),
+                0,
+                0,
+                0,
+                0,
+                HWND_MESSAGE,
+                None,
+                module_handle,
+                None,
+            );
+
+            if window.0 == 0 {
+                crate::error_report::report("Failed to create director input window");
+                return;
+            }
+
+            // Generic desktop page (0x01), keyboard usage (0x06)
+            let device = RAWINPUTDEVICE {
+                usUsagePage: 0x01,
+                usUsage: 0x06,
+                dwFlags: RIDEV_INPUTSINK,
+                hwndTarget: window,
+            };
+
+            if let Err(e) = RegisterRawInputDevices(&[device], std::mem::size_of::<RAWINPUTDEVICE>() as u32) {
+                crate::error_report::report(format!("Failed to register director raw input device: {}", e));
+                let _ = DestroyWindow(window);
+                return;
+            }
+
+            // Only hook the game's window thread, we don't care about any other application.
+            let hook = if swallow_keys {
+                let thread_id = GetWindowThreadProcessId(main_window.0, None);
+                SetWindowsHookExW(WH_KEYBOARD, Some(keyboard), module_handle, thread_id)
+                    .map_err(|e| crate::error_report::report(format!("Failed to set director keyboard hook: {}", e)))
+                    .ok()
+                    .inspect(|&hook| crate::shutdown::register_hook(hook))
+            } else {
+                None
+            };
+
+            let mut is_director_device = HashMap::new();
+            let mut message = MSG::default();
+
+            loop {
+                while PeekMessageW(&mut message, window, 0, 0, PM_REMOVE).as_bool() {
+                    if message.message == WM_INPUT {
+                        handle_raw_input(
+                            HRAWINPUT(message.lParam.0),
+                            &device_name,
+                            &mut is_director_device,
+                            &thread_keys,
+                            swallow_keys,
+                        );
+                    }
+                    // Required for the system to clean up the raw input buffer.
+                    DispatchMessageW(&message);
+                }
+
+                if recv_shutdown.try_recv().is_ok() {
+                    break;
+                }
+
+                std::thread::sleep(Duration::from_millis(1));
+            }
+
+            if let Some(hook) = hook.filter(|&hook| crate::shutdown::unregister_hook(hook)) {
+                let _ = UnhookWindowsHookEx(hook);
+            }
+            let _ = DestroyWindow(window);
+        });
+
+        Ok(Self {
+            keys,
+            shutdown: send_shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    /// Whether the given key is currently held down on the director device.
+    pub fn is_down(&self, key: VIRTUAL_KEY) -> bool {
+        self.keys.down[key.0 as usize & 0xFF].load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for DirectorInput {
+    fn drop(&mut self) {
+        let _ = self.shutdown.send(());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        // Any key which is still held would otherwise be swallowed on its next press.
+        for (down, pending) in self.keys.down.iter().zip(&self.keys.pending_swallow) {
+            down.store(false, Ordering::Relaxed);
+            pending.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+static SWALLOW_STATE: once_cell::race::OnceBox<Arc<DirectorKeys>> = once_cell::race::OnceBox::new();
+
+struct DirectorKeys {
+    down: [AtomicBool; 256],
+    /// The amount of key messages for a given virtual key which should still be hidden from the game.
+    pending_swallow: [AtomicU32; 256],
+}
+
+impl Default for DirectorKeys {
+    fn default() -> Self {
+        Self {
+            down: std::array::from_fn(|_| AtomicBool::new(false)),
+            pending_swallow: std::array::from_fn(|_| AtomicU32::new(0)),
+        }
+    }
+}
+
+unsafe fn handle_raw_input(
+    handle: HRAWINPUT,
+    device_name: &str,
+    is_director_device: &mut HashMap<isize, bool>,
+    keys: &DirectorKeys,
+    swallow_keys: bool,
+) {
+    let mut raw = RAWINPUT::default();
+    let mut size = std::mem::size_of::<RAWINPUT>() as u32;
+    let read = GetRawInputData(
+        handle,
+        RID_INPUT,
+        Some(&mut raw as *mut RAWINPUT as *mut _),
+        &mut size,
+        std::mem::size_of::<RAWINPUTHEADER>() as u32,
+    );
+
+    if read == u32::MAX || raw.header.dwType != RIM_TYPEKEYBOARD.0 {
+        return;
+    }
+
+    let is_director = *is_director_device.entry(raw.header.hDevice.0).or_insert_with(|| {
+        let name = get_device_name(raw.header.hDevice).unwrap_or_default();
+        log::debug!("Found keyboard device: {:?}", name);
+        !device_name.is_empty() && name.to_lowercase().contains(device_name)
+    });
+
+    if !is_director {
+        return;
+    }
+
+    let keyboard = raw.data.keyboard;
+    let vk = keyboard.VKey as usize & 0xFF;
+    let is_down = keyboard.Flags as u32 & RI_KEY_BREAK == 0;
+
+    // Held keys repeat, only an actual change is worth an early update.
+    if keys.down[vk].swap(is_down, Ordering::Relaxed) != is_down {
+        crate::scheduler::notify_input();
+    }
+
+    if swallow_keys {
+        keys.pending_swallow[vk].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+unsafe fn get_device_name(device: HANDLE) -> Option<String> {
+    let mut len = 0u32;
+    GetRawInputDeviceInfoW(device, RIDI_DEVICENAME, None, &mut len);
+
+    if len == 0 {
+        return None;
+    }
+
+    let mut name = vec![0u16; len as usize];
+    let read = GetRawInputDeviceInfoW(device, RIDI_DEVICENAME, Some(name.as_mut_ptr() as *mut _), &mut len);
+
+    if read == u32::MAX {
+        return None;
+    }
+
+    Some(
+        String::from_utf16_lossy(&name[..read as usize])
+            .trim_end_matches('\0')
+            .to_string(),
+    )
+}
+
+/// Hides key messages that originated from the director device from the game.
+///
+/// Raw Input is delivered to our thread, while this hook runs on the game's thread, so there is an inherent race here.
+/// In the (rare) case where the hook runs before our raw input was processed the key will simply leak through to the game.
+unsafe extern "system" fn keyboard(n_code: i32, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
+    if n_code >= 0 {
+        if let Some(keys) = SWALLOW_STATE.get() {
+            let pending = &keys.pending_swallow[w_param.0 & 0xFF];
+
+            if pending.load(Ordering::Relaxed) > 0 {
+                // `HC_NOREMOVE` means the message is only peeked, it'll come by again.
+                if n_code == HC_ACTION as i32 {
+                    let _ = pending.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |p| p.checked_sub(1));
+                }
+
+                return LRESULT(1);
+            }
+        }
+    }
+
+    CallNextHookEx(None, n_code, w_param, l_param)
+}
@@ -0,0 +1,123 @@
+//! Collects non-fatal errors (e.g., a failed patch or hook) so they can be shown to the user without spamming them, see
+//! [ErrorDisplay].
+
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use windows::core::HSTRING;
+use windows::Win32::Foundation::HWND;
+use windows::Win32::UI::WindowsAndMessaging::{MessageBoxExW, MB_ICONWARNING, MB_OK, MB_TOPMOST};
+
+/// How non-fatal errors are shown to the user, in addition to always being logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum ErrorDisplay {
+    /// Only log the errors.
+    Log,
+    /// Open the console (if it isn't already), which shows the errors as well as any later log messages.
+    Console,
+    /// Show all errors reported since the last message box in a single message box.
+    #[default]
+    MessageBox,
+}
+
+struct ErrorQueue {
+    /// Every error reported so far, so the same error is only ever shown once.
+    reported: BTreeSet<String>,
+    pending: Vec<String>,
+}
+
+static QUEUE: Mutex<ErrorQueue> = Mutex::new(ErrorQueue {
+    reported: BTreeSet::new(),
+    pending: Vec::new(),
+});
+/// Set while a message box is open, errors reported in the meantime are shown in the next one.
+static MESSAGE_BOX_OPEN: AtomicBool = AtomicBool::new(false);
+
+/// Report a non-fatal error, which is logged immediately and shown to the user during the next [show_pending].
+///
+/// Errors which have been reported before are only logged.
+pub fn report(message: impl Into<String>) {
+    let message = message.into();
+    log::error!("{}", message);
+
+    let mut queue = QUEUE.lock().unwrap_or_else(PoisonError::into_inner);
+    if queue.reported.insert(message.clone()) {
+        queue.pending.push(message);
+    }
+}
+
+/// Show all errors reported since the last call according to the given `display`.
+///
+/// Never opens a second message box while the previous one is still open, the errors are kept until it's closed instead.
+pub fn show_pending(display: ErrorDisplay) {
+    if MESSAGE_BOX_OPEN.load(Ordering::Acquire) {
+        return;
+    }
+
+    let pending = std::mem::take(&mut QUEUE.lock().unwrap_or_else(PoisonError::into_inner).pending);
+    if pending.is_empty() {
+        return;
+    }
+
+    let list = pending
+        .iter()
+        .map(|e| format!("* {}", e))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    match display {
+        ErrorDisplay::Log => {}
+        ErrorDisplay::Console => {
+            // Fails if the console is already open, which is fine.
+            let _ = unsafe { windows::Win32::System::Console::AllocConsole() };
+            // The errors were logged before the console existed, and the log keeps them apart from the status line.
+            log::error!("FreeCam ran into problems:\n{}", list);
+        }
+        ErrorDisplay::MessageBox => {
+            let message = format!("{}\n\nFreeCam will keep running, see the log for details.", list);
+            MESSAGE_BOX_OPEN.store(true, Ordering::Release);
+
+            // Message boxes block until closed, which would halt the camera.
+            std::thread::spawn(move || {
+                unsafe {
+                    let _ = MessageBoxExW(
+                        None,
+                        &HSTRING::from(message),
+                        windows::core::w!("FreeCam ran into problems"),
+                        MB_OK | MB_ICONWARNING | MB_TOPMOST,
+                        0,
+                    );
+                }
+                MESSAGE_BOX_OPEN.store(false, Ordering::Release);
+            });
+        }
+    }
+}
+
+/// Block until the message box opened by [show_pending], if any, is closed.
+///
+/// The message box's thread runs our code, so the DLL can't be unloaded before then.
+pub fn wait_for_message_box() {
+    if !MESSAGE_BOX_OPEN.load(Ordering::Acquire) {
+        return;
+    }
+
+    log::info!("Waiting for the error message box to be closed");
+    while MESSAGE_BOX_OPEN.load(Ordering::Acquire) {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+}
+
+/// Show an error the freecam can't recover from, blocking until the user closes the message box.
+pub fn show_fatal(title: &str, message: &str, parent_window: Option<HWND>) {
+    unsafe {
+        let _ = MessageBoxExW(
+            parent_window.unwrap_or_default(),
+            &HSTRING::from(message),
+            &HSTRING::from(title),
+            MB_OK,
+            0,
+        );
+    }
+}
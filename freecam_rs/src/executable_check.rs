@@ -0,0 +1,125 @@
+//! Verifies the freecam was injected into a supported game before anything is patched, see [ExecutableCheck].
+//!
+//! Injecting into a different Total War title (or a different build of Medieval 2) would otherwise crash the game on
+//! the first patch, without any indication as to why.
+
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::Context;
+
+/// Which executables the freecam is allowed to patch.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ExecutableCheck {
+    /// The accepted file names of the executable (case-insensitive), any name is accepted if empty.
+    pub names: Vec<String>,
+    /// If set, the FNV-1a hash the executable has to match, which pins the exact build.
+    pub hash: Option<u64>,
+    /// If set, the build timestamp in the executable's PE header has to match.
+    ///
+    /// Cheaper than the `hash`, and unaffected by tools which modify the executable (e.g., the large address aware
+    /// patch).
+    pub build_timestamp: Option<u32>,
+}
+
+impl Default for ExecutableCheck {
+    fn default() -> Self {
+        Self {
+            names: vec!["medieval2.exe".to_string(), "kingdoms.exe".to_string()],
+            hash: None,
+            build_timestamp: None,
+        }
+    }
+}
+
+impl ExecutableCheck {
+    /// Check the executable at `exe_path`, returning a description of every mismatch.
+    pub fn verify(&self, exe_path: &Path) -> Result<(), Vec<String>> {
+        let mut mismatches = Vec::new();
+        let name = exe_path.file_name().unwrap_or_default().to_string_lossy();
+        let timestamp = read_build_timestamp(exe_path);
+
+        match &timestamp {
+            Ok(timestamp) => log::info!("Game executable: {:?}, built at {}", exe_path, timestamp),
+            Err(e) => log::warn!(
+                "Game executable: {:?}, failed to read its build timestamp: {}",
+                exe_path,
+                e
+            ),
+        }
+
+        if !self.names.is_empty() && !self.names.iter().any(|allowed| allowed.eq_ignore_ascii_case(&name)) {
+            mismatches.push(format!("The executable `{}` isn't one of {:?}", name, self.names));
+        }
+
+        if let Some(expected) = self.build_timestamp {
+            match timestamp {
+                Ok(timestamp) if timestamp == expected => {}
+                Ok(timestamp) => {
+                    mismatches.push(format!("The build timestamp is {}, expected {}", timestamp, expected))
+                }
+                Err(e) => mismatches.push(format!("Failed to read the build timestamp: {}", e)),
+            }
+        }
+
+        if let Some(expected) = self.hash {
+            match hash_file(exe_path) {
+                Ok(hash) if hash == expected => {}
+                Ok(hash) => mismatches.push(format!("The executable's hash is {}, expected {}", hash, expected)),
+                Err(e) => mismatches.push(format!("Failed to hash the executable: {}", e)),
+            }
+        }
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches)
+        }
+    }
+}
+
+/// Read the `TimeDateStamp` of the PE file header, set by the linker when the executable was built.
+fn read_build_timestamp(exe_path: &Path) -> anyhow::Result<u32> {
+    let mut header = vec![0u8; 4096];
+    let read = std::fs::File::open(exe_path)?.read(&mut header)?;
+    header.truncate(read);
+
+    let dword = |offset: usize| {
+        let bytes = header.get(offset..offset.checked_add(4)?)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    };
+    let pe_offset = dword(0x3C).context("Not a PE file")? as usize;
+    if dword(pe_offset) != Some(u32::from_le_bytes(*b"PE\0\0")) {
+        anyhow::bail!("Missing PE signature");
+    }
+
+    // Skip the signature, `Machine`, and `NumberOfSections`.
+    dword(pe_offset.saturating_add(8)).context("Truncated PE header")
+}
+
+/// Calculate the 64-bit FNV-1a hash of the given file.
+///
+/// Unlike [std::hash::DefaultHasher] this is stable across Rust versions, which matters as it's stored in the config.
+fn hash_file(path: impl AsRef<Path>) -> std::io::Result<u64> {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    let mut file = std::fs::File::open(path)?;
+    let mut buffer = [0u8; 64 * 1024];
+    let mut hash = OFFSET_BASIS;
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+
+        for byte in &buffer[..read] {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+
+    Ok(hash)
+}
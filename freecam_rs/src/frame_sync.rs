@@ -0,0 +1,106 @@
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use retour::static_detour;
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::{CloseHandle, HANDLE};
+use windows::Win32::System::Threading::{CreateEventW, SetEvent, WaitForSingleObject};
+
+/// The signature of `IDirect3DDevice9::Present`, the first argument being the device itself.
+pub type PresentFn = unsafe extern "stdcall" fn(*mut c_void, *const c_void, *const c_void, isize, *const c_void) -> i32;
+
+static_detour! {
+    static PresentHook: unsafe extern "stdcall" fn(*mut c_void, *const c_void, *const c_void, isize, *const c_void) -> i32;
+}
+
+/// The raw `HANDLE` of the event which is signalled whenever the game presents a frame, `0` if there is none.
+static FRAME_EVENT: AtomicIsize = AtomicIsize::new(0);
+/// The address [PresentHook] was initialised with, `0` if it wasn't yet. A detour can only be initialised once.
+static HOOKED_PRESENT_FN: AtomicUsize = AtomicUsize::new(0);
+
+/// Notifies us whenever the game presents a frame, by hooking its [PresentFn].
+///
+/// Lets the camera update exactly once per rendered frame, preventing micro-stutter when the `update_rate` and the
+/// game's frame rate don't match.
+pub struct FrameSync {
+    event: HANDLE,
+    address: usize,
+}
+
+impl FrameSync {
+    /// Hook the present function at the given address.
+    ///
+    /// # Safety
+    /// The `present_fn` must point to a function matching [PresentFn].
+    pub unsafe fn new(present_fn: usize) -> anyhow::Result<Self> {
+        match HOOKED_PRESENT_FN.load(Ordering::SeqCst) {
+            0 => {
+                let target: PresentFn = std::mem::transmute(present_fn);
+                PresentHook.initialize(target, present_detour)?;
+                HOOKED_PRESENT_FN.store(present_fn, Ordering::SeqCst);
+            }
+            hooked if hooked != present_fn => anyhow::bail!(
+                "The present function is already hooked at {:#X}, restart the game to hook {:#X} instead",
+                hooked,
+                present_fn
+            ),
+            _ => {}
+        }
+
+        // Auto-reset, so a single frame only wakes us once.
+        let event = CreateEventW(None, false, false, PCWSTR::null())?;
+        FRAME_EVENT.store(event.0, Ordering::SeqCst);
+
+        if !PresentHook.is_enabled() {
+            PresentHook.enable()?;
+        }
+
+        log::info!("Hooked present function at {:#X}, updating once per frame", present_fn);
+
+        Ok(Self {
+            event,
+            address: present_fn,
+        })
+    }
+
+    /// The address of the hooked present function.
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    /// Block until the game presents its next frame, or the `timeout` passes.
+    pub fn wait(&self, timeout: Duration) {
+        unsafe {
+            WaitForSingleObject(self.event, timeout.as_millis() as u32);
+        }
+    }
+}
+
+impl Drop for FrameSync {
+    fn drop(&mut self) {
+        FRAME_EVENT.store(0, Ordering::SeqCst);
+        unsafe {
+            let _ = PresentHook.disable();
+            let _ = CloseHandle(self.event);
+        }
+    }
+}
+
+fn present_detour(
+    device: *mut c_void,
+    source: *const c_void,
+    dest: *const c_void,
+    window: isize,
+    dirty_region: *const c_void,
+) -> i32 {
+    let event = FRAME_EVENT.load(Ordering::SeqCst);
+
+    unsafe {
+        if event != 0 {
+            let _ = SetEvent(HANDLE(event));
+        }
+
+        PresentHook.call(device, source, dest, window, dirty_region)
+    }
+}
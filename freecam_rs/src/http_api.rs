@@ -0,0 +1,290 @@
+//! A small HTTP API on `localhost` for remote control apps (e.g., a phone used as a wireless camera controller).
+//!
+//! All bodies are JSON, errors are returned as `{"error": "..."}`:
+//! * `GET`/`PUT /pose`: the camera pose, `{"pos": {"x": 0.0, "y": 0.0, "z": 0.0}, "pitch": 0.0, "yaw": 0.0}`.
+//! * `GET`/`PUT /path/recording`: whether the game's camera motion is being recorded, `{"recording": true}`.
+//! * `GET`/`PUT /profile`: the active [FreecamConfig::camera_profiles] entry, `{"name": "filming"}`. A `null` name
+//!   switches back to the normal `camera` settings.
+
+use std::io::Read;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use tiny_http::Method;
+
+use crate::battle_cam::coords::WorldPos;
+use crate::battle_cam::BattleCamera;
+use crate::camera_layers::{CameraLayer, CameraLayers};
+use crate::camera_path::VanillaPathRecorder;
+use crate::config::FreecamConfig;
+
+/// How long a request waits for the main loop to handle it.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub struct HttpApi {
+    port: u16,
+    server: Arc<tiny_http::Server>,
+    /// Joined on drop, so no request is still being served once we're unloaded.
+    thread: Option<std::thread::JoinHandle<()>>,
+    requests: mpsc::Receiver<PendingRequest>,
+    active_profile: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+struct Pose {
+    pos: WorldPos,
+    pitch: f32,
+    yaw: f32,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct Recording {
+    recording: bool,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ProfileSelection {
+    name: Option<String>,
+}
+
+#[derive(Debug)]
+enum ApiRequest {
+    GetPose,
+    SetPose(Pose),
+    GetRecording,
+    SetRecording(Recording),
+    GetProfile,
+    SetProfile(ProfileSelection),
+}
+
+struct PendingRequest {
+    request: ApiRequest,
+    reply: mpsc::Sender<ApiResponse>,
+}
+
+type ApiResponse = Result<serde_json::Value, ApiError>;
+
+#[derive(Debug)]
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl ToString) -> Self {
+        Self {
+            status,
+            message: message.to_string(),
+        }
+    }
+
+    fn not_in_battle() -> Self {
+        Self::new(409, "Not in a battle")
+    }
+}
+
+impl HttpApi {
+    /// Start serving the API on `127.0.0.1:port` from a separate thread.
+    ///
+    /// Requests are only answered during [Self::update].
+    pub fn new(port: u16) -> anyhow::Result<Self> {
+        let server = tiny_http::Server::http(("127.0.0.1", port))
+            .map_err(|e| anyhow::anyhow!("Failed to listen on port {}: {}", port, e))?;
+        let server = Arc::new(server);
+        let (sender, requests) = mpsc::channel();
+
+        let thread_server = server.clone();
+        let thread = std::thread::Builder::new()
+            .name("freecam-http-api".to_string())
+            .spawn(move || serve(&thread_server, &sender))?;
+
+        log::info!("Serving the HTTP API on http://127.0.0.1:{}", port);
+
+        Ok(Self {
+            port,
+            server,
+            thread: Some(thread),
+            requests,
+            active_profile: None,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Forget the active camera profile, for when the config was reloaded and the [CameraLayers] were reset.
+    pub fn reset(&mut self) {
+        self.active_profile = None;
+    }
+
+    /// Handle all requests which came in since the last call.
+    pub fn update(
+        &mut self,
+        conf: &mut FreecamConfig,
+        layers: &mut CameraLayers,
+        battle_cam: &mut BattleCamera,
+        path_recorder: &mut VanillaPathRecorder,
+    ) {
+        while let Ok(pending) = self.requests.try_recv() {
+            log::debug!("HTTP API request: {:?}", pending.request);
+            let response = self.handle(pending.request, conf, layers, battle_cam, path_recorder);
+            // The requesting thread may have given up waiting already.
+            let _ = pending.reply.send(response);
+        }
+    }
+
+    fn handle(
+        &mut self,
+        request: ApiRequest,
+        conf: &mut FreecamConfig,
+        layers: &mut CameraLayers,
+        battle_cam: &mut BattleCamera,
+        path_recorder: &mut VanillaPathRecorder,
+    ) -> ApiResponse {
+        match request {
+            ApiRequest::GetPose => {
+                let (pos, pitch, yaw) = battle_cam.camera_pose().ok_or_else(ApiError::not_in_battle)?;
+
+                Ok(serde_json::json!(Pose { pos, pitch, yaw }))
+            }
+            ApiRequest::SetPose(pose) => {
+                if ![pose.pos.x, pose.pos.y, pose.pos.z, pose.pitch, pose.yaw]
+                    .iter()
+                    .all(|v| v.is_finite())
+                {
+                    return Err(ApiError::new(400, "All pose values should be finite"));
+                }
+                if !battle_cam.is_enabled() || !conf.camera.custom_camera_enabled {
+                    return Err(ApiError::new(409, "The custom camera is disabled"));
+                }
+                if !battle_cam.teleport_to(pose.pos, pose.pitch, pose.yaw) {
+                    return Err(ApiError::not_in_battle());
+                }
+
+                Ok(serde_json::json!(pose))
+            }
+            ApiRequest::GetRecording => Ok(serde_json::json!(Recording {
+                recording: path_recorder.is_recording()
+            })),
+            ApiRequest::SetRecording(Recording { recording }) => {
+                if recording != path_recorder.is_recording() {
+                    path_recorder.toggle();
+                }
+
+                Ok(serde_json::json!(Recording { recording }))
+            }
+            ApiRequest::GetProfile => Ok(self.profile_summary(conf)),
+            ApiRequest::SetProfile(ProfileSelection { name }) => {
+                self.switch_profile(name, conf, layers, battle_cam)?;
+
+                Ok(self.profile_summary(conf))
+            }
+        }
+    }
+
+    fn switch_profile(
+        &mut self,
+        name: Option<String>,
+        conf: &mut FreecamConfig,
+        layers: &mut CameraLayers,
+        battle_cam: &mut BattleCamera,
+    ) -> Result<(), ApiError> {
+        match name {
+            Some(name) => {
+                let camera = conf
+                    .camera_profiles
+                    .get(&name)
+                    .cloned()
+                    .ok_or_else(|| ApiError::new(404, format!("Unknown camera profile `{}`", name)))?;
+                layers.push(CameraLayer::Profile, camera, conf, battle_cam);
+
+                crate::overlay::notify(format!("Camera profile: {}", name));
+                self.active_profile = Some(name);
+            }
+            None => {
+                layers.pop(CameraLayer::Profile, conf, battle_cam);
+
+                crate::overlay::notify("Camera profile: normal");
+                self.active_profile = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn profile_summary(&self, conf: &FreecamConfig) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.active_profile,
+            "profiles": conf.camera_profiles.keys().collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl Drop for HttpApi {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Forward all incoming requests to the main loop and respond with its answer, until the server is unblocked.
+fn serve(server: &tiny_http::Server, requests: &mpsc::Sender<PendingRequest>) {
+    let content_type = tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("Invalid content type header");
+
+    for mut request in server.incoming_requests() {
+        let response = parse_request(&mut request).and_then(|api_request| {
+            let (reply, response) = mpsc::channel();
+            requests
+                .send(PendingRequest {
+                    request: api_request,
+                    reply,
+                })
+                .map_err(|_| ApiError::new(503, "The freecam has shut down"))?;
+
+            response
+                .recv_timeout(RESPONSE_TIMEOUT)
+                .map_err(|_| ApiError::new(503, "The freecam didn't respond in time"))?
+        });
+
+        let (status, body) = match response {
+            Ok(body) => (200, body),
+            Err(e) => (e.status, serde_json::json!({ "error": e.message })),
+        };
+        let response = tiny_http::Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(content_type.clone());
+
+        if let Err(e) = request.respond(response) {
+            log::debug!("Failed to respond to HTTP API request: {}", e);
+        }
+    }
+
+    log::debug!("HTTP API stopped");
+}
+
+fn parse_request(request: &mut tiny_http::Request) -> Result<ApiRequest, ApiError> {
+    let mut body = String::new();
+    request
+        .as_reader()
+        .read_to_string(&mut body)
+        .map_err(|e| ApiError::new(400, e))?;
+
+    match (request.method(), request.url()) {
+        (Method::Get, "/pose") => Ok(ApiRequest::GetPose),
+        (Method::Put, "/pose") => Ok(ApiRequest::SetPose(parse_body(&body)?)),
+        (Method::Get, "/path/recording") => Ok(ApiRequest::GetRecording),
+        (Method::Put, "/path/recording") => Ok(ApiRequest::SetRecording(parse_body(&body)?)),
+        (Method::Get, "/profile") => Ok(ApiRequest::GetProfile),
+        (Method::Put, "/profile") => Ok(ApiRequest::SetProfile(parse_body(&body)?)),
+        (method, url) => Err(ApiError::new(404, format!("Unknown endpoint: {} {}", method, url))),
+    }
+}
+
+fn parse_body<T: DeserializeOwned>(body: &str) -> Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|e| ApiError::new(400, e))
+}
@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+use rust_hooking_utils::raw_input::key_manager::{KeyState, KeyboardManager};
+use rust_hooking_utils::raw_input::virtual_keys::VirtualKey;
+use serde::de::{Error, IntoDeserializer};
+use windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY;
+
+#[cfg(feature = "director")]
+use crate::director::DirectorInput;
+
+/// Combines the primary [KeyboardManager] with an optional [DirectorInput] device.
+///
+/// Any key bound to a camera action can be pressed on either device.
+pub struct InputManager {
+    keyboard: KeyboardManager,
+    #[cfg(feature = "director")]
+    director: Option<DirectorInput>,
+    /// Snapshot of the director's key state during the current frame.
+    director_current: [bool; 256],
+    director_previous: [bool; 256],
+    /// The frame during which a binding (identified by all of its chords, see [binding_id]) was last pressed, see
+    /// [Self::axis].
+    pressed_at: HashMap<Vec<Vec<u16>>, u64>,
+    frame: u64,
+    /// Set while the game window isn't focused, all keys then read as released, see [Self::set_blocked].
+    blocked: bool,
+    /// The keys held to form a chord with another key somewhere in the config, see [Self::set_chord_modifiers].
+    chord_modifiers: Vec<VIRTUAL_KEY>,
+}
+
+/// How to handle two opposing bindings (e.g., forward and backwards) being held at the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum OpposingKeys {
+    /// Both keys cancel each other out.
+    #[default]
+    Cancel,
+    /// The key which was pressed most recently wins.
+    LastPressed,
+    /// The positive key (forward, left, rotate left) always wins.
+    Priority,
+}
+
+/// How the `freecam_key` controls looking around with the mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum FreecamMode {
+    /// Look around while the key is held down.
+    #[default]
+    Hold,
+    /// Pressing the key starts looking around, pressing it again stops.
+    Toggle,
+}
+
+impl InputManager {
+    pub fn new() -> Self {
+        Self {
+            keyboard: KeyboardManager::new(),
+            #[cfg(feature = "director")]
+            director: None,
+            director_current: [false; 256],
+            director_previous: [false; 256],
+            pressed_at: HashMap::new(),
+            frame: 0,
+            blocked: false,
+            chord_modifiers: Vec::new(),
+        }
+    }
+
+    /// Ignore all input while `blocked`, as the keys are meant for another window when the game isn't focused.
+    pub fn set_blocked(&mut self, blocked: bool) {
+        self.blocked = blocked;
+    }
+
+    /// Whether all input is currently ignored, see [Self::set_blocked].
+    pub fn is_blocked(&self) -> bool {
+        self.blocked
+    }
+
+    /// Replace the current director device (if any).
+    #[cfg(feature = "director")]
+    pub fn set_director(&mut self, director: Option<DirectorInput>) {
+        self.director = director;
+        self.director_current = [false; 256];
+        self.director_previous = [false; 256];
+    }
+
+    /// Set the modifier keys of all chords in the config, see [crate::keybind_conflicts::chord_modifiers].
+    ///
+    /// A chord is only held down while none of the other modifiers are, so e.g. `VK_W` doesn't also move the camera
+    /// while `VK_CONTROL+VK_W` is pressed.
+    pub fn set_chord_modifiers(&mut self, modifiers: Vec<VIRTUAL_KEY>) {
+        self.chord_modifiers = modifiers;
+    }
+
+    /// Whether the given key is currently held down on any device.
+    pub fn has_pressed(&mut self, key: VIRTUAL_KEY) -> bool {
+        let pressed = self.keyboard.has_pressed(key) || self.director_current[key.0 as usize & 0xFF];
+
+        pressed && !self.blocked
+    }
+
+    /// Whether all given keys are currently held down.
+    pub fn all_pressed(&mut self, keys: impl IntoIterator<Item = VIRTUAL_KEY>) -> bool {
+        keys.into_iter().all(|key| self.has_pressed(key))
+    }
+
+    /// Whether all given keys are currently held down, with at least one of them having been pressed this frame.
+    ///
+    /// Unlike [Self::all_pressed] this only triggers once per press of the key combination.
+    pub fn combo_pressed(&mut self, keys: impl IntoIterator<Item = VIRTUAL_KEY>) -> bool {
+        let mut any_pressed = false;
+
+        for key in keys {
+            match self.get_key_state(key) {
+                KeyState::Pressed => any_pressed = true,
+                KeyState::Down => {}
+                KeyState::Released | KeyState::Up => return false,
+            }
+        }
+
+        any_pressed
+    }
+
+    /// Whether any of the alternative chords of the given binding is currently held down, without any modifier keys
+    /// which aren't part of it, see [Self::set_chord_modifiers].
+    pub fn binding_down(&mut self, binding: &KeyBinding) -> bool {
+        binding.chords().iter().any(|chord| self.chord_down(chord))
+    }
+
+    fn chord_down(&mut self, chord: &KeyChord) -> bool {
+        let keys = chord.0.iter().map(VirtualKey::to_virtual_key).collect::<Vec<_>>();
+
+        // Taken for the duration, as checking a key borrows all of `self`.
+        let modifiers = std::mem::take(&mut self.chord_modifiers);
+        let extra_modifier = modifiers
+            .iter()
+            .any(|&modifier| !keys.contains(&modifier) && self.has_pressed(modifier));
+        self.chord_modifiers = modifiers;
+
+        !extra_modifier && keys.into_iter().all(|key| self.has_pressed(key))
+    }
+
+    /// Return the [KeyState] of the given binding, treating each chord as if it were a single key.
+    pub fn binding_state(&mut self, binding: &KeyBinding) -> KeyState {
+        let mut was_down = false;
+        let mut is_down = false;
+
+        for chord in binding.chords() {
+            let mut chord_was_down = true;
+            let mut chord_is_down = true;
+
+            for key in &chord.0 {
+                let (key_was_down, key_is_down) = match self.get_key_state(key.to_virtual_key()) {
+                    KeyState::Pressed => (false, true),
+                    KeyState::Down => (true, true),
+                    KeyState::Released => (true, false),
+                    KeyState::Up => (false, false),
+                };
+                chord_was_down &= key_was_down;
+                chord_is_down &= key_is_down;
+            }
+
+            was_down |= chord_was_down;
+            is_down |= chord_is_down;
+        }
+
+        match (was_down, is_down) {
+            (false, true) => KeyState::Pressed,
+            (true, true) => KeyState::Down,
+            (true, false) => KeyState::Released,
+            (false, false) => KeyState::Up,
+        }
+    }
+
+    /// Resolve two opposing bindings into a single axis value of `-1.0`, `0.0`, or `1.0`.
+    ///
+    /// Should be called every frame for [OpposingKeys::LastPressed] to notice all key presses.
+    pub fn axis(&mut self, negative: &KeyBinding, positive: &KeyBinding, behaviour: OpposingKeys) -> f32 {
+        let negative_down = self.track_binding(negative);
+        let positive_down = self.track_binding(positive);
+
+        match (negative_down, positive_down) {
+            (false, false) => 0.,
+            (true, false) => -1.,
+            (false, true) => 1.,
+            (true, true) => match behaviour {
+                OpposingKeys::Cancel => 0.,
+                OpposingKeys::Priority => 1.,
+                OpposingKeys::LastPressed => {
+                    let pressed_at =
+                        |binding: &KeyBinding| self.pressed_at.get(&binding_id(binding)).copied().unwrap_or_default();
+                    if pressed_at(positive) >= pressed_at(negative) {
+                        1.
+                    } else {
+                        -1.
+                    }
+                }
+            },
+        }
+    }
+
+    /// Like [Self::axis], but for bindings which are optional. A missing binding is never held down.
+    pub fn optional_axis(
+        &mut self,
+        negative: Option<&KeyBinding>,
+        positive: Option<&KeyBinding>,
+        behaviour: OpposingKeys,
+    ) -> f32 {
+        match (negative, positive) {
+            (Some(negative), Some(positive)) => self.axis(negative, positive, behaviour),
+            (Some(negative), None) => -(self.track_binding(negative) as u8 as f32),
+            (None, Some(positive)) => self.track_binding(positive) as u8 as f32,
+            (None, None) => 0.,
+        }
+    }
+
+    /// Return whether the binding is down, and remember when it was pressed.
+    fn track_binding(&mut self, binding: &KeyBinding) -> bool {
+        match self.binding_state(binding) {
+            KeyState::Pressed => {
+                self.pressed_at.insert(binding_id(binding), self.frame);
+                true
+            }
+            KeyState::Down => true,
+            KeyState::Released | KeyState::Up => false,
+        }
+    }
+
+    pub fn get_key_state(&mut self, key: VIRTUAL_KEY) -> KeyState {
+        let idx = key.0 as usize & 0xFF;
+        let (was_down, is_down) = match self.keyboard.get_key_state(key) {
+            KeyState::Pressed => (false, true),
+            KeyState::Down => (true, true),
+            KeyState::Released => (true, false),
+            KeyState::Up => (false, false),
+        };
+
+        // The keyboard's state is still queried while blocked, so it's up to date once we're unblocked.
+        if self.blocked {
+            return KeyState::Up;
+        }
+
+        match (
+            was_down || self.director_previous[idx],
+            is_down || self.director_current[idx],
+        ) {
+            (false, true) => KeyState::Pressed,
+            (true, true) => KeyState::Down,
+            (true, false) => KeyState::Released,
+            (false, false) => KeyState::Up,
+        }
+    }
+
+    pub fn end_frame(&mut self) {
+        self.keyboard.end_frame();
+        self.frame += 1;
+
+        #[cfg(feature = "director")]
+        if let Some(director) = &self.director {
+            self.director_previous = self.director_current;
+            for (vk, down) in self.director_current.iter_mut().enumerate() {
+                *down = director.is_down(VIRTUAL_KEY(vk as u16));
+            }
+        }
+    }
+}
+
+/// A combination of keys which all need to be held down, e.g. `Ctrl+W`.
+///
+/// Serialized as all key names joined by a `+`, e.g. `"VK_CONTROL+VK_W"`.
+#[derive(Clone, PartialEq)]
+pub struct KeyChord(pub Vec<VirtualKey>);
+
+impl Debug for KeyChord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_string_repr())
+    }
+}
+
+impl KeyChord {
+    fn to_string_repr(&self) -> String {
+        self.0
+            .iter()
+            .filter_map(|key| match serde_json::to_value(key) {
+                Ok(serde_json::Value::String(name)) => Some(name),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+}
+
+impl serde::Serialize for KeyChord {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string_repr())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for KeyChord {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = String::deserialize(deserializer)?;
+        let keys = repr
+            .split('+')
+            .map(|name| {
+                VirtualKey::deserialize(name.trim().into_deserializer())
+                    .map_err(|e: serde::de::value::Error| D::Error::custom(format!("Invalid key `{}`: {}", name, e)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(KeyChord(keys))
+    }
+}
+
+/// A binding for a single action, consisting of one or more alternative [KeyChord]s.
+///
+/// Can be written as a single chord (`"VK_W"`, `"VK_CONTROL+VK_W"`) or a list of alternatives (`["VK_W", "VK_UP"]`).
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum KeyBinding {
+    Single(KeyChord),
+    Alternatives(Vec<KeyChord>),
+}
+
+impl KeyBinding {
+    pub fn chords(&self) -> &[KeyChord] {
+        match self {
+            KeyBinding::Single(chord) => std::slice::from_ref(chord),
+            KeyBinding::Alternatives(chords) => chords,
+        }
+    }
+}
+
+/// Key into [InputManager::pressed_at] for the given binding.
+///
+/// Made up of all its chords, as bindings sharing a key (e.g. `VK_W` and `VK_CONTROL+VK_W`) are different actions.
+fn binding_id(binding: &KeyBinding) -> Vec<Vec<u16>> {
+    binding
+        .chords()
+        .iter()
+        .map(|chord| chord.0.iter().map(|key| key.to_virtual_key().0).collect())
+        .collect()
+}
+
+impl From<VirtualKey> for KeyBinding {
+    fn from(value: VirtualKey) -> Self {
+        KeyBinding::Single(KeyChord(vec![value]))
+    }
+}
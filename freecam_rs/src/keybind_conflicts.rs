@@ -0,0 +1,299 @@
+//! Detects keys bound to multiple actions, see [FreecamConfig::keybind_conflicts].
+//!
+//! Two bindings conflict when they share a chord, or when one chord is part of another (e.g., `VK_W` and
+//! `VK_CONTROL+VK_W`), as pressing the larger chord then triggers both actions. The `fast_key` and `slow_key` are
+//! meant to be held together with other keys, so they only conflict when bound to the exact same chord.
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+use rust_hooking_utils::raw_input::virtual_keys::VirtualKey;
+use windows::Win32::UI::Input::KeyboardAndMouse::VIRTUAL_KEY;
+
+use crate::config::FreecamConfig;
+use crate::input::{KeyBinding, KeyChord};
+
+/// What to do with conflicting keybindings when the config is (re)loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum ConflictHandling {
+    /// Log a warning for every conflict.
+    #[default]
+    Warn,
+    /// Refuse to load the config.
+    Error,
+    /// Log a warning, and remove the conflicting chord from the lower priority action.
+    ///
+    /// Priority follows the order of [bound_actions]. Actions which would be left without any key keep theirs.
+    Resolve,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Conflict {
+    /// The index (into [bound_actions]) and name of the higher priority action, and its conflicting chord.
+    first: (usize, Cow<'static, str>, KeyChord),
+    /// The index and name of the lower priority action, and its conflicting chord.
+    second: (usize, Cow<'static, str>, KeyChord),
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let ((_, first, first_chord), (_, second, second_chord)) = (&self.first, &self.second);
+
+        if chord_keys(first_chord) == chord_keys(second_chord) {
+            write!(f, "`{}` and `{}` are both bound to `{:?}`", first, second, first_chord)
+        } else {
+            write!(
+                f,
+                "`{}` (`{:?}`) and `{}` (`{:?}`) overlap, pressing one also triggers the other",
+                first, first_chord, second, second_chord
+            )
+        }
+    }
+}
+
+/// Mutable access to a configured binding, regardless of how it's stored in the config.
+enum BindingMut<'a> {
+    Required(&'a mut KeyBinding),
+    Optional(&'a mut Option<KeyBinding>),
+    Combo(&'a mut Option<Vec<VirtualKey>>),
+}
+
+impl BindingMut<'_> {
+    fn chords(&self) -> Vec<KeyChord> {
+        match self {
+            BindingMut::Required(binding) => binding.chords().to_vec(),
+            BindingMut::Optional(binding) => binding.as_ref().map(|b| b.chords().to_vec()).unwrap_or_default(),
+            BindingMut::Combo(keys) => keys.iter().map(|keys| KeyChord(keys.clone())).collect(),
+        }
+    }
+
+    /// Remove the given chord from this binding, returning `false` if that would leave a required binding empty.
+    fn remove(&mut self, chord: &KeyChord) -> bool {
+        let remaining = self.chords().into_iter().filter(|c| c != chord).collect::<Vec<_>>();
+        let new_binding = match remaining.len() {
+            0 => None,
+            1 => Some(KeyBinding::Single(remaining[0].clone())),
+            _ => Some(KeyBinding::Alternatives(remaining)),
+        };
+
+        match (self, new_binding) {
+            (BindingMut::Required(binding), Some(new_binding)) => **binding = new_binding,
+            (BindingMut::Required(_), None) => return false,
+            (BindingMut::Optional(binding), new_binding) => **binding = new_binding,
+            (BindingMut::Combo(keys), new_binding) => **keys = new_binding.map(|b| b.chords()[0].0.clone()),
+        }
+
+        true
+    }
+}
+
+struct BoundAction<'a> {
+    /// The action's key in the config, e.g. `bookmarks.slots.0` for the first bookmark slot.
+    name: Cow<'static, str>,
+    /// Whether this action is meant to be held together with other keys, see the module docs.
+    modifier: bool,
+    binding: BindingMut<'a>,
+}
+
+/// All actions which can be bound to a key, from highest to lowest priority.
+fn bound_actions(conf: &mut FreecamConfig) -> Vec<BoundAction<'_>> {
+    let action = |name: &'static str, binding| BoundAction {
+        name: name.into(),
+        modifier: false,
+        binding,
+    };
+    let keys = &mut conf.keybinds;
+
+    let mut actions = vec![
+        action("reload_config_keys", BindingMut::Combo(&mut conf.reload_config_keys)),
+        action("reset_keys", BindingMut::Combo(&mut conf.reset_keys)),
+        action("reset_view_keys", BindingMut::Combo(&mut conf.reset_view_keys)),
+        action("toggle_freecam_keys", BindingMut::Combo(&mut conf.toggle_freecam_keys)),
+        action("dump_state_keys", BindingMut::Combo(&mut conf.dump_state_keys)),
+        action(
+            "toggle_force_ttw_camera_keys",
+            BindingMut::Combo(&mut conf.toggle_force_ttw_camera_keys),
+        ),
+        action("freecam_key", BindingMut::Required(&mut keys.freecam_key)),
+        action("drag_pan_key", BindingMut::Optional(&mut keys.drag_pan_key)),
+        action("forward_key", BindingMut::Required(&mut keys.forward_key)),
+        action("backwards_key", BindingMut::Required(&mut keys.backwards_key)),
+        action("left_key", BindingMut::Required(&mut keys.left_key)),
+        action("right_key", BindingMut::Required(&mut keys.right_key)),
+        action("rotate_left", BindingMut::Required(&mut keys.rotate_left)),
+        action("rotate_right", BindingMut::Required(&mut keys.rotate_right)),
+        action("pitch_up", BindingMut::Optional(&mut keys.pitch_up)),
+        action("pitch_down", BindingMut::Optional(&mut keys.pitch_down)),
+        action("up_key", BindingMut::Optional(&mut keys.up_key)),
+        action("down_key", BindingMut::Optional(&mut keys.down_key)),
+        action("dolly_key", BindingMut::Optional(&mut keys.dolly_key)),
+        BoundAction {
+            modifier: true,
+            ..action("fast_key", BindingMut::Required(&mut keys.fast_key))
+        },
+        BoundAction {
+            modifier: true,
+            ..action("slow_key", BindingMut::Required(&mut keys.slow_key))
+        },
+        action("screenshot.key", BindingMut::Optional(&mut conf.screenshot.key)),
+        action(
+            "camera_paths.record_vanilla_key",
+            BindingMut::Optional(&mut conf.camera_paths.record_vanilla_key),
+        ),
+        action(
+            "camera_paths.play_key",
+            BindingMut::Optional(&mut conf.camera_paths.play_key),
+        ),
+        action(
+            "camera_paths.reload_key",
+            BindingMut::Optional(&mut conf.camera_paths.reload_key),
+        ),
+        action(
+            "camera_paths.keyframe_key",
+            BindingMut::Optional(&mut conf.camera_paths.keyframe_key),
+        ),
+        action(
+            "camera_paths.export_key",
+            BindingMut::Optional(&mut conf.camera_paths.export_key),
+        ),
+        action("shake.key", BindingMut::Optional(&mut conf.shake.key)),
+        action("auto_director.key", BindingMut::Optional(&mut conf.auto_director.key)),
+        action("bind_capture.key", BindingMut::Optional(&mut conf.bind_capture.key)),
+    ];
+
+    actions.push(BoundAction {
+        modifier: true,
+        ..action(
+            "bookmarks.save_modifier",
+            BindingMut::Required(&mut conf.bookmarks.save_modifier),
+        )
+    });
+    for (index, slot) in conf.bookmarks.slots.iter_mut().enumerate() {
+        actions.push(BoundAction {
+            name: format!("bookmarks.slots.{}", index).into(),
+            ..action("bookmarks.slots", BindingMut::Required(slot))
+        });
+    }
+
+    actions
+}
+
+/// The keys held to form a chord with another key (e.g. `VK_CONTROL` in `VK_CONTROL+VK_W`) in any of the config's
+/// bindings, see [crate::input::InputManager::set_chord_modifiers].
+///
+/// The keys of actions meant to be held together with other keys (like the `fast_key`) are left out, as holding them
+/// mustn't stop the other actions.
+pub fn chord_modifiers(conf: &mut FreecamConfig) -> Vec<VIRTUAL_KEY> {
+    let actions = bound_actions(conf);
+    let held_with_others = actions
+        .iter()
+        .filter(|action| action.modifier)
+        .flat_map(|action| action.binding.chords())
+        .flat_map(|chord| chord_keys(&chord))
+        .collect::<BTreeSet<_>>();
+
+    actions
+        .iter()
+        .filter(|action| !action.modifier)
+        .flat_map(|action| action.binding.chords())
+        .flat_map(|chord| {
+            let modifiers = chord.0.len().saturating_sub(1);
+            chord.0[..modifiers]
+                .iter()
+                .map(|key| key.to_virtual_key().0)
+                .collect::<Vec<_>>()
+        })
+        .filter(|key| !held_with_others.contains(key))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(VIRTUAL_KEY)
+        .collect()
+}
+
+fn chord_keys(chord: &KeyChord) -> BTreeSet<u16> {
+    chord.0.iter().map(|key| key.to_virtual_key().0).collect()
+}
+
+/// Whether pressing `pressed` also triggers an action bound to `bound`.
+fn triggers(pressed: &BTreeSet<u16>, bound: &BTreeSet<u16>, bound_is_modifier: bool) -> bool {
+    if bound_is_modifier {
+        pressed == bound
+    } else {
+        bound.is_subset(pressed)
+    }
+}
+
+/// Find all conflicts between the configured bindings, with the higher priority action first.
+fn find_conflicts(actions: &[BoundAction<'_>]) -> Vec<Conflict> {
+    let chords = actions.iter().map(|a| a.binding.chords()).collect::<Vec<_>>();
+    let mut conflicts = Vec::new();
+
+    for (i, first) in actions.iter().enumerate() {
+        for (j, second) in actions.iter().enumerate().skip(i + 1) {
+            for first_chord in &chords[i] {
+                for second_chord in &chords[j] {
+                    let (first_keys, second_keys) = (chord_keys(first_chord), chord_keys(second_chord));
+
+                    if triggers(&first_keys, &second_keys, second.modifier)
+                        || triggers(&second_keys, &first_keys, first.modifier)
+                    {
+                        conflicts.push(Conflict {
+                            first: (i, first.name.clone(), first_chord.clone()),
+                            second: (j, second.name.clone(), second_chord.clone()),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    conflicts
+}
+
+/// Check the config's bindings for conflicts, handling them according to [FreecamConfig::keybind_conflicts].
+///
+/// Returns an error listing all conflicts for [ConflictHandling::Error].
+pub fn check(conf: &mut FreecamConfig) -> anyhow::Result<()> {
+    let handling = conf.keybind_conflicts;
+    let mut actions = bound_actions(conf);
+    let conflicts = find_conflicts(&actions);
+
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+
+    if handling == ConflictHandling::Error {
+        let list = conflicts
+            .iter()
+            .map(|c| format!("* {}", c))
+            .collect::<Vec<_>>()
+            .join("\n");
+        anyhow::bail!("Found conflicting keybinds:\n{}", list)
+    }
+
+    for conflict in conflicts {
+        if handling == ConflictHandling::Warn {
+            log::warn!("Conflicting keybinds: {}", conflict);
+            continue;
+        }
+
+        // Either chord could've been removed while resolving an earlier conflict.
+        let is_bound =
+            |(index, _, chord): &(usize, Cow<str>, KeyChord)| actions[*index].binding.chords().contains(chord);
+        if !is_bound(&conflict.first) || !is_bound(&conflict.second) {
+            continue;
+        }
+
+        log::warn!("Conflicting keybinds: {}", conflict);
+        let (index, name, chord) = &conflict.second;
+
+        if actions[*index].binding.remove(chord) {
+            log::warn!("Removed `{:?}` from `{}`", chord, name);
+        } else {
+            log::warn!("Can't remove the only key of `{}`, change it manually", name);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rust_hooking_utils::patching::process::Window;
+use windows::Win32::Foundation::{HMODULE, LPARAM, LRESULT, WPARAM};
+use windows::Win32::UI::Input::KeyboardAndMouse::{VK_DOWN, VK_LEFT, VK_RIGHT, VK_UP};
+use windows::Win32::UI::WindowsAndMessaging::{
+    CallNextHookEx, GetWindowThreadProcessId, SetWindowsHookExW, UnhookWindowsHookEx, HHOOK, WH_KEYBOARD,
+};
+
+/// Whether [keyboard] should hide the arrow keys from the game, see [ArrowKeyFilter::set_blocking].
+static BLOCK_ARROW_KEYS: AtomicBool = AtomicBool::new(false);
+
+/// Hides the arrow keys from the game during battles, so they only move our custom camera instead of the game's as well.
+///
+/// Note that this only affects the game's window messages, our own [crate::input::InputManager] still sees the keys.
+pub struct ArrowKeyFilter {
+    hook: HHOOK,
+}
+
+impl ArrowKeyFilter {
+    /// Install a keyboard hook on the game's window thread, initially not blocking anything.
+    pub fn new(main_window: Window, module_handle: HMODULE) -> anyhow::Result<Self> {
+        let hook = unsafe {
+            let thread_id = GetWindowThreadProcessId(main_window.0, None);
+            SetWindowsHookExW(WH_KEYBOARD, Some(keyboard), module_handle, thread_id)?
+        };
+        crate::shutdown::register_hook(hook);
+
+        Ok(Self { hook })
+    }
+
+    /// Set whether the arrow keys should be hidden from the game while a battle is ongoing.
+    pub fn set_blocking(&self, block: bool) {
+        BLOCK_ARROW_KEYS.store(block, Ordering::Relaxed);
+    }
+}
+
+impl Drop for ArrowKeyFilter {
+    fn drop(&mut self) {
+        BLOCK_ARROW_KEYS.store(false, Ordering::Relaxed);
+        if crate::shutdown::unregister_hook(self.hook) {
+            unsafe {
+                let _ = UnhookWindowsHookEx(self.hook);
+            }
+        }
+    }
+}
+
+/// Runs on the game's window thread, so it should be as cheap as possible.
+unsafe extern "system" fn keyboard(n_code: i32, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
+    if n_code >= 0 && BLOCK_ARROW_KEYS.load(Ordering::Relaxed) {
+        let key = w_param.0 as u16;
+
+        if [VK_LEFT.0, VK_UP.0, VK_RIGHT.0, VK_DOWN.0].contains(&key) && crate::battle_cam::data::is_in_battle() {
+            return LRESULT(1);
+        }
+    }
+
+    CallNextHookEx(None, n_code, w_param, l_param)
+}
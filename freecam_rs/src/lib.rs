@@ -3,33 +3,93 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
-use log::LevelFilter;
-use rust_hooking_utils::patching::process::GameProcess;
+use rust_hooking_utils::patching::process::Window;
 use rust_hooking_utils::patching::LocalPatcher;
-use rust_hooking_utils::raw_input::key_manager::KeyboardManager;
 use rust_hooking_utils::raw_input::virtual_keys::VirtualKey;
 use windows::core::HSTRING;
-use windows::Win32::Foundation::HWND;
-use windows::Win32::UI::WindowsAndMessaging::{MessageBoxExW, MB_OK};
+use windows::Win32::Foundation::{CloseHandle, ERROR_ALREADY_EXISTS, HANDLE, HWND};
+use windows::Win32::System::Threading::{CreateMutexW, GetCurrentProcessId};
 
 use crate::battle_cam::BattleCamera;
+use crate::bind_capture::BindCapture;
+use crate::bookmarks::BookmarkManager;
+#[cfg(feature = "http-api")]
+use crate::camera_layers::CameraLayers;
+use crate::camera_path::{CameraPathLibrary, VanillaPathRecorder};
 use crate::config::FreecamConfig;
+use crate::console_status::ConsoleStatus;
+use crate::control::ControlChannel;
+#[cfg(feature = "director")]
+use crate::director::DirectorInput;
+#[cfg(feature = "http-api")]
+use crate::http_api::HttpApi;
+use crate::input::InputManager;
+use crate::keyboard::ArrowKeyFilter;
 use crate::mouse::MouseManager;
+#[cfg(feature = "overlay")]
+use crate::overlay::Overlay;
+use crate::scheduler::UpdateScheduler;
+#[cfg(feature = "screenshot")]
+use crate::screenshot::ScreenshotTaker;
+#[cfg(feature = "scripting")]
+use crate::scripting::ScriptHost;
+use crate::self_test::SelfTest;
+use crate::shared_memory::TelemetryPublisher;
+use crate::window::{UpdatePolicy, WindowStateTracker};
 
+mod bind_capture;
+mod bookmarks;
+#[cfg(feature = "http-api")]
+mod camera_layers;
+mod camera_path;
 mod config;
+mod console_status;
+pub mod control;
+mod crash_report;
+mod diagnostics;
+#[cfg(feature = "director")]
+mod director;
+mod error_report;
+mod executable_check;
+#[cfg(feature = "vsync-to-game")]
+mod frame_sync;
+#[cfg(feature = "http-api")]
+mod http_api;
+mod input;
+mod keybind_conflicts;
+mod keyboard;
+mod logging;
+mod memory;
 mod mouse;
+mod overlay;
+mod paths;
+#[cfg(feature = "remote")]
+pub mod remote;
+mod scheduler;
+#[cfg(feature = "screenshot")]
+mod screenshot;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod self_test;
+mod shared_memory;
+mod shutdown;
+mod window;
 
 mod battle_cam;
 
 static SHUTDOWN_FLAG: AtomicBool = AtomicBool::new(false);
+/// How often the game's windows are searched while waiting for its main window to (re)appear.
+const WINDOW_SEARCH_INTERVAL: Duration = Duration::from_millis(100);
 
 pub fn dll_attach(hinst_dll: windows::Win32::Foundation::HMODULE) -> Result<()> {
-    let dll_path = rust_hooking_utils::get_current_dll_path(hinst_dll)?;
-    let config_directory = dll_path.parent().context("DLL is in root")?;
-    let cfg = simplelog::ConfigBuilder::new().build();
+    let Some(_claim) = claim_process() else {
+        // Another proxy (e.g., both `version.dll` and `dinput8.dll` were installed) is already running the freecam.
+        return Ok(());
+    };
 
-    // Ignore result in case we have double initialisation of the DLL.
-    simplelog::SimpleLogger::init(LevelFilter::Trace, cfg)?;
+    let dll_path = rust_hooking_utils::get_current_dll_path(hinst_dll)?;
+    let config_dir = paths::find_config_directory(dll_path.parent().context("DLL is in root")?)?;
+    let config_directory = config_dir.path.as_path();
 
     config::create_initial_config(config_directory)?;
 
@@ -37,6 +97,24 @@ pub fn dll_attach(hinst_dll: windows::Win32::Foundation::HMODULE) -> Result<()>
         std::process::exit(1)
     };
 
+    logging::init_logging(config_directory, &conf)?;
+    config_dir.log_search();
+
+    if !check_executable(&conf) {
+        // Running anyway would likely crash the game on the first patch.
+        return Ok(());
+    }
+
+    shutdown::install_crash_handler();
+    crash_report::install(config_directory);
+
+    if check_keybind_conflicts(&mut conf, None).is_err() {
+        std::process::exit(1)
+    }
+
+    crash_report::set_config_summary(&conf);
+    battle_cam::profile::set_active(&conf.patch_profile);
+
     if conf.console {
         unsafe {
             windows::Win32::System::Console::AllocConsole()?;
@@ -45,50 +123,333 @@ pub fn dll_attach(hinst_dll: windows::Win32::Foundation::HMODULE) -> Result<()>
 
     log::info!("Loaded config: {:#?}", conf);
 
-    let main_window = loop {
-        if let Some(wnd) = GameProcess::current_process().get_main_window_blocking(None) {
-            if wnd.title().starts_with('M') {
-                break wnd;
-            }
-        }
+    // Declared before everything else in the main loop, so the `Stopped` event is only set once all of it is dropped.
+    let control = create_control_channel();
+
+    let Some(mut main_window) = wait_for_main_window(&conf.window.title_pattern) else {
+        return Ok(());
     };
 
-    log::info!("Found main window: {:?} ({:?})", main_window.title(), main_window.0);
+    warn_disabled_features(&conf);
 
-    let mut key_manager = KeyboardManager::new();
-    let mut update_duration = Duration::from_secs_f64(1.0 / conf.update_rate as f64);
+    if conf.observer_mode {
+        return run_observer(config_directory, conf, main_window, control);
+    }
+
+    let mut key_manager = InputManager::new();
+    key_manager.set_chord_modifiers(keybind_conflicts::chord_modifiers(&mut conf));
+    #[cfg(feature = "director")]
+    key_manager.set_director(create_director(&conf, main_window, hinst_dll));
+    let mut scheduler = UpdateScheduler::new(&conf)?;
     let mut scroll_tracker = MouseManager::new(main_window, hinst_dll, conf.block_game_middle_mouse_functionality)?;
+    let mut arrow_key_filter = create_arrow_key_filter(&conf, main_window, hinst_dll);
     let mut battle_cam = BattleCamera::new(LocalPatcher::new());
+    let mut window_tracker = WindowStateTracker::new(main_window);
+    #[cfg(feature = "screenshot")]
+    let mut screenshots = ScreenshotTaker::new(main_window, config_directory);
+    let mut path_recorder = VanillaPathRecorder::new(config_directory);
+    let mut path_library = CameraPathLibrary::new(config_directory);
+    let mut bookmarks = BookmarkManager::new(config_directory);
+    let mut bind_capture = BindCapture::new();
+    #[cfg(feature = "http-api")]
+    let mut camera_layers = CameraLayers::new(&conf);
+    let mut self_test = SelfTest::default();
+    #[cfg(feature = "http-api")]
+    let mut http_api = create_http_api(&conf);
+    let mut telemetry = create_telemetry_publisher(&conf);
+    let mut console_status = ConsoleStatus::default();
+    #[cfg(feature = "overlay")]
+    let mut overlay = create_overlay(&conf, main_window, hinst_dll);
+    #[cfg(feature = "scripting")]
+    let mut scripts = create_script_host(&conf, config_directory);
 
     let mut last_update = Instant::now();
 
     while !SHUTDOWN_FLAG.load(Ordering::Acquire) {
-        if let Some(reload) = &conf.reload_config_keys {
-            if key_manager.all_pressed(reload.iter().copied().map(VirtualKey::to_virtual_key)) {
-                conf = reload_config(config_directory, &mut conf, &mut battle_cam, main_window.0)?;
-                update_duration = Duration::from_secs_f64(1.0 / conf.update_rate as f64);
+        if control.as_ref().is_some_and(ControlChannel::detach_requested) {
+            log::info!("Detach requested, stopping the freecam");
+            break;
+        }
+
+        if !window::is_window_alive(main_window) {
+            log::warn!("The game's main window was destroyed, searching for its new window");
+            // Everything hooked into the old window has to let go of it before it can be hooked into the new one.
+            #[cfg(feature = "director")]
+            key_manager.set_director(None);
+            #[cfg(feature = "overlay")]
+            drop(overlay.take());
+            drop(arrow_key_filter.take());
+
+            let Some(new_window) = wait_for_main_window(&conf.window.title_pattern) else {
+                break;
+            };
+            main_window = new_window;
+
+            #[cfg(feature = "director")]
+            key_manager.set_director(create_director(&conf, main_window, hinst_dll));
+            #[cfg(feature = "overlay")]
+            {
+                overlay = create_overlay(&conf, main_window, hinst_dll);
+            }
+            if let Err(e) = scroll_tracker.rebind(main_window, hinst_dll, conf.block_game_middle_mouse_functionality) {
+                error_report::report(format!(
+                    "Failed to hook the game's new window, scrolling and hiding the cursor won't work: {}",
+                    e
+                ));
+            }
+            arrow_key_filter = create_arrow_key_filter(&conf, main_window, hinst_dll);
+            window_tracker = WindowStateTracker::new(main_window);
+            #[cfg(feature = "screenshot")]
+            {
+                screenshots = ScreenshotTaker::new(main_window, config_directory);
+            }
+            battle_cam.resync_camera();
+        }
+
+        // Input is only meant for us while the game is focused, everything else keeps running in the background.
+        let update_policy = window_tracker.update(&conf.window, &mut battle_cam, &mut scroll_tracker);
+        key_manager.set_blocked(update_policy != UpdatePolicy::Foreground);
+
+        let binding_captured = bind_capture.update(&conf.bind_capture, config_directory, &mut key_manager);
+        if bind_capture.is_capturing() {
+            // The keys being captured shouldn't also trigger whatever they're currently bound to.
+            key_manager.set_blocked(true);
+        }
+
+        let reload_requested = binding_captured
+            || control.as_ref().is_some_and(ControlChannel::reload_requested)
+            || conf
+                .reload_config_keys
+                .as_ref()
+                .is_some_and(|reload| key_manager.all_pressed(reload.iter().copied().map(VirtualKey::to_virtual_key)));
+        if reload_requested {
+            #[cfg(feature = "director")]
+            let old_director = conf.director.clone();
+            #[cfg(feature = "overlay")]
+            let old_overlay = conf.overlay.clone();
+            let old_block_arrow_keys = conf.camera.block_game_arrow_keys;
+            conf = reload_config(config_directory, &mut conf, &mut battle_cam, main_window.0)?;
+            warn_disabled_features(&conf);
+            key_manager.set_chord_modifiers(keybind_conflicts::chord_modifiers(&mut conf));
+
+            #[cfg(feature = "director")]
+            if conf.director != old_director {
+                // Ensure the old device listener is shut down before registering the new one.
+                key_manager.set_director(None);
+                key_manager.set_director(create_director(&conf, main_window, hinst_dll));
+            }
+            #[cfg(feature = "overlay")]
+            if conf.overlay != old_overlay {
+                // Ensure the old window is gone before creating the new one.
+                drop(overlay.take());
+                overlay = create_overlay(&conf, main_window, hinst_dll);
+            }
+            if conf.camera.block_game_arrow_keys != old_block_arrow_keys {
+                drop(arrow_key_filter.take());
+                arrow_key_filter = create_arrow_key_filter(&conf, main_window, hinst_dll);
+            }
+            if !binding_captured {
+                // Keep the message about the new binding on screen instead.
+                overlay::notify("Reloaded the config");
+            }
+            scheduler.configure(&conf);
+            #[cfg(feature = "http-api")]
+            camera_layers.reset(&conf);
+
+            #[cfg(feature = "http-api")]
+            if conf.http_api_port != http_api.as_ref().map(HttpApi::port) {
+                // Ensure the old server is shut down before starting the new one.
+                drop(http_api.take());
+                http_api = create_http_api(&conf);
+            } else if let Some(api) = &mut http_api {
+                api.reset();
+            }
+
+            if conf.shared_memory_telemetry != telemetry.is_some() {
+                telemetry = create_telemetry_publisher(&conf);
+            }
+
+            #[cfg(feature = "scripting")]
+            {
+                scripts = create_script_host(&conf, config_directory);
             }
         }
 
+        if let Some(dump) = &conf.dump_state_keys {
+            if key_manager.combo_pressed(dump.iter().copied().map(VirtualKey::to_virtual_key)) {
+                match diagnostics::write_state_dump(config_directory, &conf, &battle_cam) {
+                    Ok(path) => log::info!("Wrote state dump to: {:?}", path),
+                    Err(e) => log::error!("Failed to write state dump: {}", e),
+                }
+            }
+        }
+
+        if let Some(toggle) = &conf.toggle_force_ttw_camera_keys {
+            if key_manager.combo_pressed(toggle.iter().copied().map(VirtualKey::to_virtual_key)) {
+                conf.force_ttw_camera = !conf.force_ttw_camera;
+                overlay::notify(format!("Forcing TotalWar camera: {}", conf.force_ttw_camera));
+            }
+        }
+
+        if let Some(toggle) = &conf.toggle_freecam_keys {
+            if key_manager.combo_pressed(toggle.iter().copied().map(VirtualKey::to_virtual_key)) {
+                battle_cam.set_enabled(!battle_cam.is_enabled());
+            }
+        }
+
+        if let Some(reset) = &conf.reset_keys {
+            if key_manager.combo_pressed(reset.iter().copied().map(VirtualKey::to_virtual_key)) {
+                if path_recorder.is_recording() {
+                    path_recorder.toggle();
+                }
+                path_library.stop();
+                battle_cam.reset(&mut scroll_tracker);
+            }
+        }
+
+        if let Some(reset_view) = &conf.reset_view_keys {
+            if key_manager.combo_pressed(reset_view.iter().copied().map(VirtualKey::to_virtual_key)) {
+                battle_cam.reset_view(&mut scroll_tracker);
+            }
+        }
+
+        if let Some(arrow_key_filter) = &arrow_key_filter {
+            arrow_key_filter.set_blocking(
+                battle_cam.is_enabled() && conf.camera.custom_camera_enabled && conf.camera.block_game_arrow_keys,
+            );
+        }
+
+        #[cfg(feature = "screenshot")]
+        screenshots.update(&conf.screenshot, &mut key_manager);
+        path_recorder.update(&conf.camera_paths, &mut key_manager, &mut battle_cam);
+        path_library.update(&conf.camera_paths, &mut key_manager, &mut battle_cam);
+        bookmarks.update(&conf.bookmarks, &mut key_manager, &mut battle_cam);
+        self_test.update(conf.self_test, &mut battle_cam);
+        #[cfg(feature = "http-api")]
+        if let Some(api) = &mut http_api {
+            api.update(&mut conf, &mut camera_layers, &mut battle_cam, &mut path_recorder);
+        }
+        #[cfg(feature = "scripting")]
+        if let Some(scripts) = &mut scripts {
+            scripts.update(&mut key_manager, &mut battle_cam, last_update.elapsed());
+        }
+
         unsafe {
-            // Only run if we're in the foreground. A bit hacky, but eh...
-            if main_window.is_foreground_window() {
+            if update_policy != UpdatePolicy::Paused {
                 battle_cam.run(&mut conf, &mut scroll_tracker, &mut key_manager, last_update.elapsed())?;
             }
 
             last_update = Instant::now();
         }
 
-        std::thread::sleep(update_duration);
+        if let Some(telemetry) = &mut telemetry {
+            telemetry.publish(&mut battle_cam);
+        }
+        console_status.update(&conf, &mut battle_cam);
+
+        error_report::show_pending(conf.error_display);
+        scheduler.wait();
+        key_manager.end_frame();
+    }
+
+    Ok(())
+}
+
+/// The main loop for the [FreecamConfig::observer_mode], which only reads the game's state and publishes it.
+///
+/// No patches or input hooks are installed, so everything requiring them (e.g., the `director`) is left out.
+fn run_observer(
+    config_directory: &Path,
+    mut conf: FreecamConfig,
+    mut main_window: Window,
+    control: Option<ControlChannel>,
+) -> Result<()> {
+    log::info!("Running in observer mode, the game's memory will only be read");
+    if conf.vsync_to_game {
+        log::warn!("`vsync_to_game` hooks the game, ignoring it in observer mode");
+        conf.vsync_to_game = false;
+    }
+
+    let mut key_manager = InputManager::new();
+    key_manager.set_chord_modifiers(keybind_conflicts::chord_modifiers(&mut conf));
+    let mut scheduler = UpdateScheduler::new(&conf)?;
+    let mut battle_cam = BattleCamera::new_observer(LocalPatcher::new());
+    let mut path_recorder = VanillaPathRecorder::new(config_directory);
+    #[cfg(feature = "http-api")]
+    let mut camera_layers = CameraLayers::new(&conf);
+    #[cfg(feature = "http-api")]
+    let mut http_api = create_http_api(&conf);
+    let mut telemetry = create_telemetry_publisher(&conf);
+
+    while !SHUTDOWN_FLAG.load(Ordering::Acquire) {
+        if control.as_ref().is_some_and(ControlChannel::detach_requested) {
+            log::info!("Detach requested, stopping the observer");
+            break;
+        }
+
+        if !window::is_window_alive(main_window) {
+            log::warn!("The game's main window was destroyed, searching for its new window");
+            let Some(new_window) = wait_for_main_window(&conf.window.title_pattern) else {
+                break;
+            };
+            main_window = new_window;
+        }
+
+        key_manager.set_blocked(!main_window.is_foreground_window());
+
+        if let Some(dump) = &conf.dump_state_keys {
+            if key_manager.combo_pressed(dump.iter().copied().map(VirtualKey::to_virtual_key)) {
+                match diagnostics::write_state_dump(config_directory, &conf, &battle_cam) {
+                    Ok(path) => log::info!("Wrote state dump to: {:?}", path),
+                    Err(e) => log::error!("Failed to write state dump: {}", e),
+                }
+            }
+        }
+
+        battle_cam.observe(&conf);
+        path_recorder.update(&conf.camera_paths, &mut key_manager, &mut battle_cam);
+        #[cfg(feature = "http-api")]
+        if let Some(api) = &mut http_api {
+            api.update(&mut conf, &mut camera_layers, &mut battle_cam, &mut path_recorder);
+        }
+
+        if let Some(telemetry) = &mut telemetry {
+            telemetry.publish(&mut battle_cam);
+        }
+
+        error_report::show_pending(conf.error_display);
+        scheduler.wait();
         key_manager.end_frame();
     }
 
     Ok(())
 }
 
+/// Wait until the game's main window matching the `title_pattern` exists, see [window::find_main_window].
+///
+/// Returns [None] if the freecam is shut down in the meantime.
+fn wait_for_main_window(title_pattern: &str) -> Option<Window> {
+    log::debug!("Waiting for a window matching {:?}", title_pattern);
+
+    while !SHUTDOWN_FLAG.load(Ordering::Acquire) {
+        if let Some(window) = window::find_main_window(title_pattern) {
+            log::info!("Found main window: {:?} ({:?})", window.title(), window.0);
+            return Some(window);
+        }
+
+        std::thread::sleep(WINDOW_SEARCH_INTERVAL);
+    }
+
+    None
+}
+
 pub fn dll_detach(_hinst_dll: windows::Win32::Foundation::HMODULE) -> Result<()> {
     SHUTDOWN_FLAG.store(true, Ordering::SeqCst);
     log::info!("Detached! {:?}", std::thread::current().id());
+    // When the game exits our update thread has already been killed, so its patches/hooks were never cleaned up.
+    unsafe {
+        shutdown::restore_all();
+    }
 
     Ok(())
 }
@@ -100,7 +461,8 @@ fn reload_config(
     parent_window: HWND,
 ) -> anyhow::Result<FreecamConfig> {
     log::debug!("Reloading config");
-    let conf = load_validated_config(config_dir.as_ref(), Some(parent_window))?;
+    let mut conf = load_validated_config(config_dir.as_ref(), Some(parent_window))?;
+    check_keybind_conflicts(&mut conf, Some(parent_window))?;
 
     // Open/close console
     if old.console && !conf.console {
@@ -119,24 +481,218 @@ fn reload_config(
         battle_cam.set_custom_camera(true);
     }
 
+    log::set_max_level(conf.log_level);
+    crate::crash_report::set_config_summary(&conf);
+
+    if !battle_cam::profile::set_active(&conf.patch_profile) {
+        log::warn!("The `patch_profile` was changed, restart the game to apply it");
+    }
+
     log::debug!("New config loaded: {:#?}", conf);
 
     Ok(conf)
 }
 
-fn load_validated_config(config_dir: &Path, parent_window: Option<HWND>) -> anyhow::Result<FreecamConfig> {
-    match config::load_config(config_dir) {
-        Ok(conf) => Ok(conf),
-        Err(e) => unsafe {
-            let message = format!("Error: {}\nFreecam will now exit", e);
-            let _ = MessageBoxExW(
-                parent_window.unwrap_or_default(),
-                &HSTRING::from(message),
-                windows::core::w!("Failed to validate FreeCam config"),
-                MB_OK,
-                0,
+/// Ensure only one freecam runs in the game process, returning `None` if another one already does.
+///
+/// Each proxy DLL contains its own copy of the freecam, so this has to be coordinated through a named mutex. The claim
+/// is released once the freecam stops (e.g., after a [control::ControlEvent::Detach]), allowing it to be injected again.
+fn claim_process() -> Option<ProcessClaim> {
+    let name = HSTRING::from(format!("Local\\med2_freecam_{}", unsafe { GetCurrentProcessId() }));
+
+    match unsafe { CreateMutexW(None, false, &name) } {
+        Ok(mutex) if windows::core::Error::from_win32().code() == ERROR_ALREADY_EXISTS.to_hresult() => {
+            unsafe {
+                let _ = CloseHandle(mutex);
+            }
+            None
+        }
+        Ok(mutex) => Some(ProcessClaim(Some(mutex))),
+        // Better to risk running twice than to not run at all.
+        Err(_) => Some(ProcessClaim(None)),
+    }
+}
+
+/// The named mutex held by the freecam running in this process, see [claim_process].
+struct ProcessClaim(Option<HANDLE>);
+
+impl Drop for ProcessClaim {
+    fn drop(&mut self) {
+        if let Some(mutex) = self.0 {
+            unsafe {
+                let _ = CloseHandle(mutex);
+            }
+        }
+    }
+}
+
+/// Create the [ControlChannel], without which the freecam can only be controlled through its keybinds.
+fn create_control_channel() -> Option<ControlChannel> {
+    ControlChannel::new()
+        .map_err(|e| {
+            log::warn!(
+                "Failed to create the control events, the injector won't be able to reach us: {}",
+                e
+            )
+        })
+        .ok()
+}
+
+/// Create the [DirectorInput] if it has been configured.
+///
+/// Failure to do so isn't fatal, the primary keyboard can still be used.
+#[cfg(feature = "director")]
+fn create_director(
+    conf: &FreecamConfig,
+    main_window: Window,
+    hinst_dll: windows::Win32::Foundation::HMODULE,
+) -> Option<DirectorInput> {
+    let director_conf = conf.director.as_ref()?;
+
+    DirectorInput::new(director_conf, main_window, hinst_dll)
+        .map_err(|e| error_report::report(format!("Failed to initialise director input: {}", e)))
+        .ok()
+}
+
+/// Create the on-screen overlay, if enabled.
+///
+/// Failure to do so isn't fatal, messages are still logged.
+#[cfg(feature = "overlay")]
+fn create_overlay(
+    conf: &FreecamConfig,
+    main_window: Window,
+    hinst_dll: windows::Win32::Foundation::HMODULE,
+) -> Option<Overlay> {
+    if !conf.overlay.enabled {
+        return None;
+    }
+
+    Overlay::new(&conf.overlay, main_window, hinst_dll)
+        .map_err(|e| error_report::report(format!("Failed to create the on-screen overlay: {}", e)))
+        .ok()
+}
+
+/// Create the keyboard hook hiding the arrow keys from the game, if enabled.
+///
+/// Failure to do so isn't fatal, the game's camera just moves along with ours.
+fn create_arrow_key_filter(
+    conf: &FreecamConfig,
+    main_window: Window,
+    hinst_dll: windows::Win32::Foundation::HMODULE,
+) -> Option<ArrowKeyFilter> {
+    if !conf.camera.block_game_arrow_keys {
+        return None;
+    }
+
+    ArrowKeyFilter::new(main_window, hinst_dll)
+        .map_err(|e| error_report::report(format!("Failed to hook the arrow keys: {}", e)))
+        .ok()
+}
+
+fn create_telemetry_publisher(conf: &FreecamConfig) -> Option<TelemetryPublisher> {
+    if !conf.shared_memory_telemetry {
+        return None;
+    }
+
+    TelemetryPublisher::new()
+        .map_err(|e| error_report::report(format!("Failed to create the shared memory telemetry section: {}", e)))
+        .ok()
+}
+
+#[cfg(feature = "http-api")]
+fn create_http_api(conf: &FreecamConfig) -> Option<HttpApi> {
+    let port = conf.http_api_port?;
+
+    HttpApi::new(port)
+        .map_err(|e| error_report::report(format!("Failed to start the HTTP API: {}", e)))
+        .ok()
+}
+
+/// Load the user's scripts, if enabled.
+#[cfg(feature = "scripting")]
+fn create_script_host(conf: &FreecamConfig, config_directory: &Path) -> Option<ScriptHost> {
+    conf.scripting
+        .enabled
+        .then(|| ScriptHost::new(&conf.scripting, config_directory))
+}
+
+/// Warn the user about configured functionality which has been left out of this build.
+fn warn_disabled_features(conf: &FreecamConfig) {
+    let features = [
+        ("director", cfg!(feature = "director"), conf.director.is_some()),
+        (
+            "screenshot",
+            cfg!(feature = "screenshot"),
+            conf.screenshot.key.is_some(),
+        ),
+        (
+            "game-thread-teleport",
+            cfg!(feature = "game-thread-teleport"),
+            conf.experimental.game_thread_teleport.is_some(),
+        ),
+        ("vsync-to-game", cfg!(feature = "vsync-to-game"), conf.vsync_to_game),
+        ("http-api", cfg!(feature = "http-api"), conf.http_api_port.is_some()),
+        ("overlay", cfg!(feature = "overlay"), conf.overlay.enabled),
+        ("scripting", cfg!(feature = "scripting"), conf.scripting.enabled),
+    ];
+
+    for (feature, enabled, configured) in features {
+        if configured && !enabled {
+            log::warn!(
+                "The config uses `{}`, but this build doesn't include that feature",
+                feature
+            );
+        }
+    }
+}
+
+/// Verify the game executable according to the [FreecamConfig::executable_check], telling the user if it didn't pass.
+fn check_executable(conf: &FreecamConfig) -> bool {
+    let Some(check) = &conf.executable_check else {
+        return true;
+    };
+    let exe_path = match std::env::current_exe() {
+        Ok(path) => path,
+        Err(e) => {
+            log::warn!(
+                "Failed to find the game executable, skipping the executable check: {}",
+                e
+            );
+            return true;
+        }
+    };
+
+    match check.verify(&exe_path) {
+        Ok(()) => true,
+        Err(mismatches) => {
+            for mismatch in &mismatches {
+                log::error!("Executable check failed: {}", mismatch);
+            }
+            let message = format!(
+                "FreeCam was loaded into an unsupported game ({:?}):\n{}\n\nThe game will run without FreeCam. If this \
+                 is a supported game, adjust or remove the `executable_check` in the config.",
+                exe_path,
+                mismatches.iter().map(|m| format!("* {}", m)).collect::<Vec<_>>().join("\n")
             );
-            Err(e)
-        },
+            error_report::show_fatal("FreeCam doesn't support this game", &message, None);
+
+            false
+        }
     }
 }
+
+fn load_validated_config(config_dir: &Path, parent_window: Option<HWND>) -> anyhow::Result<FreecamConfig> {
+    config::load_config(config_dir).map_err(|e| show_config_error(e, parent_window))
+}
+
+/// Handle any conflicting keybinds according to the config, which has to happen after logging has been initialised.
+fn check_keybind_conflicts(conf: &mut FreecamConfig, parent_window: Option<HWND>) -> anyhow::Result<()> {
+    keybind_conflicts::check(conf).map_err(|e| show_config_error(e, parent_window))
+}
+
+fn show_config_error(e: anyhow::Error, parent_window: Option<HWND>) -> anyhow::Error {
+    let message = format!("Error: {}\nFreecam will now exit", e);
+    error_report::show_fatal("Failed to validate FreeCam config", &message, parent_window);
+
+    e
+}
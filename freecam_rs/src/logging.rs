@@ -0,0 +1,118 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use log::LevelFilter;
+
+use crate::config::FreecamConfig;
+use crate::console_status::ConsoleLogWriter;
+use crate::crash_report::RecentLogWriter;
+
+pub const LOG_FILE_NAME: &str = "freecam";
+
+/// Initialise the global logger.
+///
+/// Always logs to a rotating `freecam.log` file in `directory`, as well as to the console.
+/// The console logger is always present as the console can be opened at runtime through a config reload, and keeps its
+/// messages apart from the [crate::console_status] line.
+/// The last few log lines are additionally kept in memory for crash reports.
+pub fn init_logging(directory: impl AsRef<Path>, conf: &FreecamConfig) -> anyhow::Result<()> {
+    let cfg = simplelog::ConfigBuilder::new().build();
+    let file = RotatingFileWriter::new(directory.as_ref(), conf.log_max_file_size_kb * 1024, conf.log_max_files)?;
+
+    // All loggers log everything, the actual filtering happens through `log::set_max_level` to allow it to be changed
+    // on config reloads.
+    simplelog::CombinedLogger::init(vec![
+        simplelog::WriteLogger::new(LevelFilter::Trace, cfg.clone(), ConsoleLogWriter),
+        simplelog::WriteLogger::new(LevelFilter::Trace, cfg.clone(), file),
+        simplelog::WriteLogger::new(LevelFilter::Trace, cfg, RecentLogWriter::default()),
+    ])?;
+
+    log::set_max_level(conf.log_level);
+
+    Ok(())
+}
+
+/// A size-capped log file which moves older logs to `freecam.1.log`, `freecam.2.log`, etc.
+///
+/// Only the last `max_files` old logs are kept. Rotation also happens on creation, so each game session starts with a
+/// fresh log.
+pub struct RotatingFileWriter {
+    directory: PathBuf,
+    file: File,
+    written: u64,
+    max_size: u64,
+    max_files: u32,
+}
+
+impl RotatingFileWriter {
+    pub fn new(directory: impl Into<PathBuf>, max_size: u64, max_files: u32) -> std::io::Result<Self> {
+        let directory = directory.into();
+        rotate_files(&directory, max_files)?;
+        let file = File::create(log_path(&directory, 0))?;
+
+        Ok(Self {
+            directory,
+            file,
+            written: 0,
+            max_size,
+            max_files,
+        })
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        rotate_files(&self.directory, self.max_files)?;
+        self.file = File::create(log_path(&self.directory, 0))?;
+        self.written = 0;
+
+        Ok(())
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Shift all existing log files up by one, dropping the oldest.
+fn rotate_files(directory: &Path, max_files: u32) -> std::io::Result<()> {
+    if max_files == 0 {
+        return Ok(());
+    }
+
+    let oldest = log_path(directory, max_files);
+    if oldest.exists() {
+        std::fs::remove_file(oldest)?;
+    }
+
+    for i in (0..max_files).rev() {
+        let path = log_path(directory, i);
+
+        if path.exists() {
+            std::fs::rename(path, log_path(directory, i + 1))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn log_path(directory: &Path, index: u32) -> PathBuf {
+    if index == 0 {
+        directory.join(format!("{}.log", LOG_FILE_NAME))
+    } else {
+        directory.join(format!("{}.{}.log", LOG_FILE_NAME, index))
+    }
+}
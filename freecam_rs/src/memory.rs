@@ -0,0 +1,44 @@
+use windows::Win32::System::Memory::{VirtualQuery, MEMORY_BASIC_INFORMATION, MEM_COMMIT, PAGE_GUARD, PAGE_NOACCESS};
+
+/// Read/write access to the game's memory.
+///
+/// Either directly, when we're injected into the game ([LocalMemory]), or through an external process
+/// ([crate::remote::RemoteProcess]).
+pub trait GameMemory {
+    /// Read a `T` from the given game address.
+    unsafe fn read<T: Copy>(&self, address: *const T) -> anyhow::Result<T>;
+
+    /// Write `value` to the given game address.
+    #[cfg_attr(not(feature = "remote"), allow(dead_code))]
+    unsafe fn write<T: Copy>(&self, address: *mut T, value: T) -> anyhow::Result<()>;
+}
+
+/// Direct memory access for when we're running inside the game process.
+pub struct LocalMemory;
+
+impl GameMemory for LocalMemory {
+    unsafe fn read<T: Copy>(&self, address: *const T) -> anyhow::Result<T> {
+        Ok(address.read_unaligned())
+    }
+
+    unsafe fn write<T: Copy>(&self, address: *mut T, value: T) -> anyhow::Result<()> {
+        address.write_unaligned(value);
+        Ok(())
+    }
+}
+
+/// Whether `address` lies in committed memory of our own process which isn't guarded or inaccessible.
+///
+/// Doesn't guarantee the memory stays accessible, but catches the game having unloaded/not yet allocated it.
+pub fn is_accessible(address: usize) -> bool {
+    let mut info = MEMORY_BASIC_INFORMATION::default();
+    let size = unsafe {
+        VirtualQuery(
+            Some(address as *const _),
+            &mut info,
+            std::mem::size_of::<MEMORY_BASIC_INFORMATION>(),
+        )
+    };
+
+    size != 0 && info.State == MEM_COMMIT && info.Protect.0 & (PAGE_NOACCESS.0 | PAGE_GUARD.0) == 0
+}
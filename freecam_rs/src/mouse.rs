@@ -1,32 +1,128 @@
-use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
 use std::time::Duration;
 
 use rust_hooking_utils::patching::process::Window;
-use windows::Win32::Foundation::{HMODULE, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Foundation::{HMODULE, HWND, LPARAM, LRESULT, POINT, RECT, WPARAM};
+use windows::Win32::Graphics::Gdi::{
+    ClientToScreen, GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+};
+use windows::Win32::UI::HiDpi::GetDpiForWindow;
 use windows::Win32::UI::WindowsAndMessaging::{
-    CallNextHookEx, PeekMessageW, SetWindowsHookExW, ShowCursor, UnhookWindowsHookEx, HHOOK, MOUSEHOOKSTRUCTEX, MSG,
-    PM_REMOVE, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_MOUSEMOVE, WM_MOUSEWHEEL,
+    CallNextHookEx, ClipCursor, GetClientRect, GetClipCursor, GetWindowThreadProcessId, PeekMessageW,
+    SetWindowsHookExW, ShowCursor, UnhookWindowsHookEx, CWPSTRUCT, HHOOK, MOUSEHOOKSTRUCTEX, MSG, PM_REMOVE,
+    WA_INACTIVE, WHEEL_DELTA, WH_CALLWNDPROC, WM_ACTIVATE, WM_DISPLAYCHANGE, WM_DPICHANGED, WM_MBUTTONDOWN,
+    WM_MBUTTONUP, WM_MOUSEHWHEEL, WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_MOVE, WM_SIZE,
 };
 
+/// The monitor height (in pixels) at which a [MouseManager::display_scale] of `1.0` is used.
+const REFERENCE_MONITOR_HEIGHT: f32 = 1080.;
+
+/// What scrolling horizontally (with a tilt wheel or touchpad) does to the custom camera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum HorizontalScroll {
+    #[default]
+    Ignore,
+    /// Move the camera left/right.
+    Pan,
+    /// Rotate the camera left/right.
+    Rotate,
+}
+
+/// The raw wheel deltas, in (fractions of) multiples of `WHEEL_DELTA`, sent from the hook to our hook thread.
+///
+/// Every scroll source (mouse wheels, precision touchpads, tilt wheels) ends up as one of these, so they're all summed.
+#[derive(Debug, Clone, Copy)]
+enum WheelEvent {
+    Vertical(i32),
+    Horizontal(i32),
+}
+
+/// Accumulates the raw deltas of a single scroll axis, see [MouseManager::get_scroll_delta].
+#[derive(Debug, Default)]
+struct ScrollAxis {
+    /// Accumulated by the hook thread, an atomic to keep reading it cheap at high update rates.
+    pos: Arc<AtomicI32>,
+    old_pos: i32,
+}
+
+impl ScrollAxis {
+    /// Return how many notches were scrolled since the last call.
+    fn delta(&mut self) -> f32 {
+        let new_pos = self.pos.load(Ordering::Relaxed);
+        // Wrapping, as free-spinning wheels can scroll a very long way over the course of a session.
+        let delta = new_pos.wrapping_sub(self.old_pos);
+        self.old_pos = new_pos;
+
+        delta as f32 / WHEEL_DELTA as f32
+    }
+
+    fn reset(&mut self) {
+        self.pos.store(0, Ordering::Relaxed);
+        self.old_pos = 0;
+    }
+}
+
 pub struct MouseManager {
-    scroll_pos: Arc<Mutex<i32>>,
-    old_scroll_pos: i32,
-    shutdown: std::sync::mpsc::SyncSender<()>,
+    main_window: Window,
+    vertical_scroll: ScrollAxis,
+    horizontal_scroll: ScrollAxis,
+    /// The cursor clip region from before we confined the cursor to the game window, if we currently are.
+    ///
+    /// Shared with the [window_messages] hook, which releases the clip while the game is in the background.
+    previous_clip: Arc<Mutex<Option<RECT>>>,
+    /// The [MouseState::client_rect_version] last returned by [Self::take_window_change].
+    client_rect_version: u32,
+    /// Stops the hook thread, `None` while no hooks are installed.
+    shutdown: Option<std::sync::mpsc::SyncSender<()>>,
 }
 
 impl MouseManager {
     /// Initialises a new Windows hook for low level mouse events and tracks the mouse's scroll.
     pub fn new(main_window: Window, module_handle: HMODULE, block_middle_mouse: bool) -> anyhow::Result<Self> {
-        if STATE.get().is_some() {
+        let mut manager = Self {
+            main_window,
+            vertical_scroll: ScrollAxis::default(),
+            horizontal_scroll: ScrollAxis::default(),
+            previous_clip: Arc::new(Mutex::new(None)),
+            client_rect_version: 0,
+            shutdown: None,
+        };
+        manager.install_hooks(module_handle, block_middle_mouse)?;
+
+        Ok(manager)
+    }
+
+    /// Move our hooks over to the game's new `main_window`, after the game recreated its window.
+    ///
+    /// If this fails no hooks are installed, scrolling and hiding the cursor then don't work until the next attempt.
+    pub fn rebind(
+        &mut self,
+        main_window: Window,
+        module_handle: HMODULE,
+        block_middle_mouse: bool,
+    ) -> anyhow::Result<()> {
+        self.release_cursor_clip();
+        self.uninstall_hooks();
+        self.reset_scroll();
+        self.main_window = main_window;
+        self.client_rect_version = 0;
+
+        self.install_hooks(module_handle, block_middle_mouse)
+    }
+
+    fn install_hooks(&mut self, module_handle: HMODULE, block_middle_mouse: bool) -> anyhow::Result<()> {
+        if state().is_some() {
             anyhow::bail!("Can't initialise multiple ScrollTrackers!");
         }
 
         let (send_shutdown, recv_shutdown) = std::sync::mpsc::sync_channel(1);
-        let scroll_pos = Arc::new(Mutex::new(0));
+        let main_window = self.main_window;
 
         // Initialise listener
-        let other_scroll = scroll_pos.clone();
+        let vertical_pos = self.vertical_scroll.pos.clone();
+        let horizontal_pos = self.horizontal_scroll.pos.clone();
+        let state_previous_clip = self.previous_clip.clone();
         std::thread::spawn(move || {
             let hook = unsafe {
                 SetWindowsHookExW(
@@ -37,24 +133,57 @@ impl MouseManager {
                 )
                 .expect("Failed to set hook")
             };
+            crate::shutdown::register_hook(hook);
+            // Window messages are only seen by a hook on the game's window thread.
+            let window_hook = unsafe {
+                let thread_id = GetWindowThreadProcessId(main_window.0, None);
+                SetWindowsHookExW(WH_CALLWNDPROC, Some(window_messages), module_handle, thread_id)
+                    .map_err(|e| {
+                        crate::error_report::report(format!(
+                            "Failed to hook the game window's messages, resizing the window during a battle may \
+                             misplace the cursor: {}",
+                            e
+                        ))
+                    })
+                    .ok()
+            };
+            if let Some(window_hook) = window_hook {
+                crate::shutdown::register_hook(window_hook);
+            }
 
             let (scroll_sender, scroll_recv) = std::sync::mpsc::channel();
             let state = MouseState {
                 block_middle_mouse,
                 main_window,
                 scroll_sender,
-                hide_cursor: AtomicU32::new(2),
+                hide_cursor: AtomicBool::new(false),
+                cursor_hidden: AtomicBool::new(false),
+                active: AtomicBool::new(main_window.is_foreground_window()),
+                previous_clip: state_previous_clip,
+                client_rect: Mutex::new(unsafe { client_screen_rect(main_window.0) }.unwrap_or_default()),
+                client_rect_version: AtomicU32::new(0),
+                display_scale: AtomicU32::new(unsafe { display_scale(main_window.0) }.to_bits()),
                 hook,
+                window_hook,
             };
-            let _ = STATE.set(Box::new(state));
+            *STATE.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(state));
 
             let mut message: MSG = MSG::default();
 
             loop {
                 unsafe { while PeekMessageW(&mut message, main_window.0, 0, 0, PM_REMOVE).as_bool() {} }
 
-                while let Ok(scroll_delta) = scroll_recv.try_recv() {
-                    *other_scroll.lock().unwrap() += scroll_delta;
+                let mut scrolled = false;
+                while let Ok(event) = scroll_recv.try_recv() {
+                    let (pos, delta) = match event {
+                        WheelEvent::Vertical(delta) => (&vertical_pos, delta),
+                        WheelEvent::Horizontal(delta) => (&horizontal_pos, delta),
+                    };
+                    pos.fetch_add(delta, Ordering::Relaxed);
+                    scrolled = true;
+                }
+                if scrolled {
+                    crate::scheduler::notify_input();
                 }
 
                 if recv_shutdown.try_recv().is_ok() {
@@ -66,31 +195,90 @@ impl MouseManager {
                 std::thread::sleep(Duration::from_millis(1));
             }
         });
+        self.shutdown = Some(send_shutdown);
 
-        Ok(Self {
-            scroll_pos,
-            old_scroll_pos: 0,
-            shutdown: send_shutdown,
-        })
+        Ok(())
     }
 
-    /// Return the current scroll position
+    /// Stop the hook thread and remove its hooks, clearing the [MouseState] so hooks can be installed again.
+    fn uninstall_hooks(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+            // Block to wait for the receiver to shutdown
+            let _ = shutdown.send(());
+        }
+
+        let Some(state) = STATE.write().unwrap_or_else(PoisonError::into_inner).take() else {
+            return;
+        };
+        unsafe {
+            if crate::shutdown::unregister_hook(state.hook) {
+                UnhookWindowsHookEx(state.hook).expect("Failed to unhook");
+            }
+            if let Some(window_hook) = state.window_hook.filter(|hook| crate::shutdown::unregister_hook(*hook)) {
+                let _ = UnhookWindowsHookEx(window_hook);
+            }
+        }
+    }
+
+    /// The game window's client area in screen coordinates, kept up to date as the window is resized or moved.
+    pub fn client_rect(&self) -> RECT {
+        match state() {
+            Some(state) => *state.client_rect.lock().unwrap_or_else(PoisonError::into_inner),
+            // The hook thread hasn't started yet.
+            None => unsafe { client_screen_rect(self.main_window.0) }.unwrap_or_default(),
+        }
+    }
+
+    /// The factor to multiply cursor movement (in pixels) with, so it covers the same fraction of the screen regardless
+    /// of the monitor's resolution and DPI scaling. `1.0` on a 1080p monitor.
+    pub fn display_scale(&self) -> f32 {
+        match state() {
+            Some(state) => f32::from_bits(state.display_scale.load(Ordering::Relaxed)),
+            None => unsafe { display_scale(self.main_window.0) },
+        }
+    }
+
+    /// Return the new [Self::client_rect] if the game window was resized or moved, or the display resolution changed
+    /// (e.g., switching between fullscreen and windowed with `Alt+Enter`), since the last call.
+    ///
+    /// The cursor is confined to the new client area if it was confined to the old one.
+    pub fn take_window_change(&mut self) -> Option<RECT> {
+        let version = state()?.client_rect_version.load(Ordering::Acquire);
+        if version == self.client_rect_version {
+            return None;
+        }
+        self.client_rect_version = version;
+
+        if self.is_cursor_clipped() {
+            self.clip_cursor();
+        }
+
+        Some(self.client_rect())
+    }
+
+    /// Return the current vertical scroll position, in raw wheel deltas.
     #[allow(unused)]
     pub fn get_scroll(&self) -> i32 {
-        *self.scroll_pos.lock().unwrap()
+        self.vertical_scroll.pos.load(Ordering::Relaxed)
     }
 
-    /// Return how much the scrolling occurred since the last time this method was called.
-    pub fn get_scroll_delta(&mut self) -> i32 {
-        let new_pos = *self.scroll_pos.lock().unwrap();
-        let delta = new_pos - self.old_scroll_pos;
-        self.old_scroll_pos = new_pos;
+    /// Return how many notches were scrolled (vertically) since the last time this method was called.
+    ///
+    /// Can be fractional for high resolution scrolling (e.g., free-spinning mouse wheels and precision touchpads).
+    pub fn get_scroll_delta(&mut self) -> f32 {
+        self.vertical_scroll.delta()
+    }
 
-        delta
+    /// Like [Self::get_scroll_delta], but for horizontal scrolling (tilt wheels and touchpads), positive to the right.
+    pub fn get_horizontal_scroll_delta(&mut self) -> f32 {
+        self.horizontal_scroll.delta()
     }
 
-    pub fn reset_scroll(&self) {
-        *self.scroll_pos.lock().unwrap() = 0;
+    /// Discard any scrolling which hasn't been retrieved yet.
+    pub fn reset_scroll(&mut self) {
+        self.vertical_scroll.reset();
+        self.horizontal_scroll.reset();
     }
 
     /// Show the current game cursor.
@@ -98,7 +286,7 @@ impl MouseManager {
     /// As `SetCursor` and `ShowCursor` seemingly only work on the thread that created the window the actual method call
     /// will be executed in the context of our MouseHook, so there is a slight delay.
     pub fn show_cursor(&self) {
-        if let Some(state) = STATE.get() {
+        if let Some(state) = state() {
             state.show_cursor();
         }
     }
@@ -108,46 +296,269 @@ impl MouseManager {
     /// As `SetCursor` and `ShowCursor` seemingly only work on the thread that created the window the actual method call
     /// will be executed in the context of our MouseHook, so there is a slight delay.
     pub fn hide_cursor(&self) {
-        if let Some(state) = STATE.get() {
+        if let Some(state) = state() {
             state.hide_cursor();
         }
     }
-}
 
-impl Drop for MouseManager {
-    fn drop(&mut self) {
-        let _ = self.shutdown.send(());
-        // Block to wait for the receiver to shutdown
-        let _ = self.shutdown.send(());
+    /// Confine the cursor to the game window's client area.
+    ///
+    /// Prevents the cursor from ending up on a different monitor while we're resetting its position during freecam.
+    /// The previous clip region is restored with [Self::release_cursor_clip].
+    ///
+    /// While the game is in the background the cursor is only confined once the game is activated again, see
+    /// [MouseState::set_active].
+    pub fn clip_cursor(&mut self) {
+        let clip = self.client_rect();
+        if clip.right <= clip.left || clip.bottom <= clip.top {
+            return;
+        }
 
+        let mut previous_clip = self.previous_clip.lock().unwrap_or_else(PoisonError::into_inner);
         unsafe {
-            if let Some(state) = STATE.get() {
-                UnhookWindowsHookEx(state.hook).expect("Failed to unhook");
+            let mut previous = RECT::default();
+            if previous_clip.is_none() && GetClipCursor(&mut previous).is_err() {
+                return;
+            }
+
+            if !self.is_window_active() || ClipCursor(Some(&clip)).is_ok() {
+                // Don't overwrite the original region if we were already clipping.
+                previous_clip.get_or_insert(previous);
+            }
+        }
+    }
+
+    /// Restore the cursor clip region from before [Self::clip_cursor] was called, if any.
+    pub fn release_cursor_clip(&mut self) {
+        let previous = self.previous_clip.lock().unwrap_or_else(PoisonError::into_inner).take();
+
+        // The clip was already released when the game was deactivated, by now another application may have set its own.
+        if let Some(previous) = previous.filter(|_| self.is_window_active()) {
+            unsafe {
+                let _ = ClipCursor(Some(&previous));
             }
         }
     }
+
+    /// Whether the cursor is currently confined by [Self::clip_cursor].
+    ///
+    /// Stays `true` while the game is in the background, even though the clip is released in the meantime.
+    pub fn is_cursor_clipped(&self) -> bool {
+        self.previous_clip
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    /// Whether the game's window is the active window, as tracked by the [window_messages] hook.
+    fn is_window_active(&self) -> bool {
+        state().map_or(true, |state| state.active.load(Ordering::Acquire))
+    }
+
+    /// Return the `(forward, left)` direction to edge scroll in if the cursor (at screen position `point`) is within
+    /// `margin` pixels of the game window's client edges, each in the range `-1..=1`.
+    ///
+    /// Returns `(0, 0)` if the cursor isn't near an edge, or outside the window entirely.
+    pub fn edge_scroll_direction(&self, point: POINT, margin: u32) -> (f32, f32) {
+        let client = self.client_rect();
+
+        if point.x < client.left || point.x >= client.right || point.y < client.top || point.y >= client.bottom {
+            return (0., 0.);
+        }
+
+        let margin = margin as i32;
+        let direction = |pos: i32, low: i32, high: i32| {
+            if pos < low + margin {
+                1.
+            } else if pos >= high - margin {
+                -1.
+            } else {
+                0.
+            }
+        };
+
+        (
+            direction(point.y, client.top, client.bottom),
+            direction(point.x, client.left, client.right),
+        )
+    }
 }
 
-static STATE: once_cell::race::OnceBox<MouseState> = once_cell::race::OnceBox::new();
+impl Drop for MouseManager {
+    fn drop(&mut self) {
+        self.release_cursor_clip();
+        self.uninstall_hooks();
+    }
+}
+
+/// The state shared with our hooks, set while a [MouseManager] has its hooks installed.
+static STATE: RwLock<Option<Arc<MouseState>>> = RwLock::new(None);
+
+/// The current [STATE], cloned so the hooks never hold the lock while handling a message.
+fn state() -> Option<Arc<MouseState>> {
+    STATE.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
 
 pub struct MouseState {
     block_middle_mouse: bool,
     main_window: Window,
-    scroll_sender: std::sync::mpsc::Sender<i32>,
-    /// We use a `u32` here to allow us to represent 3 state transitions.
-    /// Hide (0), Show (1), and everything else.
-    hide_cursor: AtomicU32,
+    scroll_sender: std::sync::mpsc::Sender<WheelEvent>,
+    /// Whether we want the cursor hidden, applied on the game's window thread by the [mouse] hook.
+    hide_cursor: AtomicBool,
+    /// Whether our `ShowCursor(false)` is currently in effect, so it's never applied (or undone) twice. Only changed on
+    /// the game's window thread.
+    cursor_hidden: AtomicBool,
+    /// Whether the game's window is active, tracked through `WM_ACTIVATE` by the [window_messages] hook.
+    active: AtomicBool,
+    /// See [MouseManager::previous_clip].
+    previous_clip: Arc<Mutex<Option<RECT>>>,
+    /// The game window's client area in screen coordinates, refreshed by [window_messages].
+    client_rect: Mutex<RECT>,
+    /// Incremented whenever the `client_rect` changes.
+    client_rect_version: AtomicU32,
+    /// The `f32` bits of the [MouseManager::display_scale], refreshed by [window_messages].
+    display_scale: AtomicU32,
     hook: HHOOK,
+    /// The [window_messages] hook, if it could be installed.
+    window_hook: Option<HHOOK>,
 }
 
 impl MouseState {
     pub fn show_cursor(&self) {
-        self.hide_cursor.store(1, Ordering::Relaxed);
+        self.hide_cursor.store(false, Ordering::Relaxed);
     }
 
     pub fn hide_cursor(&self) {
-        self.hide_cursor.store(0, Ordering::Relaxed);
+        self.hide_cursor.store(true, Ordering::Relaxed);
+    }
+
+    /// Hide or show the cursor according to [Self::hide_cursor], never hiding it while the game is in the background.
+    ///
+    /// Has to be called on the game's window thread, as that's the only thread `ShowCursor` affects the game's cursor
+    /// from.
+    unsafe fn apply_cursor_visibility(&self) {
+        let hide = self.hide_cursor.load(Ordering::Relaxed) && self.active.load(Ordering::Acquire);
+        if self.cursor_hidden.load(Ordering::Relaxed) != hide {
+            ShowCursor(!hide);
+            self.cursor_hidden.store(hide, Ordering::Relaxed);
+        }
     }
+
+    /// Release everything we did to the cursor when the game is deactivated (e.g., alt-tabbing), and re-apply it once
+    /// the game is activated again.
+    ///
+    /// Otherwise the cursor stays hidden and trapped in the (now background) game window until our next update notices
+    /// the focus change, or forever if the game stops sending us mouse messages.
+    unsafe fn set_active(&self, active: bool) {
+        if self.active.swap(active, Ordering::AcqRel) == active {
+            return;
+        }
+
+        self.apply_cursor_visibility();
+
+        let previous_clip = *self.previous_clip.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(previous) = previous_clip {
+            if active {
+                let clip = *self.client_rect.lock().unwrap_or_else(PoisonError::into_inner);
+                let _ = ClipCursor(Some(&clip));
+            } else {
+                let _ = ClipCursor(Some(&previous));
+            }
+        }
+
+        log::debug!(
+            "Game window {}, cursor hidden: {}, clipped: {}",
+            if active { "activated" } else { "deactivated" },
+            self.cursor_hidden.load(Ordering::Relaxed),
+            active && previous_clip.is_some()
+        );
+    }
+}
+
+/// The client area of `window` in screen coordinates, which also covers borderless windows spanning the whole screen.
+unsafe fn client_screen_rect(window: HWND) -> Option<RECT> {
+    let mut client = RECT::default();
+    GetClientRect(window, &mut client).ok()?;
+
+    let mut top_left = POINT {
+        x: client.left,
+        y: client.top,
+    };
+    let mut bottom_right = POINT {
+        x: client.right,
+        y: client.bottom,
+    };
+    if !ClientToScreen(window, &mut top_left).as_bool() || !ClientToScreen(window, &mut bottom_right).as_bool() {
+        return None;
+    }
+
+    Some(RECT {
+        left: top_left.x,
+        top: top_left.y,
+        right: bottom_right.x,
+        bottom: bottom_right.y,
+    })
+}
+
+/// See [MouseManager::display_scale], `1.0` if the monitor of `window` can't be determined.
+///
+/// Cursor positions and monitor sizes are in the same (possibly DPI virtualised) coordinates, so the monitor's height
+/// alone accounts for both its resolution and the DPI scaling.
+unsafe fn display_scale(window: HWND) -> f32 {
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    if !GetMonitorInfoW(MonitorFromWindow(window, MONITOR_DEFAULTTONEAREST), &mut info).as_bool() {
+        return 1.;
+    }
+
+    let height = info.rcMonitor.bottom - info.rcMonitor.top;
+    if height > 0 {
+        REFERENCE_MONITOR_HEIGHT / height as f32
+    } else {
+        1.
+    }
+}
+
+/// Keeps the [MouseState::client_rect] and [MouseState::display_scale] up to date, runs on the game's window thread.
+///
+/// These messages are rare (outside of dragging the window around), so querying the window here is cheap enough.
+unsafe extern "system" fn window_messages(n_code: i32, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
+    if n_code >= 0 {
+        let message = &*(l_param.0 as *const CWPSTRUCT);
+
+        if let Some(state) = state().filter(|state| message.hwnd == state.main_window.0) {
+            if message.message == WM_ACTIVATE {
+                state.set_active((message.wParam.0 & 0xFFFF) as u32 != WA_INACTIVE);
+            }
+
+            if matches!(message.message, WM_SIZE | WM_MOVE | WM_DISPLAYCHANGE | WM_DPICHANGED) {
+                // Minimizing shrinks the client area to nothing, which is handled separately.
+                let rect = client_screen_rect(state.main_window.0)
+                    .filter(|rect| rect.right > rect.left && rect.bottom > rect.top);
+
+                if let Some(rect) = rect {
+                    let mut current = state.client_rect.lock().unwrap_or_else(PoisonError::into_inner);
+                    if *current != rect {
+                        *current = rect;
+                        state.client_rect_version.fetch_add(1, Ordering::Release);
+                        // The window may have moved to a different monitor.
+                        let scale = display_scale(state.main_window.0);
+                        if state.display_scale.swap(scale.to_bits(), Ordering::Relaxed) != scale.to_bits() {
+                            log::info!(
+                                "Display scale changed to {}, window at {} DPI",
+                                scale,
+                                GetDpiForWindow(state.main_window.0)
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    CallNextHookEx(None, n_code, w_param, l_param)
 }
 
 /// Non low-level hooks can be executed from any thread, so we can't use a thread-local.
@@ -155,7 +566,7 @@ impl MouseState {
 /// This hook is also _extremely_ vulnerable to causing lag/blocking applications, so it should be as cheap as possible to execute.
 unsafe extern "system" fn mouse(n_code: i32, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
     if n_code >= 0 {
-        let Some(state) = STATE.get() else {
+        let Some(state) = state() else {
             return CallNextHookEx(None, n_code, w_param, l_param);
         };
 
@@ -170,30 +581,22 @@ unsafe extern "system" fn mouse(n_code: i32, w_param: WPARAM, l_param: LPARAM) -
                     return LRESULT(1);
                 }
             }
-            WM_MOUSEWHEEL => {
+            message @ (WM_MOUSEWHEEL | WM_MOUSEHWHEEL) => {
                 let p_mouse = l_param.0 as *mut MOUSEHOOKSTRUCTEX;
-                let to_store = if (*p_mouse).mouseData >> 16 == 120 { 1 } else { -1 };
+                // The high word is the signed wheel delta, in (fractions of) multiples of `WHEEL_DELTA`.
+                let delta = ((*p_mouse).mouseData >> 16) as i16 as i32;
+                let event =
+                    if message == WM_MOUSEWHEEL { WheelEvent::Vertical(delta) } else { WheelEvent::Horizontal(delta) };
 
                 if (*p_mouse).Base.hwnd == state.main_window.0 {
-                    let _ = state.scroll_sender.send(to_store);
+                    let _ = state.scroll_sender.send(event);
                 }
             }
             WM_MOUSEMOVE => {
                 // We need to call the `ShowCursor` routines in the context of the right thread (as far as I can tell, documentation
                 // is rather sparse on _where_ exactly you're supposed to call these functions. It doesn't work when called from our DLL threads, at least).
                 // The mousemove event is incredibly common, so a decent place to ensure the cursor is hidden quickly.
-                let cursor_value = state.hide_cursor.load(Ordering::Relaxed);
-                match cursor_value {
-                    0 => {
-                        ShowCursor(false);
-                        state.hide_cursor.store(2, Ordering::Relaxed);
-                    }
-                    1 => {
-                        ShowCursor(true);
-                        state.hide_cursor.store(2, Ordering::Relaxed);
-                    }
-                    _ => {}
-                }
+                state.apply_cursor_visibility();
             }
             _ => {}
         }
@@ -0,0 +1,254 @@
+//! A tiny on-screen text overlay for transient feedback (e.g., "Bookmark 3 saved"), see [notify].
+//!
+//! The overlay is a click-through, always-on-top window drawn with GDI over the top left of the game window. It's only
+//! visible while the game runs in windowed (or borderless) mode, as nothing can be drawn over exclusive fullscreen.
+
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "overlay")]
+pub use window::Overlay;
+
+/// How the on-screen messages are shown.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct OverlayConfig {
+    /// Off by default, as the overlay window can interfere with exclusive fullscreen, where it isn't visible anyway.
+    pub enabled: bool,
+    /// How long a message is fully visible.
+    pub message_duration: Duration,
+    /// How long a message takes to fade out afterwards.
+    pub fade_duration: Duration,
+    /// The height of the text, in pixels.
+    pub font_size: i32,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            message_duration: Duration::from_secs(1),
+            fade_duration: Duration::from_millis(500),
+            font_size: 24,
+        }
+    }
+}
+
+/// The message currently shown, and when it was first shown.
+static MESSAGE: Mutex<Option<(String, Instant)>> = Mutex::new(None);
+
+/// Show `message` on the overlay (if enabled), replacing any earlier message. Always logged as well.
+pub fn notify(message: impl Into<String>) {
+    let message = message.into();
+    log::info!("{}", message);
+
+    *MESSAGE.lock().unwrap_or_else(PoisonError::into_inner) = Some((message, Instant::now()));
+}
+
+/// The opacity (in the range `0..=1`) of a message shown `elapsed` ago.
+#[cfg_attr(not(feature = "overlay"), allow(dead_code))]
+fn opacity(elapsed: Duration, conf: &OverlayConfig) -> f32 {
+    let Some(fading) = elapsed.checked_sub(conf.message_duration) else {
+        return 1.;
+    };
+
+    if fading >= conf.fade_duration {
+        0.
+    } else {
+        1. - fading.as_secs_f32() / conf.fade_duration.as_secs_f32()
+    }
+}
+
+#[cfg(feature = "overlay")]
+mod window {
+    use std::sync::mpsc::SyncSender;
+    use std::sync::PoisonError;
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+
+    use rust_hooking_utils::patching::process::Window;
+    use windows::Win32::Foundation::{COLORREF, HMODULE, HWND, LPARAM, LRESULT, POINT, RECT, WPARAM};
+    use windows::Win32::Graphics::Gdi::{
+        ClientToScreen, CreateFontIndirectW, CreateSolidBrush, DeleteObject, FillRect, GetDC, ReleaseDC, SelectObject,
+        SetBkMode, SetTextColor, TextOutW, LOGFONTW, TRANSPARENT,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, GetClientRect, PeekMessageW, RegisterClassW,
+        SetLayeredWindowAttributes, SetWindowPos, ShowWindow, UnregisterClassW, HWND_TOPMOST, LWA_ALPHA, LWA_COLORKEY,
+        MSG, PM_REMOVE, SWP_NOACTIVATE, SW_HIDE, SW_SHOWNOACTIVATE, WNDCLASSW, WS_EX_LAYERED, WS_EX_NOACTIVATE,
+        WS_EX_TOOLWINDOW, WS_EX_TOPMOST, WS_EX_TRANSPARENT, WS_POPUP,
+    };
+
+    use super::{opacity, OverlayConfig, MESSAGE};
+
+    /// Drawn as fully transparent, the text is drawn on top of it.
+    const TRANSPARENT_KEY: COLORREF = COLORREF(0x000000);
+    const TEXT_COLOUR: COLORREF = COLORREF(0xFFFFFF);
+    const SHADOW_COLOUR: COLORREF = COLORREF(0x202020);
+    /// The distance between the text and the game window's top left corner.
+    const MARGIN: i32 = 16;
+    const REDRAW_INTERVAL: Duration = Duration::from_millis(33);
+
+    /// The overlay window, which shows the messages passed to [super::notify] until dropped.
+    pub struct Overlay {
+        shutdown: SyncSender<()>,
+        /// Joined on drop, as the thread runs our code and window procedure until it has seen the shutdown.
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl Overlay {
+        pub fn new(conf: &OverlayConfig, main_window: Window, module_handle: HMODULE) -> anyhow::Result<Self> {
+            let (send_shutdown, recv_shutdown) = std::sync::mpsc::sync_channel(1);
+            let conf = conf.clone();
+            let game_window = main_window.0;
+
+            let thread = std::thread::spawn(move || unsafe {
+                let class = WNDCLASSW {
+                    lpfnWndProc: Some(window_proc),
+                    hInstance: module_handle.into(),
+                    lpszClassName: windows::core::w!("FreecamOverlay"),
+                    ..Default::default()
+                };
+                // Unregistered again once the thread exits, as the class' window procedure is part of our module.
+                RegisterClassW(&class);
+
+                let window = CreateWindowExW(
+                    WS_EX_LAYERED | WS_EX_TRANSPARENT | WS_EX_TOPMOST | WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE,
+                    windows::core::w!("FreecamOverlay"),
+                    windows::core::w!("FreeCam"),
+                    WS_POPUP,
+                    0,
+                    0,
+                    0,
+                    0,
+                    None,
+                    None,
+                    module_handle,
+                    None,
+                );
+
+                if window.0 == 0 {
+                    crate::error_report::report("Failed to create the on-screen overlay window");
+                    let _ = UnregisterClassW(class.lpszClassName, module_handle);
+                    return;
+                }
+
+                let mut message = MSG::default();
+                let mut visible = false;
+
+                loop {
+                    while PeekMessageW(&mut message, window, 0, 0, PM_REMOVE).as_bool() {
+                        DispatchMessageW(&message);
+                    }
+
+                    if recv_shutdown.try_recv().is_ok() {
+                        break;
+                    }
+
+                    let current = MESSAGE.lock().unwrap_or_else(PoisonError::into_inner).clone();
+                    let shown = current.and_then(|(text, since)| {
+                        let alpha = opacity(since.elapsed(), &conf);
+                        (alpha > 0.).then_some((text, alpha))
+                    });
+
+                    match shown {
+                        Some((text, alpha)) => {
+                            draw(window, game_window, &text, alpha, &conf);
+                            if !visible {
+                                ShowWindow(window, SW_SHOWNOACTIVATE);
+                                visible = true;
+                            }
+                        }
+                        None if visible => {
+                            ShowWindow(window, SW_HIDE);
+                            visible = false;
+                        }
+                        None => {}
+                    }
+
+                    std::thread::sleep(REDRAW_INTERVAL);
+                }
+
+                let _ = DestroyWindow(window);
+                let _ = UnregisterClassW(class.lpszClassName, module_handle);
+            });
+
+            Ok(Self {
+                shutdown: send_shutdown,
+                thread: Some(thread),
+            })
+        }
+    }
+
+    impl Drop for Overlay {
+        fn drop(&mut self) {
+            let _ = self.shutdown.send(());
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    /// Move the overlay to the game window's top left corner, and draw `text` with the given `alpha`.
+    unsafe fn draw(window: HWND, game_window: HWND, text: &str, alpha: f32, conf: &OverlayConfig) {
+        let mut client = RECT::default();
+        let mut origin = POINT::default();
+        if GetClientRect(game_window, &mut client).is_err() || !ClientToScreen(game_window, &mut origin).as_bool() {
+            return;
+        }
+
+        let width = client.right - client.left - 2 * MARGIN;
+        let height = conf.font_size * 2;
+        let _ = SetWindowPos(
+            window,
+            HWND_TOPMOST,
+            origin.x + MARGIN,
+            origin.y + MARGIN,
+            width,
+            height,
+            SWP_NOACTIVATE,
+        );
+        let _ = SetLayeredWindowAttributes(window, TRANSPARENT_KEY, (alpha * 255.) as u8, LWA_COLORKEY | LWA_ALPHA);
+
+        let dc = GetDC(window);
+        let background = CreateSolidBrush(TRANSPARENT_KEY);
+        FillRect(
+            dc,
+            &RECT {
+                left: 0,
+                top: 0,
+                right: width,
+                bottom: height,
+            },
+            background,
+        );
+        let _ = DeleteObject(background);
+
+        let mut font = LOGFONTW {
+            lfHeight: conf.font_size,
+            lfWeight: 700,
+            ..Default::default()
+        };
+        for (dst, src) in font.lfFaceName.iter_mut().zip("Segoe UI".encode_utf16()) {
+            *dst = src;
+        }
+        let font = CreateFontIndirectW(&font);
+        let previous_font = SelectObject(dc, font);
+        SetBkMode(dc, TRANSPARENT);
+
+        let text = text.encode_utf16().collect::<Vec<_>>();
+        // A shadow keeps the text readable on bright backgrounds.
+        SetTextColor(dc, SHADOW_COLOUR);
+        TextOutW(dc, 2, 2, &text);
+        SetTextColor(dc, TEXT_COLOUR);
+        TextOutW(dc, 0, 0, &text);
+
+        SelectObject(dc, previous_font);
+        let _ = DeleteObject(font);
+        ReleaseDC(window, dc);
+    }
+
+    unsafe extern "system" fn window_proc(window: HWND, message: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        DefWindowProcW(window, message, wparam, lparam)
+    }
+}
@@ -0,0 +1,55 @@
+use std::path::{Path, PathBuf};
+
+/// Environment variable which, if set, overrides the directory used for the config, logs, and screenshots.
+pub const CONFIG_DIR_ENV: &str = "MED2_FREECAM_DIR";
+/// The per-user fallback directory within `%APPDATA%`.
+pub const APPDATA_DIR_NAME: &str = "med2_freecam";
+
+/// The directory to store the config, logs, and screenshots in, see [find_config_directory].
+pub struct ConfigDirectory {
+    pub path: PathBuf,
+    /// The outcome for each directory that was tried, in order. Logged once logging has been initialised.
+    pub search_log: Vec<String>,
+}
+
+impl ConfigDirectory {
+    /// Log the search order which led to this directory being chosen.
+    pub fn log_search(&self) {
+        for line in &self.search_log {
+            log::info!("Config directory search: {}", line);
+        }
+        log::info!("Using config directory: {:?}", self.path);
+    }
+}
+
+/// Find the first writable config directory.
+///
+/// The search order is:
+/// 1. The [CONFIG_DIR_ENV] environment variable, if set.
+/// 2. The `default_directory` (next to the DLL/executable).
+/// 3. `%APPDATA%/med2_freecam`, for when the game is installed somewhere read-only (e.g., `Program Files`).
+pub fn find_config_directory(default_directory: &Path) -> anyhow::Result<ConfigDirectory> {
+    let candidates = [
+        std::env::var_os(CONFIG_DIR_ENV).map(|dir| (CONFIG_DIR_ENV, PathBuf::from(dir))),
+        Some(("default", default_directory.to_path_buf())),
+        std::env::var_os("APPDATA").map(|dir| ("APPDATA", PathBuf::from(dir).join(APPDATA_DIR_NAME))),
+    ];
+    let mut search_log = Vec::new();
+
+    for (source, path) in candidates.into_iter().flatten() {
+        match ensure_writable(&path) {
+            Ok(()) => return Ok(ConfigDirectory { path, search_log }),
+            Err(e) => search_log.push(format!("{} ({:?}) isn't writable: {}", source, path, e)),
+        }
+    }
+
+    anyhow::bail!("No writable config directory found: {}", search_log.join(", "))
+}
+
+/// Create the directory if needed, and check whether we can write files to it.
+fn ensure_writable(directory: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(directory)?;
+    let probe = directory.join(".freecam_write_test");
+    std::fs::write(&probe, [])?;
+    std::fs::remove_file(probe)
+}
@@ -0,0 +1,389 @@
+//! Remote process mode, running the freecam as a standalone executable instead of injecting a DLL.
+//!
+//! All reads/writes go through `ReadProcessMemory`/`WriteProcessMemory`. As we can't point the game's code at our own
+//! memory this mode is restricted compared to the injected DLL: there is no ground clipping prevention, relative height,
+//! scroll/freecam mouse movement, or unit card teleport handling. Only keyboard movement and rotation are supported.
+
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
+
+use anyhow::Context;
+use windows::Win32::Foundation::{CloseHandle, HANDLE, HWND};
+use windows::Win32::System::Diagnostics::Debug::{FlushInstructionCache, ReadProcessMemory, WriteProcessMemory};
+use windows::Win32::System::Memory::{VirtualProtectEx, PAGE_EXECUTE_READWRITE, PAGE_PROTECTION_FLAGS};
+use windows::Win32::System::Threading::{
+    OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_OPERATION, PROCESS_VM_READ, PROCESS_VM_WRITE,
+};
+use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId, IsWindow};
+
+use crate::battle_cam::coords::{GamePos, WorldPos};
+use crate::battle_cam::data::{battle_cam_addr, battle_cam_conf_type_addr, battle_cam_target_addr, BattleCameraType};
+use crate::battle_cam::smoothing::Smoother;
+use crate::battle_cam::velocity::{Acceleration, Velocity};
+use crate::battle_cam::{
+    data, game_target_distance, patch_locations, profile, velocity, KeyboardLookRamp, DEFAULT_TARGET_DISTANCE,
+};
+use crate::config::FreecamConfig;
+use crate::input::InputManager;
+use crate::memory::GameMemory;
+
+/// The default title of the game's main window.
+pub const DEFAULT_WINDOW_TITLE: &str = "Medieval 2";
+
+/// Attach to the game window matching the given title pattern and run the (restricted) freecam until the game exits.
+///
+/// * `pid` - Only attach to the game process with this id, for when multiple instances of the game are running.
+///
+/// The config is looked for in `default_directory` first, see [crate::paths::find_config_directory].
+pub fn run(default_directory: &Path, window_title: &str, pid: Option<u32>) -> anyhow::Result<()> {
+    let config_dir = crate::paths::find_config_directory(default_directory)?;
+    let config_directory = config_dir.path.as_path();
+    crate::config::create_initial_config(config_directory)?;
+    let conf = crate::config::load_config(config_directory)?;
+    crate::logging::init_logging(config_directory, &conf)?;
+    config_dir.log_search();
+    profile::set_active(&conf.patch_profile);
+
+    log::info!("Waiting for window: {:?} (pid: {:?})", window_title, pid);
+    let window = loop {
+        if let Some(wnd) = crate::window::find_window(window_title, pid) {
+            break wnd;
+        }
+        std::thread::sleep(Duration::from_secs(1));
+    };
+
+    let process = Rc::new(RemoteProcess::attach(window)?);
+    log::info!("Attached to process: {}", process.pid);
+
+    let mut key_manager = InputManager::new();
+    let update_duration = Duration::from_secs_f64(1.0 / conf.update_rate as f64);
+    let mut camera: Option<RemoteBattleCamera> = None;
+
+    while process.is_alive() {
+        let in_battle = data::is_in_battle_in(process.as_ref());
+
+        if in_battle && camera.is_none() {
+            log::debug!("Battle started");
+            camera = Some(RemoteBattleCamera::new(process.clone())?);
+        } else if !in_battle && camera.is_some() {
+            log::debug!("Battle ended");
+            camera = None;
+        }
+
+        if let Some(camera) = camera.as_mut() {
+            if unsafe { GetForegroundWindow() } == window {
+                camera.run(&conf, &mut key_manager)?;
+            }
+        }
+
+        std::thread::sleep(update_duration);
+        key_manager.end_frame();
+    }
+
+    log::info!("Game exited");
+
+    Ok(())
+}
+
+/// A handle to an external game process.
+pub struct RemoteProcess {
+    handle: HANDLE,
+    window: HWND,
+    pub pid: u32,
+}
+
+impl RemoteProcess {
+    /// Open the process owning the given window.
+    pub fn attach(window: HWND) -> anyhow::Result<Self> {
+        unsafe {
+            let mut pid = 0;
+            GetWindowThreadProcessId(window, Some(&mut pid));
+            let handle = OpenProcess(
+                PROCESS_VM_READ | PROCESS_VM_WRITE | PROCESS_VM_OPERATION | PROCESS_QUERY_INFORMATION,
+                false,
+                pid,
+            )
+            .context("Failed to open game process, try running as administrator")?;
+
+            Ok(Self { handle, window, pid })
+        }
+    }
+
+    /// Whether the game's window still exists.
+    pub fn is_alive(&self) -> bool {
+        unsafe { IsWindow(self.window).as_bool() }
+    }
+
+    pub fn read_bytes(&self, address: usize, length: usize) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = vec![0u8; length];
+        unsafe {
+            ReadProcessMemory(
+                self.handle,
+                address as *const _,
+                buffer.as_mut_ptr().cast(),
+                length,
+                None,
+            )?;
+        }
+        Ok(buffer)
+    }
+
+    /// Overwrite code in the game process, temporarily making the page writable.
+    pub fn write_code(&self, address: usize, bytes: &[u8]) -> anyhow::Result<()> {
+        unsafe {
+            let mut old_protect = PAGE_PROTECTION_FLAGS::default();
+            VirtualProtectEx(
+                self.handle,
+                address as *const _,
+                bytes.len(),
+                PAGE_EXECUTE_READWRITE,
+                &mut old_protect,
+            )?;
+            let result = WriteProcessMemory(
+                self.handle,
+                address as *const _,
+                bytes.as_ptr().cast(),
+                bytes.len(),
+                None,
+            );
+            VirtualProtectEx(
+                self.handle,
+                address as *const _,
+                bytes.len(),
+                old_protect,
+                &mut old_protect,
+            )?;
+            result?;
+            FlushInstructionCache(self.handle, Some(address as *const _), bytes.len())?;
+        }
+        Ok(())
+    }
+}
+
+impl GameMemory for RemoteProcess {
+    unsafe fn read<T: Copy>(&self, address: *const T) -> anyhow::Result<T> {
+        let mut value = std::mem::MaybeUninit::<T>::uninit();
+        ReadProcessMemory(
+            self.handle,
+            address.cast(),
+            value.as_mut_ptr().cast(),
+            std::mem::size_of::<T>(),
+            None,
+        )?;
+        Ok(value.assume_init())
+    }
+
+    unsafe fn write<T: Copy>(&self, address: *mut T, value: T) -> anyhow::Result<()> {
+        WriteProcessMemory(
+            self.handle,
+            address.cast_const().cast(),
+            (&value as *const T).cast(),
+            std::mem::size_of::<T>(),
+            None,
+        )?;
+        Ok(())
+    }
+}
+
+impl Drop for RemoteProcess {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseHandle(self.handle);
+        }
+    }
+}
+
+/// Code patches in a [RemoteProcess], the original code is restored when dropped.
+pub struct RemotePatcher {
+    process: Rc<RemoteProcess>,
+    /// `(address, original, patched)`
+    patches: Vec<(usize, Vec<u8>, Vec<u8>)>,
+    enabled: bool,
+}
+
+impl RemotePatcher {
+    pub fn new(process: Rc<RemoteProcess>) -> Self {
+        Self {
+            process,
+            patches: Vec::new(),
+            enabled: false,
+        }
+    }
+
+    /// Register a patch, it's only written once [Self::enable_all_patches] is called.
+    pub fn add_patch(&mut self, address: usize, bytes: Vec<u8>) -> anyhow::Result<()> {
+        let original = self.process.read_bytes(address, bytes.len())?;
+        self.patches.push((address, original, bytes));
+        Ok(())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn enable_all_patches(&mut self) -> anyhow::Result<()> {
+        for (address, _, patched) in &self.patches {
+            self.process.write_code(*address, patched)?;
+        }
+        self.enabled = true;
+        Ok(())
+    }
+
+    pub fn disable_all_patches(&mut self) -> anyhow::Result<()> {
+        for (address, original, _) in &self.patches {
+            self.process.write_code(*address, original)?;
+        }
+        self.enabled = false;
+        Ok(())
+    }
+}
+
+impl Drop for RemotePatcher {
+    fn drop(&mut self) {
+        if self.enabled && self.process.is_alive() {
+            if let Err(e) = self.disable_all_patches() {
+                log::error!("Failed to restore game code: {}", e);
+            }
+        }
+    }
+}
+
+/// The restricted equivalent of [crate::battle_cam::BattleState] for a [RemoteProcess].
+struct RemoteBattleCamera {
+    process: Rc<RemoteProcess>,
+    patcher: RemotePatcher,
+    pos: WorldPos,
+    pitch: f32,
+    yaw: f32,
+    velocity: Velocity,
+    smoother: Smoother,
+    keyboard_look_ramp: KeyboardLookRamp,
+    /// The last camera position we wrote, used to detect the game moving the camera.
+    last_written: Option<GamePos>,
+    original_camera_type: Option<u32>,
+    /// How far the game placed its camera target as of the last sync, see [crate::config::TargetDistance::Game].
+    game_target_distance: f32,
+}
+
+impl RemoteBattleCamera {
+    fn new(process: Rc<RemoteProcess>) -> anyhow::Result<Self> {
+        let mut patcher = RemotePatcher::new(process.clone());
+        for (_, location) in profile::active().patch_locations.iter() {
+            let address = location.address();
+            let first_byte = unsafe { process.read(address.cast::<u8>())? };
+            patcher.add_patch(address.get(), patch_locations::nop_patch(first_byte))?;
+        }
+
+        Ok(Self {
+            process,
+            patcher,
+            pos: WorldPos::default(),
+            pitch: 0.,
+            yaw: 0.,
+            velocity: Velocity::default(),
+            smoother: Smoother::default(),
+            keyboard_look_ramp: KeyboardLookRamp::default(),
+            last_written: None,
+            original_camera_type: None,
+            game_target_distance: DEFAULT_TARGET_DISTANCE,
+        })
+    }
+
+    fn run(&mut self, conf: &FreecamConfig, key_man: &mut InputManager) -> anyhow::Result<()> {
+        let camera_pos = unsafe { self.process.read(battle_cam_addr())? };
+
+        if !self.patcher.is_enabled() || self.last_written != Some(camera_pos) {
+            self.sync(camera_pos)?;
+        }
+
+        let mut acceleration = Acceleration::default();
+        let moved = crate::battle_cam::movement_acceleration(key_man, conf, self.yaw, &mut acceleration);
+        let rotated =
+            crate::battle_cam::rotation_acceleration(key_man, conf, &mut self.keyboard_look_ramp, &mut acceleration);
+
+        if (moved || rotated) && !self.patcher.is_enabled() {
+            self.take_control(conf)?;
+        }
+
+        if !self.patcher.is_enabled() {
+            return Ok(());
+        }
+
+        let (horizontal_speed, vertical_speed) = crate::battle_cam::calculate_speed_multipliers(conf, key_man);
+        let smoothing = conf.camera.smoothing();
+        velocity::calculate_next_velocity(
+            &mut self.velocity,
+            &acceleration,
+            horizontal_speed,
+            vertical_speed,
+            &smoothing,
+            conf.camera.normalize_diagonal,
+        );
+        conf.camera
+            .clamp_velocity(&mut self.velocity, 1. / conf.update_rate as f32);
+
+        self.pos.x += self.velocity.x;
+        self.pos.y += self.velocity.y;
+        self.pos.z += self.velocity.z;
+        self.pitch = conf.camera.clamp_pitch(self.pitch + self.velocity.pitch);
+        self.yaw += self.velocity.yaw;
+        self.smoother
+            .decay(&mut self.velocity, &smoothing, &conf.camera.smoothing_models);
+        self.pos = conf.camera.clamp_to_bounds(self.pos);
+
+        let camera_pos: GamePos = self.pos.into();
+        let distance = conf.camera.target_distance(self.game_target_distance);
+        unsafe {
+            self.process.write(battle_cam_addr(), camera_pos)?;
+            self.process.write(
+                battle_cam_target_addr(),
+                self.pos.look_target(self.pitch, self.yaw, distance).into(),
+            )?;
+        }
+        self.last_written = Some(camera_pos);
+
+        Ok(())
+    }
+
+    fn sync(&mut self, camera_pos: GamePos) -> anyhow::Result<()> {
+        let target_pos = unsafe { self.process.read(battle_cam_target_addr())? };
+        self.pos = camera_pos.into();
+        (self.pitch, self.yaw) = self.pos.pitch_yaw_towards(&target_pos.into());
+        if let Some(distance) = game_target_distance(camera_pos, target_pos) {
+            self.game_target_distance = distance;
+        }
+        self.last_written = Some(camera_pos);
+        Ok(())
+    }
+
+    fn take_control(&mut self, conf: &FreecamConfig) -> anyhow::Result<()> {
+        if conf.force_ttw_camera {
+            let current = unsafe { self.process.read(battle_cam_conf_type_addr().cast::<u32>())? };
+            if current != BattleCameraType::TotalWar as u32 {
+                self.original_camera_type.get_or_insert(current);
+                unsafe {
+                    self.process.write(
+                        battle_cam_conf_type_addr().cast::<u32>(),
+                        BattleCameraType::TotalWar as u32,
+                    )?
+                };
+            }
+        }
+
+        self.velocity = Velocity::default();
+
+        self.smoother.reset();
+        self.patcher.enable_all_patches()
+    }
+}
+
+impl Drop for RemoteBattleCamera {
+    fn drop(&mut self) {
+        if let Some(original) = self.original_camera_type.take() {
+            if self.process.is_alive() {
+                let _ = unsafe { self.process.write(battle_cam_conf_type_addr().cast::<u32>(), original) };
+            }
+        }
+    }
+}
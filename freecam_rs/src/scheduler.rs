@@ -0,0 +1,153 @@
+use std::sync::atomic::{AtomicIsize, Ordering};
+use std::time::{Duration, Instant};
+
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::{CloseHandle, HANDLE, WAIT_EVENT, WAIT_OBJECT_0};
+use windows::Win32::System::Threading::{
+    CreateEventW, CreateWaitableTimerExW, SetEvent, SetWaitableTimer, WaitForMultipleObjects, WaitForSingleObject,
+    CREATE_WAITABLE_TIMER_HIGH_RESOLUTION, INFINITE, TIMER_ALL_ACCESS,
+};
+
+use crate::config::FreecamConfig;
+#[cfg(feature = "vsync-to-game")]
+use crate::frame_sync::FrameSync;
+
+/// The raw `HANDLE` of the event signalled by [notify_input], `0` if there is no scheduler.
+static INPUT_EVENT: AtomicIsize = AtomicIsize::new(0);
+
+/// Wake the update loop early, as the user just provided input which the camera should respond to.
+///
+/// Meant for discrete events (e.g. a scroll or director key press) which would otherwise wait for the next tick.
+pub fn notify_input() {
+    let event = INPUT_EVENT.load(Ordering::SeqCst);
+
+    if event != 0 {
+        unsafe {
+            let _ = SetEvent(HANDLE(event));
+        }
+    }
+}
+
+/// Paces the main update loop, either at a fixed [FreecamConfig::update_rate] or once per frame rendered by the game.
+///
+/// Uses a (high resolution) waitable timer, as `thread::sleep` can overshoot by up to a full scheduler quantum.
+/// Ticks are scheduled at fixed intervals, so the time spent running the update doesn't lower the actual rate.
+/// In between ticks an input event (see [notify_input]) runs an extra update right away, at most once per tick.
+pub struct UpdateScheduler {
+    timer: HANDLE,
+    input_event: HANDLE,
+    period: Duration,
+    next_tick: Instant,
+    /// Whether the last wait was cut short by an input event, in which case the `next_tick` is still ahead of us.
+    woke_early: bool,
+    #[cfg(feature = "vsync-to-game")]
+    frame_sync: Option<FrameSync>,
+}
+
+impl UpdateScheduler {
+    pub fn new(conf: &FreecamConfig) -> anyhow::Result<Self> {
+        let timer = unsafe {
+            // High resolution timers are only supported since Windows 10 1803.
+            CreateWaitableTimerExW(
+                None,
+                PCWSTR::null(),
+                CREATE_WAITABLE_TIMER_HIGH_RESOLUTION,
+                TIMER_ALL_ACCESS.0,
+            )
+            .or_else(|_| CreateWaitableTimerExW(None, PCWSTR::null(), 0, TIMER_ALL_ACCESS.0))?
+        };
+        // Auto-reset, so any amount of input in between two updates only wakes us once.
+        let input_event = unsafe { CreateEventW(None, false, false, PCWSTR::null())? };
+        INPUT_EVENT.store(input_event.0, Ordering::SeqCst);
+
+        let mut scheduler = Self {
+            timer,
+            input_event,
+            period: Duration::ZERO,
+            next_tick: Instant::now(),
+            woke_early: false,
+            #[cfg(feature = "vsync-to-game")]
+            frame_sync: None,
+        };
+        scheduler.configure(conf);
+
+        Ok(scheduler)
+    }
+
+    /// Apply the update rate (and frame synchronisation) settings of a (reloaded) config.
+    pub fn configure(&mut self, conf: &FreecamConfig) {
+        self.period = Duration::from_secs_f64(1.0 / conf.update_rate as f64);
+
+        #[cfg(feature = "vsync-to-game")]
+        {
+            let present_fn = conf
+                .present_fn_address
+                .filter(|_| conf.vsync_to_game && !conf.safe_mode);
+
+            if present_fn != self.frame_sync.as_ref().map(FrameSync::address) {
+                // Ensure the old hook is removed before installing a new one.
+                self.frame_sync = None;
+                self.frame_sync = present_fn.and_then(|address| unsafe {
+                    FrameSync::new(address)
+                        .map_err(|e| {
+                            crate::error_report::report(format!(
+                                "Failed to hook present function, using `update_rate`: {}",
+                                e
+                            ))
+                        })
+                        .ok()
+                });
+            }
+        }
+    }
+
+    /// Block until the next update should run.
+    pub fn wait(&mut self) {
+        // Input is already sampled once per rendered frame, which is as often as the camera can visibly respond.
+        #[cfg(feature = "vsync-to-game")]
+        if let Some(frame_sync) = &self.frame_sync {
+            // The game doesn't render while minimized, keep updating at a reduced rate in that case.
+            frame_sync.wait(self.period * 4);
+            self.next_tick = Instant::now();
+            return;
+        }
+
+        let woke_early = std::mem::take(&mut self.woke_early);
+        if !woke_early {
+            self.next_tick += self.period;
+        }
+        let now = Instant::now();
+
+        if self.next_tick <= now {
+            // We fell behind, don't try to catch up with a burst of updates.
+            self.next_tick = now;
+            return;
+        }
+
+        let remaining = self.next_tick - now;
+        // Negative due times are relative, in 100 nanosecond intervals.
+        let due_time = -((remaining.as_nanos() / 100) as i64);
+
+        unsafe {
+            if SetWaitableTimer(self.timer, &due_time, 0, None, None, false).is_err() {
+                std::thread::sleep(remaining);
+            } else if woke_early {
+                // A continuous stream of input (e.g. a free-spinning wheel) shouldn't double the update rate.
+                WaitForSingleObject(self.timer, INFINITE);
+            } else {
+                let woken = WaitForMultipleObjects(&[self.timer, self.input_event], false, INFINITE);
+                self.woke_early = woken == WAIT_EVENT(WAIT_OBJECT_0.0 + 1);
+            }
+        }
+    }
+}
+
+impl Drop for UpdateScheduler {
+    fn drop(&mut self) {
+        INPUT_EVENT.store(0, Ordering::SeqCst);
+        unsafe {
+            let _ = CloseHandle(self.timer);
+            let _ = CloseHandle(self.input_event);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+
+use rust_hooking_utils::patching::process::Window;
+use rust_hooking_utils::raw_input::key_manager::KeyState;
+use windows::Win32::Foundation::{HWND, POINT, RECT};
+use windows::Win32::Graphics::Gdi::{
+    BitBlt, ClientToScreen, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDC, GetDIBits,
+    ReleaseDC, SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, SRCCOPY,
+};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP,
+};
+use windows::Win32::UI::WindowsAndMessaging::GetClientRect;
+
+use crate::config::ScreenshotConfig;
+use crate::input::{InputManager, KeyChord};
+
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+/// Takes screenshots of the game window's client area, optionally hiding the HUD and adding letterbox bars.
+///
+/// Screenshots are taken on a separate thread, as we have to wait for the game to render a frame without its HUD.
+pub struct ScreenshotTaker {
+    main_window: HWND,
+    directory: PathBuf,
+    /// The screenshot currently being taken, joined on drop as it runs our code.
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ScreenshotTaker {
+    pub fn new(main_window: Window, config_directory: impl AsRef<Path>) -> Self {
+        Self {
+            main_window: main_window.0,
+            directory: config_directory.as_ref().join(SCREENSHOT_DIR),
+            thread: None,
+        }
+    }
+
+    /// Take a screenshot if the configured key was pressed.
+    pub fn update(&mut self, conf: &ScreenshotConfig, key_man: &mut InputManager) {
+        if let Some(key) = &conf.key {
+            if matches!(key_man.binding_state(key), KeyState::Pressed) {
+                self.take(conf);
+            }
+        }
+    }
+
+    /// Start taking a screenshot, ignored if one is already being taken.
+    pub fn take(&mut self, conf: &ScreenshotConfig) {
+        if self.thread.as_ref().is_some_and(|thread| !thread.is_finished()) {
+            return;
+        }
+
+        let hwnd = self.main_window.0;
+        let directory = self.directory.clone();
+        let conf = conf.clone();
+
+        self.thread = Some(std::thread::spawn(move || {
+            if let Some(chord) = &conf.hide_hud_chord {
+                send_chord(chord);
+                std::thread::sleep(conf.hide_hud_delay);
+            }
+
+            let result = unsafe { capture_client_area(HWND(hwnd)) }.and_then(|mut image| {
+                if let Some(aspect_ratio) = conf.letterbox_aspect_ratio {
+                    image.letterbox(aspect_ratio);
+                }
+                image.save_bmp(&directory)
+            });
+
+            if let Some(chord) = &conf.hide_hud_chord {
+                send_chord(chord);
+            }
+
+            match result {
+                Ok(path) => crate::overlay::notify(format!("Saved screenshot to: {:?}", path)),
+                Err(e) => log::error!("Failed to take screenshot: {}", e),
+            }
+        }));
+    }
+}
+
+impl Drop for ScreenshotTaker {
+    fn drop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A top-down 32-bit BGRA image.
+struct Screenshot {
+    width: i32,
+    height: i32,
+    pixels: Vec<u8>,
+}
+
+impl Screenshot {
+    /// Black out the top and bottom of the image until it has the given aspect ratio.
+    ///
+    /// Images which are already wider than the `aspect_ratio` are left as is.
+    fn letterbox(&mut self, aspect_ratio: f32) {
+        let visible_height = (self.width as f32 / aspect_ratio).round() as i32;
+        let bar_height = ((self.height - visible_height) / 2).max(0) as usize;
+        let row_size = self.width as usize * 4;
+
+        for row in (0..bar_height).chain(self.height as usize - bar_height..self.height as usize) {
+            for pixel in self.pixels[row * row_size..(row + 1) * row_size].chunks_exact_mut(4) {
+                pixel.copy_from_slice(&[0, 0, 0, 0xFF]);
+            }
+        }
+    }
+
+    fn save_bmp(&self, directory: &Path) -> anyhow::Result<PathBuf> {
+        const HEADER_SIZE: u32 = 14 + std::mem::size_of::<BITMAPINFOHEADER>() as u32;
+
+        std::fs::create_dir_all(directory)?;
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis();
+        let path = directory.join(format!("freecam_{}.bmp", timestamp));
+        let mut file = BufWriter::new(File::create(&path)?);
+
+        // BITMAPFILEHEADER
+        file.write_all(b"BM")?;
+        file.write_all(&(HEADER_SIZE + self.pixels.len() as u32).to_le_bytes())?;
+        file.write_all(&[0; 4])?;
+        file.write_all(&HEADER_SIZE.to_le_bytes())?;
+        // BITMAPINFOHEADER, negative height for a top-down image
+        file.write_all(&(std::mem::size_of::<BITMAPINFOHEADER>() as u32).to_le_bytes())?;
+        file.write_all(&self.width.to_le_bytes())?;
+        file.write_all(&(-self.height).to_le_bytes())?;
+        file.write_all(&1u16.to_le_bytes())?;
+        file.write_all(&32u16.to_le_bytes())?;
+        file.write_all(&BI_RGB.0.to_le_bytes())?;
+        file.write_all(&(self.pixels.len() as u32).to_le_bytes())?;
+        file.write_all(&[0; 16])?;
+
+        file.write_all(&self.pixels)?;
+        file.flush()?;
+
+        Ok(path)
+    }
+}
+
+/// Copy the window's client area from the screen.
+///
+/// Note that this doesn't work in exclusive fullscreen mode, where the result will be black.
+unsafe fn capture_client_area(hwnd: HWND) -> anyhow::Result<Screenshot> {
+    let mut rect = RECT::default();
+    GetClientRect(hwnd, &mut rect)?;
+    let mut origin = POINT::default();
+    let _ = ClientToScreen(hwnd, &mut origin);
+
+    let width = rect.right - rect.left;
+    let height = rect.bottom - rect.top;
+    anyhow::ensure!(width > 0 && height > 0, "Game window has no size");
+
+    let screen_dc = GetDC(HWND::default());
+    let memory_dc = CreateCompatibleDC(screen_dc);
+    let bitmap = CreateCompatibleBitmap(screen_dc, width, height);
+    let previous = SelectObject(memory_dc, bitmap);
+
+    let copied = BitBlt(memory_dc, 0, 0, width, height, screen_dc, origin.x, origin.y, SRCCOPY);
+    SelectObject(memory_dc, previous);
+
+    let mut info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width,
+            biHeight: -height,
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    let lines = GetDIBits(
+        memory_dc,
+        bitmap,
+        0,
+        height as u32,
+        Some(pixels.as_mut_ptr().cast()),
+        &mut info,
+        DIB_RGB_COLORS,
+    );
+
+    let _ = DeleteObject(bitmap);
+    let _ = DeleteDC(memory_dc);
+    ReleaseDC(HWND::default(), screen_dc);
+
+    copied?;
+    anyhow::ensure!(lines == height, "Failed to read screenshot pixels");
+
+    Ok(Screenshot { width, height, pixels })
+}
+
+/// Simulate pressing and releasing the given chord.
+fn send_chord(chord: &KeyChord) {
+    let key_input = |key: &rust_hooking_utils::raw_input::virtual_keys::VirtualKey, flags| INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: key.to_virtual_key(),
+                wScan: 0,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    };
+
+    let inputs = chord
+        .0
+        .iter()
+        .map(|key| key_input(key, KEYBD_EVENT_FLAGS(0)))
+        .chain(chord.0.iter().rev().map(|key| key_input(key, KEYEVENTF_KEYUP)))
+        .collect::<Vec<_>>();
+
+    unsafe {
+        SendInput(&inputs, std::mem::size_of::<INPUT>() as i32);
+    }
+}
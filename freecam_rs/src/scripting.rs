@@ -0,0 +1,454 @@
+//! User scripts controlling the camera, written in [Rhai](https://rhai.rs), see [ScriptHost].
+//!
+//! Every `.rhai` file in the `scripts` folder is loaded as its own script. Its top level runs once when it's loaded,
+//! after which the host calls the following functions, if the script defines them:
+//! * `init()`: once, after the top level ran.
+//! * `on_tick(dt)`: every update, with the seconds since the last update.
+//! * `on_phase(phase)`: whenever the battle phase changes, e.g. `"InBattle"`.
+//!
+//! Within these functions `this` is a map which is kept between calls, for the script's own state.
+//!
+//! Scripts can call:
+//! * `camera()`: the current camera as `#{x, y, z, pitch, yaw}`, or `()` outside of battles.
+//! * `set_camera(x, y, z, pitch, yaw)`: place our custom camera there immediately.
+//! * `teleport_camera(x, y, z, pitch, yaw)`: move our custom camera there like a unit card teleport.
+//! * `move_camera(forward, rotate, vertical)`: keep moving the camera as if the movement keys were held, each in the
+//!   range `-1..=1`. Pass zeroes to stop.
+//! * `battle_phase()`: the battle's state.
+//! * `units()`: all units as `#{x, y, z, melee_count, is_general}`, requires the `unit_list` in the `patch_profile`.
+//! * `bind_key(binding, callback)`: call the function named `callback` whenever the binding (e.g. `"VK_CONTROL+VK_O"`)
+//!   is pressed.
+//! * `after(seconds, callback)`, `every(seconds, callback)`: call the function named `callback` once, or repeatedly.
+//! * `notify(message)`: show a message on screen.
+//!
+//! All numbers passed to these functions are floats, e.g. `10.0` rather than `10`. Angles are in radians.
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST, FLOAT, INT};
+use rust_hooking_utils::raw_input::key_manager::KeyState;
+
+use crate::battle_cam::coords::WorldPos;
+use crate::battle_cam::{data, BattleCamera, BattlePhase, ScriptedInput};
+use crate::config::ScriptingConfig;
+use crate::error_report;
+use crate::input::{InputManager, KeyBinding};
+use crate::overlay;
+
+pub const SCRIPTS_DIRECTORY: &str = "scripts";
+const SCRIPT_EXTENSION: &str = "rhai";
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// Something a script asked for, applied by the host after the script function returned.
+#[derive(Debug)]
+enum ScriptCommand {
+    SetPose(WorldPos, f32, f32),
+    Teleport(WorldPos, f32, f32),
+    Move(ScriptedInput),
+    BindKey(KeyBinding, String),
+    Timer {
+        delay: Duration,
+        repeat: bool,
+        callback: String,
+    },
+    Notify(String),
+}
+
+/// The state shared with the functions registered on the [Engine].
+#[derive(Default)]
+struct ScriptContext {
+    /// The camera's `(position, pitch, yaw)` as of the start of the update, see [BattleCamera::camera_pose].
+    pose: Option<(WorldPos, f32, f32)>,
+    phase: Option<BattlePhase>,
+    commands: Vec<ScriptCommand>,
+}
+
+struct Timer {
+    due: Instant,
+    /// Set for `every` timers.
+    interval: Option<Duration>,
+    callback: String,
+}
+
+struct Script {
+    /// The file name, for log messages.
+    name: String,
+    ast: AST,
+    /// The script's `this` map.
+    state: Dynamic,
+    bindings: Vec<(KeyBinding, String)>,
+    timers: Vec<Timer>,
+}
+
+impl Script {
+    fn has_fn(&self, name: &str, arity: usize) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|function| function.name == name && function.params.len() == arity)
+    }
+}
+
+/// Loads the user's scripts and runs them from the main loop, see the [module docs](self) for the API.
+///
+/// A script which causes an error is reported and stopped, until the scripts are reloaded.
+pub struct ScriptHost {
+    engine: Engine,
+    context: Rc<RefCell<ScriptContext>>,
+    scripts: Vec<Script>,
+    last_phase: Option<BattlePhase>,
+}
+
+impl ScriptHost {
+    /// Load all scripts from the [SCRIPTS_DIRECTORY] in the `config_directory`, running their top level and `init()`.
+    pub fn new(conf: &ScriptingConfig, config_directory: impl AsRef<Path>) -> Self {
+        let context = Rc::new(RefCell::new(ScriptContext::default()));
+        let mut host = Self {
+            engine: create_engine(conf, &context),
+            context,
+            scripts: Vec::new(),
+            last_phase: None,
+        };
+
+        let directory = config_directory.as_ref().join(SCRIPTS_DIRECTORY);
+        for path in script_files(&directory) {
+            host.load(&path);
+        }
+
+        log::info!("Loaded {} script(s) from {:?}", host.scripts.len(), directory);
+        host
+    }
+
+    fn load(&mut self, path: &Path) {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let ast = match self.engine.compile_file(path.to_path_buf()) {
+            Ok(ast) => ast,
+            Err(e) => {
+                error_report::report(format!("Failed to compile script `{}`: {}", name, e));
+                return;
+            }
+        };
+
+        self.scripts.push(Script {
+            name,
+            ast,
+            state: Dynamic::from_map(Map::new()),
+            bindings: Vec::new(),
+            timers: Vec::new(),
+        });
+        let index = self.scripts.len() - 1;
+
+        let result = self.engine.run_ast(&self.scripts[index].ast).and_then(|_| {
+            self.apply_commands(index, None);
+            self.call_if_defined(index, "init", Vec::new())
+        });
+        self.apply_commands(index, None);
+
+        if let Err(e) = result {
+            let script = self.scripts.remove(index);
+            error_report::report(format!("Failed to initialise script `{}`: {}", script.name, e));
+        }
+    }
+
+    /// Call the scripts for everything which happened since the last update, and apply what they asked for.
+    pub fn update(&mut self, key_man: &mut InputManager, battle_cam: &mut BattleCamera, t_delta: Duration) {
+        if self.scripts.is_empty() {
+            return;
+        }
+
+        let phase = battle_cam.phase();
+        {
+            let mut context = self.context.borrow_mut();
+            context.pose = battle_cam.camera_pose();
+            context.phase = Some(phase);
+        }
+        let phase_changed = self.last_phase.replace(phase) != Some(phase);
+        let now = Instant::now();
+
+        let mut index = 0;
+        while index < self.scripts.len() {
+            let result = self.update_script(index, key_man, battle_cam, phase_changed.then_some(phase), now, t_delta);
+
+            match result {
+                Ok(()) => index += 1,
+                Err(e) => {
+                    // Anything the failed call asked for is discarded.
+                    self.context.borrow_mut().commands.clear();
+                    let script = self.scripts.remove(index);
+                    error_report::report(format!("Stopped script `{}` after an error: {}", script.name, e));
+                }
+            }
+        }
+    }
+
+    fn update_script(
+        &mut self,
+        index: usize,
+        key_man: &mut InputManager,
+        battle_cam: &mut BattleCamera,
+        new_phase: Option<BattlePhase>,
+        now: Instant,
+        t_delta: Duration,
+    ) -> ScriptResult<()> {
+        if let Some(phase) = new_phase {
+            self.call_if_defined(index, "on_phase", vec![format!("{:?}", phase).into()])?;
+            self.apply_commands(index, Some(&mut *battle_cam));
+        }
+
+        let pressed = self.scripts[index]
+            .bindings
+            .iter()
+            .filter(|(binding, _)| matches!(key_man.binding_state(binding), KeyState::Pressed))
+            .map(|(_, callback)| callback.clone())
+            .collect::<Vec<_>>();
+        for callback in pressed {
+            self.call(index, &callback, Vec::new())?;
+            self.apply_commands(index, Some(&mut *battle_cam));
+        }
+
+        for callback in self.take_due_timers(index, now) {
+            self.call(index, &callback, Vec::new())?;
+            self.apply_commands(index, Some(&mut *battle_cam));
+        }
+
+        let dt = Dynamic::from_float(t_delta.as_secs_f64() as FLOAT);
+        self.call_if_defined(index, "on_tick", vec![dt])?;
+        self.apply_commands(index, Some(&mut *battle_cam));
+
+        Ok(())
+    }
+
+    /// Remove the timers which are due, rescheduling the repeating ones, and return their callbacks.
+    fn take_due_timers(&mut self, index: usize, now: Instant) -> Vec<String> {
+        let timers = &mut self.scripts[index].timers;
+        let mut due = Vec::new();
+
+        timers.retain_mut(|timer| {
+            if timer.due > now {
+                return true;
+            }
+
+            due.push(timer.callback.clone());
+            match timer.interval {
+                Some(interval) => {
+                    timer.due = now + interval;
+                    true
+                }
+                None => false,
+            }
+        });
+
+        due
+    }
+
+    /// Call one of the optional functions, see the [module docs](self).
+    fn call_if_defined(&mut self, index: usize, name: &str, args: Vec<Dynamic>) -> ScriptResult<()> {
+        if !self.scripts[index].has_fn(name, args.len()) {
+            return Ok(());
+        }
+
+        self.call(index, name, args)
+    }
+
+    fn call(&mut self, index: usize, name: &str, args: Vec<Dynamic>) -> ScriptResult<()> {
+        let script = &mut self.scripts[index];
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut script.state);
+
+        self.engine
+            .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &script.ast, name, args)
+            .map(|_| ())
+    }
+
+    /// Apply the commands queued by the last call of the script at `index`.
+    ///
+    /// Only the key bindings and timers are applied if there's no `battle_cam` yet (i.e., while loading the script).
+    fn apply_commands(&mut self, index: usize, mut battle_cam: Option<&mut BattleCamera>) {
+        let commands = std::mem::take(&mut self.context.borrow_mut().commands);
+        let script = &mut self.scripts[index];
+
+        for command in commands {
+            match (command, battle_cam.as_deref_mut()) {
+                (ScriptCommand::BindKey(binding, callback), _) => {
+                    if !script.has_fn(&callback, 0) {
+                        log::warn!(
+                            "Script `{}` bound {:?} to `{}`, which doesn't exist",
+                            script.name,
+                            binding,
+                            callback
+                        );
+                    }
+                    script.bindings.push((binding, callback));
+                }
+                (
+                    ScriptCommand::Timer {
+                        delay,
+                        repeat,
+                        callback,
+                    },
+                    _,
+                ) => script.timers.push(Timer {
+                    due: Instant::now() + delay,
+                    interval: repeat.then_some(delay),
+                    callback,
+                }),
+                (ScriptCommand::Notify(message), _) => overlay::notify(message),
+                (ScriptCommand::SetPose(pos, pitch, yaw), Some(battle_cam)) => {
+                    battle_cam.set_camera_pose(pos, pitch, yaw);
+                }
+                (ScriptCommand::Teleport(pos, pitch, yaw), Some(battle_cam)) => {
+                    battle_cam.teleport_to(pos, pitch, yaw);
+                }
+                (ScriptCommand::Move(input), Some(battle_cam)) => battle_cam.set_scripted_input(input),
+                (command, None) => log::debug!("Script `{}` can't move the camera yet: {:?}", script.name, command),
+            }
+        }
+    }
+}
+
+/// All script files in the `directory`, sorted by name so they always run in the same order.
+fn script_files(directory: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let mut files = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case(SCRIPT_EXTENSION))
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+
+    files
+}
+
+fn create_engine(conf: &ScriptingConfig, context: &Rc<RefCell<ScriptContext>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(conf.max_operations);
+    engine.on_print(|text| log::info!("[script] {}", text));
+    engine.on_debug(|text, source, _| log::debug!("[script {}] {}", source.unwrap_or_default(), text));
+
+    let ctx = context.clone();
+    engine.register_fn("camera", move || -> Dynamic {
+        match ctx.borrow().pose {
+            Some((pos, pitch, yaw)) => {
+                let mut camera = position_map(pos);
+                camera.insert("pitch".into(), Dynamic::from_float(pitch as FLOAT));
+                camera.insert("yaw".into(), Dynamic::from_float(yaw as FLOAT));
+                Dynamic::from_map(camera)
+            }
+            None => Dynamic::UNIT,
+        }
+    });
+
+    let ctx = context.clone();
+    engine.register_fn(
+        "set_camera",
+        move |x: FLOAT, y: FLOAT, z: FLOAT, pitch: FLOAT, yaw: FLOAT| -> ScriptResult<()> {
+            let (pos, pitch, yaw) = to_pose(x, y, z, pitch, yaw)?;
+            let mut ctx = ctx.borrow_mut();
+            // Later calls of `camera()` should see the new pose as well.
+            if ctx.pose.is_some() {
+                ctx.pose = Some((pos, pitch, yaw));
+            }
+            ctx.commands.push(ScriptCommand::SetPose(pos, pitch, yaw));
+            Ok(())
+        },
+    );
+
+    let ctx = context.clone();
+    engine.register_fn(
+        "teleport_camera",
+        move |x: FLOAT, y: FLOAT, z: FLOAT, pitch: FLOAT, yaw: FLOAT| -> ScriptResult<()> {
+            let (pos, pitch, yaw) = to_pose(x, y, z, pitch, yaw)?;
+            ctx.borrow_mut().commands.push(ScriptCommand::Teleport(pos, pitch, yaw));
+            Ok(())
+        },
+    );
+
+    let ctx = context.clone();
+    engine.register_fn("move_camera", move |forward: FLOAT, rotate: FLOAT, vertical: FLOAT| {
+        let input = ScriptedInput {
+            forward: (forward as f32).clamp(-1., 1.),
+            rotate: (rotate as f32).clamp(-1., 1.),
+            vertical: (vertical as f32).clamp(-1., 1.),
+        };
+        ctx.borrow_mut().commands.push(ScriptCommand::Move(input));
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("battle_phase", move || -> String {
+        ctx.borrow()
+            .phase
+            .map(|phase| format!("{:?}", phase))
+            .unwrap_or_default()
+    });
+
+    engine.register_fn("units", || -> Array {
+        data::battle_units()
+            .into_iter()
+            .map(|unit| {
+                let mut map = position_map(unit.position);
+                map.insert("melee_count".into(), Dynamic::from_int(unit.melee_count as INT));
+                map.insert("is_general".into(), Dynamic::from_bool(unit.is_general));
+                Dynamic::from_map(map)
+            })
+            .collect()
+    });
+
+    let ctx = context.clone();
+    engine.register_fn("bind_key", move |binding: &str, callback: &str| -> ScriptResult<()> {
+        let binding = serde_json::from_value::<KeyBinding>(serde_json::Value::String(binding.to_string()))
+            .map_err(|e| format!("Invalid key binding `{}`: {}", binding, e))?;
+        ctx.borrow_mut()
+            .commands
+            .push(ScriptCommand::BindKey(binding, callback.to_string()));
+        Ok(())
+    });
+
+    for (name, repeat) in [("after", false), ("every", true)] {
+        let ctx = context.clone();
+        engine.register_fn(name, move |seconds: FLOAT, callback: &str| -> ScriptResult<()> {
+            let delay = Duration::try_from_secs_f64(seconds as f64)
+                .map_err(|_| format!("Invalid timer duration: {}", seconds))?;
+            ctx.borrow_mut().commands.push(ScriptCommand::Timer {
+                delay,
+                repeat,
+                callback: callback.to_string(),
+            });
+            Ok(())
+        });
+    }
+
+    let ctx = context.clone();
+    engine.register_fn("notify", move |message: &str| {
+        ctx.borrow_mut()
+            .commands
+            .push(ScriptCommand::Notify(message.to_string()));
+    });
+
+    engine
+}
+
+fn to_pose(x: FLOAT, y: FLOAT, z: FLOAT, pitch: FLOAT, yaw: FLOAT) -> ScriptResult<(WorldPos, f32, f32)> {
+    if ![x, y, z, pitch, yaw].iter().all(|v| v.is_finite()) {
+        return Err("All camera values should be finite".into());
+    }
+
+    Ok((WorldPos::new(x as f32, y as f32, z as f32), pitch as f32, yaw as f32))
+}
+
+fn position_map(pos: WorldPos) -> Map {
+    let mut map = Map::new();
+    map.insert("x".into(), Dynamic::from_float(pos.x as FLOAT));
+    map.insert("y".into(), Dynamic::from_float(pos.y as FLOAT));
+    map.insert("z".into(), Dynamic::from_float(pos.z as FLOAT));
+    map
+}
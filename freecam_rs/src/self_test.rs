@@ -0,0 +1,250 @@
+//! A scripted camera sequence run once at the start of every battle, see [crate::config::FreecamConfig::self_test].
+//!
+//! Meant for validating a new `patch_profile` or game version without having to play: every step drives the custom
+//! camera like the user would, and logs the camera's state along the way.
+
+use std::f32::consts::TAU;
+use std::time::{Duration, Instant};
+
+use crate::battle_cam::coords::{angle_difference, WorldPos};
+use crate::battle_cam::{BattleCamera, BattlePhase, ScriptedInput};
+use crate::overlay;
+
+/// How often the camera's state is logged while a step runs.
+const LOG_INTERVAL: Duration = Duration::from_millis(250);
+/// How long the camera has to stay put for [Step::ZoomToGround] to consider the ground reached.
+const GROUND_SETTLE_TIME: Duration = Duration::from_millis(500);
+/// How close the camera has to get to the teleport destination.
+const TELEPORT_TOLERANCE: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    /// Let the camera come to rest, and remember where it started.
+    Settle,
+    MoveForward,
+    /// Rotate a full circle.
+    Rotate,
+    /// Descend until the ground clipping prevention stops the camera.
+    ZoomToGround,
+    /// Teleport back to where the camera started.
+    Teleport,
+}
+
+const STEPS: [Step; 5] = [
+    Step::Settle,
+    Step::MoveForward,
+    Step::Rotate,
+    Step::ZoomToGround,
+    Step::Teleport,
+];
+
+impl Step {
+    /// How long the step may take before it's considered failed, or just runs for if it has no goal.
+    fn timeout(self) -> Duration {
+        match self {
+            Step::Settle => Duration::from_secs(1),
+            Step::MoveForward => Duration::from_secs(2),
+            Step::Rotate => Duration::from_secs(15),
+            Step::ZoomToGround => Duration::from_secs(10),
+            Step::Teleport => Duration::from_secs(5),
+        }
+    }
+
+    fn input(self) -> ScriptedInput {
+        match self {
+            Step::Settle | Step::Teleport => ScriptedInput::default(),
+            Step::MoveForward => ScriptedInput {
+                forward: 1.,
+                ..Default::default()
+            },
+            Step::Rotate => ScriptedInput {
+                rotate: 1.,
+                ..Default::default()
+            },
+            Step::ZoomToGround => ScriptedInput {
+                vertical: -1.,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Runs the [STEPS] once per battle while enabled.
+#[derive(Default)]
+pub struct SelfTest {
+    run: Option<Run>,
+    /// Set once the sequence ran (or was aborted) during the current battle.
+    finished: bool,
+}
+
+struct Run {
+    step: usize,
+    step_started: Instant,
+    last_log: Option<Instant>,
+    /// The camera pose at the end of [Step::Settle].
+    start: (WorldPos, f32, f32),
+    /// The camera pose at the start of the current step.
+    step_start: (WorldPos, f32, f32),
+    last_pose: (WorldPos, f32, f32),
+    /// The total rotation during [Step::Rotate].
+    rotated: f32,
+    /// When the camera last moved down during [Step::ZoomToGround].
+    last_descent: Instant,
+    passed: Vec<(Step, bool)>,
+}
+
+impl SelfTest {
+    /// Start the sequence once our custom camera is in control of a new battle, and advance it every update.
+    pub fn update(&mut self, enabled: bool, battle_cam: &mut BattleCamera) {
+        if battle_cam.phase() == BattlePhase::OutsideBattle {
+            self.run = None;
+            self.finished = false;
+            return;
+        }
+        if !enabled {
+            if self.run.take().is_some() {
+                battle_cam.set_scripted_input(ScriptedInput::default());
+                overlay::notify("Self test cancelled");
+            }
+            return;
+        }
+        if self.finished {
+            return;
+        }
+
+        let Some(pose) = battle_cam.camera_pose() else {
+            return;
+        };
+
+        if self.run.is_none() {
+            if !battle_cam.is_custom_camera_active() {
+                return;
+            }
+
+            overlay::notify("Starting self test");
+            battle_cam.set_scripted_input(STEPS[0].input());
+            self.run = Some(Run {
+                step: 0,
+                step_started: Instant::now(),
+                last_log: None,
+                start: pose,
+                step_start: pose,
+                last_pose: pose,
+                rotated: 0.,
+                last_descent: Instant::now(),
+                passed: Vec::new(),
+            });
+        }
+        let Some(run) = &mut self.run else {
+            return;
+        };
+
+        let step = STEPS[run.step];
+        run.log_pose(step, pose);
+
+        if let Some(passed) = run.evaluate(step, pose) {
+            log::info!(
+                "Self test step {:?} {} after {:?}",
+                step,
+                if passed { "passed" } else { "FAILED" },
+                run.step_started.elapsed()
+            );
+            run.passed.push((step, passed));
+            run.step += 1;
+
+            match STEPS.get(run.step) {
+                Some(&next) => {
+                    run.step_started = Instant::now();
+                    run.last_log = None;
+                    run.step_start = pose;
+                    run.rotated = 0.;
+                    run.last_descent = Instant::now();
+                    battle_cam.set_scripted_input(next.input());
+
+                    if next == Step::Teleport {
+                        let (pos, pitch, yaw) = run.start;
+                        battle_cam.teleport_to(pos, pitch, yaw);
+                    }
+                }
+                None => {
+                    battle_cam.set_scripted_input(ScriptedInput::default());
+                    let passed = run.passed.iter().filter(|(_, passed)| *passed).count();
+                    log::info!("Self test results: {:?}", run.passed);
+                    overlay::notify(format!("Self test finished, {}/{} steps passed", passed, STEPS.len()));
+
+                    self.run = None;
+                    self.finished = true;
+                    return;
+                }
+            }
+        }
+
+        run.last_pose = pose;
+    }
+}
+
+impl Run {
+    fn log_pose(&mut self, step: Step, (pos, pitch, yaw): (WorldPos, f32, f32)) {
+        if self.last_log.is_some_and(|last| last.elapsed() < LOG_INTERVAL) {
+            return;
+        }
+        self.last_log = Some(Instant::now());
+
+        log::info!(
+            "Self test {:?} +{:.2}s: pos ({:.2}, {:.2}, {:.2}), pitch {:.3}, yaw {:.3}",
+            step,
+            self.step_started.elapsed().as_secs_f32(),
+            pos.x,
+            pos.y,
+            pos.z,
+            pitch,
+            yaw
+        );
+    }
+
+    /// Whether the current `step` passed, `None` while it's still running.
+    fn evaluate(&mut self, step: Step, pose: (WorldPos, f32, f32)) -> Option<bool> {
+        let timed_out = self.step_started.elapsed() >= step.timeout();
+        let (pos, _, yaw) = pose;
+
+        match step {
+            Step::Settle => timed_out.then(|| {
+                self.start = pose;
+                true
+            }),
+            Step::MoveForward => {
+                let (start, _, _) = self.step_start;
+                let moved = WorldPos::new(pos.x, pos.y, start.z).distance(&start);
+
+                timed_out.then_some(moved > TELEPORT_TOLERANCE)
+            }
+            Step::Rotate => {
+                self.rotated += angle_difference(self.last_pose.2, yaw);
+
+                if self.rotated.abs() >= TAU {
+                    Some(true)
+                } else {
+                    timed_out.then_some(false)
+                }
+            }
+            Step::ZoomToGround => {
+                if pos.z < self.last_pose.0.z - 0.01 {
+                    self.last_descent = Instant::now();
+                }
+
+                if self.last_descent.elapsed() >= GROUND_SETTLE_TIME {
+                    Some(pos.z < self.step_start.0.z)
+                } else {
+                    timed_out.then_some(false)
+                }
+            }
+            Step::Teleport => {
+                if pos.distance(&self.start.0) < TELEPORT_TOLERANCE {
+                    Some(true)
+                } else {
+                    timed_out.then_some(false)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Publishes the camera's state to a named shared memory section, see [FreecamConfig::shared_memory_telemetry].
+//!
+//! Meant for OBS plugins and overlays which want to show camera telemetry during capture. The section is named
+//! [SECTION_NAME] and contains a single [CameraTelemetry].
+//!
+//! [FreecamConfig::shared_memory_telemetry]: crate::config::FreecamConfig::shared_memory_telemetry
+
+use std::sync::atomic::{fence, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use windows::core::HSTRING;
+use windows::Win32::Foundation::{CloseHandle, HANDLE, INVALID_HANDLE_VALUE};
+use windows::Win32::System::Memory::{
+    CreateFileMappingW, MapViewOfFile, UnmapViewOfFile, FILE_MAP_WRITE, MEMORY_MAPPED_VIEW_ADDRESS, PAGE_READWRITE,
+};
+
+use crate::battle_cam::{BattleCamera, BattlePhase};
+
+/// The name of the shared memory section, in the session-local namespace.
+pub const SECTION_NAME: &str = "Local\\med2_freecam_telemetry";
+/// Always the first 4 bytes of the section, `b"M2FC"` in little endian.
+pub const TELEMETRY_MAGIC: u32 = u32::from_le_bytes(*b"M2FC");
+/// Incremented on any incompatible change to [CameraTelemetry]. Fields are only ever appended.
+pub const TELEMETRY_VERSION: u32 = 1;
+
+/// The layout of the shared memory section.
+///
+/// Readers should read `sequence`, copy the struct, and read `sequence` again. If it changed or is odd the copy was
+/// torn by a concurrent update and should be retried.
+#[derive(Debug, Clone, Copy, Default)]
+#[repr(C)]
+pub struct CameraTelemetry {
+    /// Always [TELEMETRY_MAGIC].
+    pub magic: u32,
+    /// Always [TELEMETRY_VERSION].
+    pub version: u32,
+    /// Odd while an update is being written.
+    pub sequence: u32,
+    /// `0` outside of battle, `1` loading, `2` deployment, `3` in battle, `4` after the battle has ended.
+    pub phase: u32,
+    /// `1` if the freecam is enabled, see [crate::config::FreecamConfig::toggle_freecam_keys].
+    pub freecam_enabled: u32,
+    /// `1` if our custom camera (rather than the game) currently controls the camera.
+    pub custom_camera_active: u32,
+    /// Milliseconds since the Unix epoch of this update.
+    pub timestamp_ms: u64,
+    /// Milliseconds since the Unix epoch of the last update during a battle, `0` if there hasn't been one yet.
+    pub last_battle_timestamp_ms: u64,
+    /// The camera position, with `z` being the altitude. All `0.0` outside of battle.
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    /// The camera's pitch and yaw in radians.
+    pub pitch: f32,
+    pub yaw: f32,
+}
+
+pub struct TelemetryPublisher {
+    mapping: HANDLE,
+    view: MEMORY_MAPPED_VIEW_ADDRESS,
+    telemetry: CameraTelemetry,
+}
+
+impl TelemetryPublisher {
+    /// Create (or open, if a reader created it first) the shared memory section.
+    pub fn new() -> anyhow::Result<Self> {
+        unsafe {
+            let size = std::mem::size_of::<CameraTelemetry>() as u32;
+            let mapping = CreateFileMappingW(
+                INVALID_HANDLE_VALUE,
+                None,
+                PAGE_READWRITE,
+                0,
+                size,
+                &HSTRING::from(SECTION_NAME),
+            )?;
+            let view = MapViewOfFile(mapping, FILE_MAP_WRITE, 0, 0, size as usize);
+
+            if view.Value.is_null() {
+                let error = windows::core::Error::from_win32();
+                let _ = CloseHandle(mapping);
+                return Err(error.into());
+            }
+
+            log::info!(
+                "Publishing camera telemetry to shared memory section: {:?}",
+                SECTION_NAME
+            );
+
+            Ok(Self {
+                mapping,
+                view,
+                telemetry: CameraTelemetry {
+                    magic: TELEMETRY_MAGIC,
+                    version: TELEMETRY_VERSION,
+                    ..Default::default()
+                },
+            })
+        }
+    }
+
+    /// Publish the current state of the `battle_cam`.
+    pub fn publish(&mut self, battle_cam: &mut BattleCamera) {
+        let telemetry = &mut self.telemetry;
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |t| t.as_millis() as u64);
+
+        telemetry.timestamp_ms = timestamp_ms;
+        telemetry.phase = match battle_cam.phase() {
+            BattlePhase::OutsideBattle => 0,
+            BattlePhase::Loading => 1,
+            BattlePhase::Deployment => 2,
+            BattlePhase::InBattle => 3,
+            BattlePhase::BattleEnded => 4,
+        };
+        telemetry.freecam_enabled = battle_cam.is_enabled() as u32;
+        telemetry.custom_camera_active = battle_cam.is_custom_camera_active() as u32;
+
+        if battle_cam.phase() != BattlePhase::OutsideBattle {
+            telemetry.last_battle_timestamp_ms = timestamp_ms;
+        }
+
+        let (pos, pitch, yaw) = battle_cam.camera_pose().unwrap_or_default();
+        telemetry.x = pos.x;
+        telemetry.y = pos.y;
+        telemetry.z = pos.z;
+        telemetry.pitch = pitch;
+        telemetry.yaw = yaw;
+
+        unsafe { self.write() }
+    }
+
+    /// Write our copy of the telemetry to the section, bracketed by `sequence` updates for readers to detect tearing.
+    unsafe fn write(&mut self) {
+        let shared = self.view.Value.cast::<CameraTelemetry>();
+        let sequence = std::ptr::addr_of_mut!((*shared).sequence);
+
+        let writing = self.telemetry.sequence.wrapping_add(1);
+        sequence.write_volatile(writing);
+        fence(Ordering::Release);
+
+        shared.write_volatile(CameraTelemetry {
+            sequence: writing,
+            ..self.telemetry
+        });
+        fence(Ordering::Release);
+
+        self.telemetry.sequence = writing.wrapping_add(1);
+        sequence.write_volatile(self.telemetry.sequence);
+    }
+}
+
+impl Drop for TelemetryPublisher {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = UnmapViewOfFile(self.view);
+            let _ = CloseHandle(self.mapping);
+        }
+    }
+}
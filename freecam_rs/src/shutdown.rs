@@ -0,0 +1,125 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use once_cell::sync::OnceCell;
+use windows::Win32::System::Diagnostics::Debug::{
+    FlushInstructionCache, SetUnhandledExceptionFilter, EXCEPTION_CONTINUE_SEARCH, EXCEPTION_POINTERS,
+    LPTOP_LEVEL_EXCEPTION_FILTER,
+};
+use windows::Win32::System::Memory::{VirtualProtect, PAGE_EXECUTE_READWRITE, PAGE_PROTECTION_FLAGS};
+use windows::Win32::System::Threading::GetCurrentProcess;
+use windows::Win32::UI::WindowsAndMessaging::{UnhookWindowsHookEx, HHOOK};
+
+/// A patched region of the game's code, and the bytes it contained before we touched it.
+struct PatchedRegion {
+    address: usize,
+    original: Vec<u8>,
+}
+
+/// Every region of game code we've ever patched, see [track_patch].
+static PATCHED_REGIONS: Mutex<Vec<PatchedRegion>> = Mutex::new(Vec::new());
+/// The raw handles of all currently installed Windows hooks, see [register_hook].
+static HOOKS: Mutex<Vec<isize>> = Mutex::new(Vec::new());
+/// Set once [restore_all] ran, after which our patches and hooks are gone for good.
+static RESTORED: AtomicBool = AtomicBool::new(false);
+static PREVIOUS_CRASH_FILTER: OnceCell<LPTOP_LEVEL_EXCEPTION_FILTER> = OnceCell::new();
+
+/// Remember the current (original) bytes at `address`, so they can be restored by [restore_all].
+///
+/// Should be called right before handing a patch to a `LocalPatcher`. The `LocalPatcher`s restore their patches when
+/// dropped, but that never happens if the game exits (or crashes) while our update thread is running, as Windows kills
+/// the thread outright.
+///
+/// # Safety
+/// `address..address + len` must be readable.
+pub unsafe fn track_patch(address: *const u8, len: usize) {
+    let mut regions = PATCHED_REGIONS.lock().unwrap_or_else(|e| e.into_inner());
+
+    // Patches are created anew for every battle, but the first recording is the one containing the game's code.
+    if regions.iter().any(|region| region.address == address as usize) {
+        return;
+    }
+
+    regions.push(PatchedRegion {
+        address: address as usize,
+        original: std::slice::from_raw_parts(address, len).to_vec(),
+    });
+}
+
+/// The bytes [track_patch] recorded at `address` before we first patched it, if any.
+pub fn original_bytes(address: *const u8) -> Option<Vec<u8>> {
+    let regions = PATCHED_REGIONS.lock().unwrap_or_else(|e| e.into_inner());
+
+    regions
+        .iter()
+        .find(|region| region.address == address as usize)
+        .map(|region| region.original.clone())
+}
+
+/// Register a Windows hook which should be removed by [restore_all] if we don't get the chance to do so ourselves.
+pub fn register_hook(hook: HHOOK) {
+    HOOKS.lock().unwrap_or_else(|e| e.into_inner()).push(hook.0);
+}
+
+/// Unregister a hook which is about to be removed by its owner.
+///
+/// Returns `false` if [restore_all] already removed it, in which case it mustn't be unhooked again.
+pub fn unregister_hook(hook: HHOOK) -> bool {
+    let mut hooks = HOOKS.lock().unwrap_or_else(|e| e.into_inner());
+    let before = hooks.len();
+    hooks.retain(|&h| h != hook.0);
+
+    hooks.len() != before
+}
+
+/// Install a handler which calls [restore_all] when the game is about to crash.
+///
+/// This is an unhandled exception filter rather than a vectored exception handler, as the latter also sees all the
+/// exceptions the game handles itself. The previous filter (if any) is still called afterwards.
+pub fn install_crash_handler() {
+    PREVIOUS_CRASH_FILTER.get_or_init(|| unsafe { SetUnhandledExceptionFilter(Some(crash_filter)) });
+}
+
+/// Restore all game code we patched and remove our Windows hooks, regardless of which thread owns them.
+///
+/// Only does anything the first time it's called. Meant for when the process exits or crashes, in which case our
+/// update thread might have been killed (possibly while holding a lock). We therefore never block on our own locks, and
+/// don't log either, as the logger's lock could be held by that thread just the same.
+pub unsafe fn restore_all() {
+    if RESTORED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    if let Ok(regions) = PATCHED_REGIONS.try_lock() {
+        for region in regions.iter() {
+            let _ = restore_region(region);
+        }
+    }
+
+    if let Ok(mut hooks) = HOOKS.try_lock() {
+        for hook in hooks.drain(..) {
+            let _ = UnhookWindowsHookEx(HHOOK(hook));
+        }
+    }
+}
+
+unsafe fn restore_region(region: &PatchedRegion) -> windows::core::Result<()> {
+    let address = region.address as *const std::ffi::c_void;
+    let len = region.original.len();
+    let mut old_protection = PAGE_PROTECTION_FLAGS::default();
+
+    VirtualProtect(address, len, PAGE_EXECUTE_READWRITE, &mut old_protection)?;
+    std::ptr::copy_nonoverlapping(region.original.as_ptr(), region.address as *mut u8, len);
+    VirtualProtect(address, len, old_protection, &mut old_protection)?;
+
+    FlushInstructionCache(GetCurrentProcess(), Some(address), len)
+}
+
+unsafe extern "system" fn crash_filter(exception: *const EXCEPTION_POINTERS) -> i32 {
+    restore_all();
+
+    match PREVIOUS_CRASH_FILTER.get().copied().flatten() {
+        Some(previous) => previous(exception),
+        None => EXCEPTION_CONTINUE_SEARCH,
+    }
+}
@@ -0,0 +1,206 @@
+use std::time::Instant;
+
+use rust_hooking_utils::patching::process::Window;
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+use windows::Win32::System::Threading::GetCurrentProcessId;
+use windows::Win32::UI::WindowsAndMessaging::{
+    EnumWindows, GetWindowTextW, GetWindowThreadProcessId, IsIconic, IsWindow, IsWindowVisible,
+};
+
+use crate::battle_cam::BattleCamera;
+use crate::config::WindowConfig;
+use crate::mouse::MouseManager;
+
+/// How the camera should be updated during a tick, see [WindowStateTracker::update].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdatePolicy {
+    /// Don't update the camera at all.
+    Paused,
+    /// Update the camera, but ignore the user's input as it's meant for another window.
+    Background,
+    /// Update the camera as normal.
+    Foreground,
+}
+
+/// Tracks minimize/restore transitions, focus changes, and resizes of the game's main window, applying the configured
+/// [WindowConfig] behaviour.
+pub struct WindowStateTracker {
+    main_window: Window,
+    was_minimized: bool,
+    was_foreground: bool,
+    restored_at: Option<Instant>,
+}
+
+impl WindowStateTracker {
+    pub fn new(main_window: Window) -> Self {
+        Self {
+            main_window,
+            was_minimized: false,
+            was_foreground: true,
+            restored_at: None,
+        }
+    }
+
+    /// Handle any minimize/restore transitions, focus changes, and resizes since the last call.
+    ///
+    /// Returns how the camera should be updated this tick.
+    pub fn update(
+        &mut self,
+        conf: &WindowConfig,
+        battle_cam: &mut BattleCamera,
+        mouse: &mut MouseManager,
+    ) -> UpdatePolicy {
+        let minimized = unsafe { IsIconic(self.main_window.0).as_bool() };
+
+        if minimized && !self.was_minimized {
+            log::debug!("Game window minimized");
+
+            if !conf.minimize_keep_patches {
+                // Patches will be re-applied as soon as the user provides input again.
+                battle_cam.set_custom_camera(false);
+            }
+
+            if conf.minimize_release_cursor {
+                mouse.show_cursor();
+                mouse.release_cursor_clip();
+            }
+        } else if !minimized && self.was_minimized {
+            log::debug!("Game window restored");
+            self.restored_at = Some(Instant::now());
+
+            if conf.restore_resync_camera {
+                battle_cam.resync_camera();
+            }
+        }
+
+        self.was_minimized = minimized;
+
+        let foreground = self.main_window.is_foreground_window();
+        if !foreground && self.was_foreground {
+            log::debug!("Game window lost focus");
+            // Keys released while alt-tabbed are never seen, so the freecam key (or a movement key) could otherwise
+            // stay held down: never keep looking around, the cursor hidden and trapped, or the camera drifting.
+            battle_cam.end_mouse_look(mouse);
+            mouse.show_cursor();
+            mouse.release_cursor_clip();
+            mouse.reset_scroll();
+            battle_cam.resync_camera();
+        } else if foreground && !self.was_foreground {
+            log::debug!("Game window regained focus");
+            // The game (or a teleport) may have moved the camera in the meantime.
+            battle_cam.resync_camera();
+        }
+
+        self.was_foreground = foreground;
+
+        if let Some(client_rect) = mouse.take_window_change() {
+            log::debug!("Game window resized or moved, client area is now {:?}", client_rect);
+            battle_cam.fit_cursor_to_window(client_rect);
+        }
+
+        if minimized {
+            return if conf.minimize_pause_updates { UpdatePolicy::Paused } else { UpdatePolicy::Background };
+        }
+
+        // Give the game some time to settle after a restore before we take control again.
+        if let Some(restored_at) = self.restored_at {
+            if restored_at.elapsed() < conf.restore_grace_period {
+                return UpdatePolicy::Paused;
+            }
+
+            self.restored_at = None;
+        }
+
+        if foreground {
+            UpdatePolicy::Foreground
+        } else if conf.background_updates {
+            UpdatePolicy::Background
+        } else {
+            UpdatePolicy::Paused
+        }
+    }
+}
+
+/// Find the game's main window: the visible top-level window of our own process whose title matches the
+/// `title_pattern`, see [WindowConfig::title_pattern].
+pub fn find_main_window(title_pattern: &str) -> Option<Window> {
+    find_window(title_pattern, Some(unsafe { GetCurrentProcessId() })).map(Window)
+}
+
+/// Find a visible top-level window whose title matches the `title_pattern` (see [title_matches]), owned by the process
+/// with the given `pid` if any.
+pub fn find_window(title_pattern: &str, pid: Option<u32>) -> Option<HWND> {
+    struct Search<'a> {
+        title_pattern: &'a str,
+        pid: Option<u32>,
+        found: Option<HWND>,
+    }
+
+    unsafe extern "system" fn visit(window: HWND, search: LPARAM) -> BOOL {
+        let search = &mut *(search.0 as *mut Search);
+        if !IsWindowVisible(window).as_bool() {
+            return true.into();
+        }
+
+        if let Some(pid) = search.pid {
+            let mut owner = 0;
+            GetWindowThreadProcessId(window, Some(&mut owner));
+            if owner != pid {
+                return true.into();
+            }
+        }
+
+        let mut title = [0u16; 256];
+        let len = GetWindowTextW(window, &mut title).max(0) as usize;
+        if title_matches(&String::from_utf16_lossy(&title[..len]), search.title_pattern) {
+            search.found = Some(window);
+            // Stop enumerating.
+            return false.into();
+        }
+
+        true.into()
+    }
+
+    let mut search = Search {
+        title_pattern,
+        pid,
+        found: None,
+    };
+    // Reports an error when the enumeration is stopped early, which is exactly when we found our window.
+    let _ = unsafe { EnumWindows(Some(visit), LPARAM(&mut search as *mut Search as isize)) };
+
+    search.found
+}
+
+/// Whether the window still exists, it's destroyed when the game exits (or recreates its window).
+pub fn is_window_alive(window: Window) -> bool {
+    unsafe { IsWindow(window.0).as_bool() }
+}
+
+/// Whether the `title` matches the `pattern`, ignoring case. A `*` in the `pattern` matches any (possibly empty) text.
+pub fn title_matches(title: &str, pattern: &str) -> bool {
+    let title = title.to_lowercase().chars().collect::<Vec<_>>();
+    let pattern = pattern.to_lowercase().chars().collect::<Vec<_>>();
+    let (mut t, mut p) = (0, 0);
+    // The position after the last `*` in the pattern, and the title position it was last tried at.
+    let mut last_wildcard = None;
+
+    while t < title.len() {
+        if pattern.get(p) == Some(&'*') {
+            p += 1;
+            last_wildcard = Some((p, t));
+        } else if pattern.get(p) == Some(&title[t]) {
+            p += 1;
+            t += 1;
+        } else if let Some((after_wildcard, tried_at)) = last_wildcard {
+            // Let the last `*` swallow one more character and try again.
+            p = after_wildcard;
+            t = tried_at + 1;
+            last_wildcard = Some((after_wildcard, t));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}